@@ -0,0 +1,391 @@
+// SPDX-License-Identifier: ISC
+//! Command-line admin tool for the casino program.
+//!
+//! The commands build their instructions with `casino_game::client` and read
+//! accounts through its fetch helpers, so the tool exercises the same code
+//! paths as the services and the program tests.
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use casino_game::{
+    client::{self, AccountFetcher, FetchError},
+    randomness, CasinoState, GameState, ProgramAccount, ACCOUNT_HEADER_LEN, ROLE_ALL, ROLE_PARAMS,
+    ROLE_PAUSE, ROLE_SETTLE,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_account_decoder::UiAccountEncoding;
+use solana_program::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::Error as ClientError,
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::transaction::Transaction;
+use std::{fmt::Debug, path::PathBuf};
+use thiserror::Error;
+
+// Test module
+#[cfg(test)]
+mod test;
+
+/// Admin tool for the Casino Game program
+#[derive(Parser, Debug)]
+#[command(name = "casino", version)]
+pub struct Cli {
+    /// JSON-RPC URL of the cluster
+    #[arg(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    pub url: String,
+
+    /// Keypair file of the signer [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', global = true)]
+    pub keypair: Option<PathBuf>,
+
+    /// Casino state address [default: the casino created by the signer]
+    #[arg(long, global = true)]
+    pub casino: Option<Pubkey>,
+
+    /// Print the signed transaction, base64 encoded, instead of sending it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Sign with this blockhash instead of fetching the latest one
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a casino owned by the signer
+    Init {
+        /// House edge in basis points (e.g., 250 = 2.5%)
+        #[arg(long, default_value_t = 250)]
+        house_edge: u16,
+
+        /// Minimum bet amount in lamports (or token base units)
+        #[arg(long)]
+        min_bet: u64,
+
+        /// Maximum bet amount in lamports (or token base units)
+        #[arg(long)]
+        max_bet: u64,
+
+        /// Run the casino on this SPL token instead of SOL
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+
+    /// Change the casino's parameters
+    UpdateParams {
+        /// New house edge in basis points
+        #[arg(long)]
+        house_edge: Option<u16>,
+
+        /// New minimum bet amount
+        #[arg(long)]
+        min_bet: Option<u64>,
+
+        /// New maximum bet amount
+        #[arg(long)]
+        max_bet: Option<u64>,
+
+        /// New settlement timeout in seconds
+        #[arg(long)]
+        settle_timeout: Option<i64>,
+    },
+
+    /// Add an operator, or change the roles and payout cap of an existing one
+    AddOperator {
+        operator: Pubkey,
+
+        /// Roles to grant
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "settle")]
+        roles: Vec<Role>,
+
+        /// Most the operator may pay out in wins per UTC day
+        #[arg(long)]
+        daily_payout_cap: u64,
+    },
+
+    /// Remove an operator
+    RemoveOperator {
+        operator: Pubkey,
+    },
+
+    /// Settle a game by revealing its server seed
+    Settle {
+        game: Pubkey,
+
+        /// The server seed committed to when the bet was placed, hex encoded
+        #[arg(long, value_parser = parse_seed)]
+        server_seed: [u8; 32],
+    },
+
+    /// Show the casino's state
+    ShowCasino,
+
+    /// Show a game
+    ShowGame {
+        game: Pubkey,
+    },
+
+    /// List the casino's games
+    ListGames {
+        /// Only list this player's games
+        #[arg(long)]
+        player: Option<Pubkey>,
+
+        /// Only list games that haven't been settled
+        #[arg(long)]
+        unsettled: bool,
+    },
+}
+
+/// Operator roles
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Settle,
+    Pause,
+    Params,
+    All,
+}
+
+impl Role {
+    /// The `ROLE_*` bitmask of a set of roles
+    pub fn mask(roles: &[Role]) -> u8 {
+        roles.iter().fold(0, |mask, role| {
+            mask | match role {
+                Role::Settle => ROLE_SETTLE,
+                Role::Pause => ROLE_PAUSE,
+                Role::Params => ROLE_PARAMS,
+                Role::All => ROLE_ALL,
+            }
+        })
+    }
+}
+
+/// Errors the commands can return
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("{0}")]
+    Fetch(String),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Game {0} is already settled")]
+    AlreadySettled(Pubkey),
+
+    #[error("The server seed does not match the game's commitment")]
+    WrongServerSeed,
+
+    #[error("The payout overflows")]
+    PayoutOverflow,
+
+    #[error("Could not encode the transaction: {0}")]
+    Encoding(String),
+}
+
+impl<E: Debug> From<FetchError<E>> for CliError {
+    fn from(err: FetchError<E>) -> Self {
+        CliError::Fetch(err.to_string())
+    }
+}
+
+impl From<ClientError> for CliError {
+    fn from(err: ClientError) -> Self {
+        CliError::Rpc(err.to_string())
+    }
+}
+
+/// Reads accounts through a nonblocking `RpcClient`
+pub struct RpcFetcher(pub RpcClient);
+
+impl AccountFetcher for RpcFetcher {
+    type Error = ClientError;
+
+    async fn get_account_data(&mut self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, Self::Error> {
+        let response = self.0.get_account_with_commitment(address, self.0.commitment()).await?;
+        Ok(response.value.map(|account| (account.owner, account.data)))
+    }
+}
+
+/// Builds the instructions of a command signed by `signer`. The read-only
+/// commands need none.
+pub async fn instructions<F: AccountFetcher>(
+    fetcher: &mut F,
+    signer: &Pubkey,
+    casino: &Pubkey,
+    command: &Command,
+) -> Result<Vec<Instruction>, CliError>
+where
+    F::Error: Debug,
+{
+    let instruction = match command {
+        Command::Init { house_edge, min_bet, max_bet, mint } => match mint {
+            Some(mint) => client::initialize_with_mint(signer, casino, mint, *house_edge, *min_bet, *max_bet),
+            None => client::initialize(signer, casino, *house_edge, *min_bet, *max_bet),
+        },
+        Command::UpdateParams { house_edge, min_bet, max_bet, settle_timeout } => {
+            client::update_params(signer, casino, *house_edge, *min_bet, *max_bet, *settle_timeout)
+        },
+        Command::AddOperator { operator, roles, daily_payout_cap } => {
+            client::add_operator(signer, casino, operator, Role::mask(roles), *daily_payout_cap)
+        },
+        Command::RemoveOperator { operator } => client::remove_operator(signer, casino, operator),
+        Command::Settle { game, server_seed } => settle_instruction(fetcher, signer, game, server_seed).await?,
+        Command::ShowCasino | Command::ShowGame { .. } | Command::ListGames { .. } => return Ok(Vec::new()),
+    };
+    Ok(vec![instruction])
+}
+
+// Builds a settlement the program will accept for a revealed server seed
+async fn settle_instruction<F: AccountFetcher>(
+    fetcher: &mut F,
+    signer: &Pubkey,
+    game: &Pubkey,
+    server_seed: &[u8; 32],
+) -> Result<Instruction, CliError>
+where
+    F::Error: Debug,
+{
+    let game_state = client::fetch_game_state(fetcher, game).await?;
+    if game_state.is_settled {
+        return Err(CliError::AlreadySettled(*game));
+    }
+    if randomness::commit(server_seed) != game_state.server_seed_hash {
+        return Err(CliError::WrongServerSeed);
+    }
+
+    let casino_state = client::fetch_casino_state(fetcher, &game_state.casino).await?;
+    let (is_win, payout) = client::settlement(&game_state, game, server_seed)
+        .ok_or(CliError::PayoutOverflow)?;
+
+    // A coinflip challenge the player lost pays the opponent
+    let payee = game_state.payee(is_win);
+    Ok(match casino_state.mint {
+        Some(mint) => client::settle_token_game(
+            signer,
+            &game_state.casino,
+            game,
+            &payee,
+            &mint,
+            is_win,
+            payout,
+            *server_seed,
+        ),
+        None => client::settle_game(signer, &game_state.casino, game, &payee, is_win, payout, *server_seed),
+    })
+}
+
+/// Fetches the casino's games, oldest first
+pub async fn list_games(
+    rpc: &RpcClient,
+    casino: &Pubkey,
+    player: Option<&Pubkey>,
+    unsettled: bool,
+) -> Result<Vec<(Pubkey, GameState)>, CliError> {
+    // Games can only be filtered on fields before their variable-length game
+    // type, so the casino is checked after decoding
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, GameState::DISCRIMINATOR.to_vec()))];
+    if let Some(player) = player {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(ACCOUNT_HEADER_LEN, player.to_bytes().to_vec())));
+    }
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut games: Vec<(Pubkey, GameState)> = rpc
+        .get_program_accounts_with_config(&casino_game::id::id(), config)
+        .await?
+        .into_iter()
+        .filter_map(|(address, account)| Some((address, GameState::decode(&account.data).ok()?)))
+        .filter(|(_, game)| game.casino == *casino && !(unsettled && game.is_settled))
+        .collect();
+    games.sort_by_key(|(_, game)| game.created_at);
+    Ok(games)
+}
+
+/// Describes a casino
+pub fn format_casino(address: &Pubkey, casino: &CasinoState) -> String {
+    let mut lines = vec![
+        format!("Casino:            {}", address),
+        format!("Authority:         {}", casino.authority),
+        format!("Creator:           {}", casino.creator),
+        format!("Mint:              {}", casino.mint.map_or("SOL".to_string(), |mint| mint.to_string())),
+        format!("House edge:        {} bps", casino.house_edge),
+        format!("Bet limits:        {} - {}", casino.min_bet, casino.max_bet),
+        format!("Settle timeout:    {} s", casino.settle_timeout),
+        format!("Open stakes:       {}", casino.liabilities),
+        format!("Betting paused:    {}", casino.betting_paused),
+        format!("Settlement paused: {}", casino.settlement_paused),
+    ];
+    if casino.pending_authority != Pubkey::default() {
+        lines.push(format!("Pending authority: {}", casino.pending_authority));
+    }
+    lines.join("\n")
+}
+
+/// Describes a game
+pub fn format_game(address: &Pubkey, game: &GameState) -> String {
+    let mut lines = vec![
+        format!("Game:        {}", address),
+        format!("Casino:      {}", game.casino),
+        format!("Player:      {}", game.player),
+        format!("Game ID:     {}", hex(&game.game_id)),
+        format!("Game type:   {:?}", game.game_type),
+        format!("Bet amount:  {}", game.bet_amount),
+        format!("Created at:  {}", game.created_at),
+        format!("Client seed: {}", hex(&game.client_seed)),
+        format!("Commitment:  {}", hex(&game.server_seed_hash)),
+        format!("Settled:     {}", game.is_settled),
+    ];
+    if game.is_settled {
+        lines.push(format!("Server seed: {}", hex(&game.server_seed)));
+        lines.push(format!("Result hash: {}", hex(&game.result_hash)));
+        lines.push(format!("Win:         {}", game.is_win));
+        lines.push(format!("Win amount:  {}", game.win_amount));
+    }
+    lines.join("\n")
+}
+
+/// Describes a game on one line, for listings
+pub fn format_game_summary(address: &Pubkey, game: &GameState) -> String {
+    let outcome = match (game.is_settled, game.is_win) {
+        (false, _) => "unsettled".to_string(),
+        (true, true) => format!("won {}", game.win_amount),
+        (true, false) => "lost".to_string(),
+    };
+    format!("{} {} bet {} {}", address, game.player, game.bet_amount, outcome)
+}
+
+/// Encodes a transaction the way `sendTransaction` expects it
+pub fn encode_transaction(transaction: &Transaction) -> Result<String, CliError> {
+    let bytes = bincode::serialize(transaction).map_err(|err| CliError::Encoding(err.to_string()))?;
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Parses a 32-byte hex seed
+pub fn parse_seed(seed: &str) -> Result<[u8; 32], String> {
+    let seed = seed.strip_prefix("0x").unwrap_or(seed);
+    if seed.len() != 64 || !seed.is_ascii() {
+        return Err("expected 64 hex digits".to_string());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (byte, digits) in bytes.iter_mut().zip(seed.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|err| err.to_string())?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|err| err.to_string())?;
+    }
+    Ok(bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: ISC
+use casino_cli::{
+    encode_transaction, format_casino, format_game, format_game_summary, instructions, list_games, Cli, Command,
+    RpcFetcher,
+};
+use casino_game::client;
+use clap::Parser;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Signer},
+    transaction::Transaction,
+};
+use std::{env, error::Error, path::PathBuf};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let keypair_path = match &cli.keypair {
+        Some(path) => path.clone(),
+        None => PathBuf::from(env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    let signer = read_keypair_file(&keypair_path)
+        .map_err(|err| format!("Could not read keypair {}: {}", keypair_path.display(), err))?;
+    let casino = cli
+        .casino
+        .unwrap_or_else(|| client::find_casino_state_address(&signer.pubkey()).0);
+
+    let mut fetcher = RpcFetcher(RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed()));
+
+    match &cli.command {
+        Command::ShowCasino => {
+            let casino_state = client::fetch_casino_state(&mut fetcher, &casino).await?;
+            println!("{}", format_casino(&casino, &casino_state));
+        },
+        Command::ShowGame { game } => {
+            let game_state = client::fetch_game_state(&mut fetcher, game).await?;
+            println!("{}", format_game(game, &game_state));
+        },
+        Command::ListGames { player, unsettled } => {
+            for (address, game) in list_games(&fetcher.0, &casino, player.as_ref(), *unsettled).await? {
+                println!("{}", format_game_summary(&address, &game));
+            }
+        },
+        command => {
+            let instructions = instructions(&mut fetcher, &signer.pubkey(), &casino, command).await?;
+            let blockhash = match cli.blockhash {
+                Some(blockhash) => blockhash,
+                None => fetcher.0.get_latest_blockhash().await?,
+            };
+            let transaction =
+                Transaction::new_signed_with_payer(&instructions, Some(&signer.pubkey()), &[&signer], blockhash);
+
+            if cli.dry_run {
+                println!("{}", encode_transaction(&transaction)?);
+            } else {
+                let signature = fetcher.0.send_and_confirm_transaction(&transaction).await?;
+                println!("{}", signature);
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{encode_transaction, format_casino, format_game, instructions, Cli, CliError, Command, Role},
+        base64::{engine::general_purpose::STANDARD, Engine as _},
+        casino_game::{client, id, process_instruction, randomness, ROLE_PAUSE, ROLE_SETTLE},
+        clap::Parser,
+        solana_program::pubkey::Pubkey,
+        solana_program_test::*,
+        solana_sdk::{
+            account::Account,
+            hash::Hash,
+            signature::{Keypair, Signer},
+            transaction::Transaction,
+        },
+    };
+
+    // Reads accounts for the commands through program-test's client
+    struct Banks(BanksClient);
+
+    impl client::AccountFetcher for Banks {
+        type Error = BanksClientError;
+
+        async fn get_account_data(&mut self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, Self::Error> {
+            Ok(self.0.get_account(*address).await?.map(|account| (account.owner, account.data)))
+        }
+    }
+
+    // Helper function to create a program test environment
+    fn program_test() -> ProgramTest {
+        ProgramTest::new(
+            "casino_game",
+            id::id(),
+            processor!(process_instruction),
+        )
+    }
+
+    // Helper function to parse a command line the way the binary does
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from(["casino"].iter().chain(args)).unwrap().command
+    }
+
+    // Helper function to build a command's instructions and process them
+    async fn run(banks: &mut Banks, authority: &Keypair, casino: &Pubkey, command: &Command, recent_blockhash: Hash) {
+        let instructions = instructions(banks, &authority.pubkey(), casino, command).await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&authority.pubkey()),
+            &[authority],
+            recent_blockhash,
+        );
+        banks.0.process_transaction(transaction).await.unwrap();
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let operator = Pubkey::new_unique();
+        match parse(&["add-operator", &operator.to_string(), "--roles", "settle,pause", "--daily-payout-cap", "5"]) {
+            Command::AddOperator { operator: parsed, roles, daily_payout_cap } => {
+                assert_eq!(parsed, operator);
+                assert_eq!(Role::mask(&roles), ROLE_SETTLE | ROLE_PAUSE);
+                assert_eq!(daily_payout_cap, 5);
+            },
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let seed = format!("0x{}", hex(&[0xab; 32]));
+        match parse(&["settle", &operator.to_string(), "--server-seed", &seed]) {
+            Command::Settle { server_seed, .. } => {
+                assert_eq!(server_seed, [0xab; 32]);
+            },
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        // Seeds must be exactly 32 bytes of hex
+        assert!(Cli::try_parse_from(["casino", "settle", &operator.to_string(), "--server-seed", "abcd"]).is_err());
+        assert!(Cli::try_parse_from(["casino", "settle", &operator.to_string(), "--server-seed", &"zz".repeat(32)]).is_err());
+        // Settling needs the server seed
+        assert!(Cli::try_parse_from(["casino", "settle", &operator.to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_admin_commands() {
+        let mut program_test = program_test();
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        let operator = Pubkey::new_unique();
+        let (casino, _) = client::find_casino_state_address(&authority.pubkey());
+        for account in [&authority, &player] {
+            program_test.add_account(
+                account.pubkey(),
+                Account {
+                    lamports: 10_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+        let mut banks = Banks(banks_client);
+
+        // Create and configure the casino through the commands
+        run(&mut banks, &authority, &casino, &parse(&["init", "--min-bet", "100000", "--max-bet", "1000000000"]), recent_blockhash).await;
+        run(&mut banks, &authority, &casino, &parse(&["update-params", "--house-edge", "300"]), recent_blockhash).await;
+        run(
+            &mut banks,
+            &authority,
+            &casino,
+            &parse(&["add-operator", &operator.to_string(), "--roles", "all", "--daily-payout-cap", "1000"]),
+            recent_blockhash,
+        ).await;
+
+        let casino_state = client::fetch_casino_state(&mut banks, &casino).await.unwrap();
+        assert_eq!(casino_state.house_edge, 300);
+        assert_eq!(casino_state.min_bet, 100_000);
+        assert!(format_casino(&casino, &casino_state).contains("House edge:        300 bps"));
+        let operator_state = client::fetch_operator_state(&mut banks, &casino, &operator).await.unwrap();
+        assert_eq!(operator_state.roles, casino_game::ROLE_ALL);
+        assert_eq!(operator_state.daily_payout_cap, 1000);
+
+        run(&mut banks, &authority, &casino, &parse(&["remove-operator", &operator.to_string()]), recent_blockhash).await;
+        assert!(banks.0.get_account(client::find_operator_address(&casino, &operator).0).await.unwrap().is_none());
+
+        // Read-only commands need no instructions
+        assert!(instructions(&mut banks, &authority.pubkey(), &casino, &Command::ShowCasino).await.unwrap().is_empty());
+
+        // Fund the vault and have the player bet on a winning game
+        let client_seed = [7; 32];
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &[1; 32]);
+        let server_seed = (0..=255u8)
+            .map(|byte| [byte; 32])
+            .find(|seed| client::game_outcome(seed, &client_seed, &game))
+            .unwrap();
+        let bet_amount = 10_000_000;
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                client::fund_vault(&payer.pubkey(), &casino, 1_000_000_000),
+                client::post_commitment(&authority.pubkey(), &casino, randomness::commit(&server_seed)),
+                client::place_bet(
+                    &player.pubkey(),
+                    &casino,
+                    &game,
+                    [1; 32],
+                    bet_amount,
+                    client_seed,
+                    randomness::commit(&server_seed),
+                ),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &authority, &player],
+            recent_blockhash,
+        );
+        banks.0.process_transaction(transaction).await.unwrap();
+
+        // A seed that doesn't match the commitment is refused before sending
+        let wrong_seed = parse(&["settle", &game.to_string(), "--server-seed", &hex(&[0xee; 32])]);
+        assert!(matches!(
+            instructions(&mut banks, &authority.pubkey(), &casino, &wrong_seed).await,
+            Err(CliError::WrongServerSeed)
+        ));
+
+        // Settling pays even odds less the house edge
+        let settle = parse(&["settle", &game.to_string(), "--server-seed", &hex(&server_seed)]);
+        run(&mut banks, &authority, &casino, &settle, recent_blockhash).await;
+
+        let game_state = client::fetch_game_state(&mut banks, &game).await.unwrap();
+        assert!(game_state.is_settled);
+        assert!(game_state.is_win);
+        assert_eq!(game_state.win_amount, 2 * bet_amount * (10_000 - 300) / 10_000);
+        assert!(format_game(&game, &game_state).contains(&format!("Server seed: {}", hex(&server_seed))));
+
+        // The game can't be settled twice
+        assert!(matches!(
+            instructions(&mut banks, &authority.pubkey(), &casino, &settle).await,
+            Err(CliError::AlreadySettled(address)) if address == game
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_encoding() {
+        let (banks_client, payer, recent_blockhash) = program_test().start().await;
+        let mut banks = Banks(banks_client);
+        let (casino, _) = client::find_casino_state_address(&payer.pubkey());
+
+        let command = parse(&["init", "--min-bet", "1", "--max-bet", "2", "--dry-run"]);
+        let instructions = instructions(&mut banks, &payer.pubkey(), &casino, &command).await.unwrap();
+        let transaction =
+            Transaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[&payer], recent_blockhash);
+
+        // The encoded transaction decodes back to the signed one
+        let encoded = encode_transaction(&transaction).unwrap();
+        let decoded: Transaction = bincode::deserialize(&STANDARD.decode(encoded).unwrap()).unwrap();
+        assert_eq!(decoded, transaction);
+        assert_eq!(decoded.message.instructions[0].data, client::initialize(&payer.pubkey(), &casino, 250, 1, 2).data);
+    }
+}
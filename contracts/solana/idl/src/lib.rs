@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: ISC
+//! Generates the casino program's IDL, a JSON description of its
+//! instructions, accounts, events, types and errors, from the program's
+//! source.
+//!
+//! The layout follows Anchor's IDL where the program allows it:
+//!
+//! - Instructions and events are tagged with their one-byte Borsh enum index
+//!   (`discriminant`) rather than an 8-byte sighash. Their account lists come
+//!   from the `Accounts expected:` doc comments of `CasinoInstruction`, with
+//!   the SPL token accounts marked `isOptional`.
+//! - Account data starts with the type's 8-byte `discriminator` and a
+//!   `version` byte, followed by the Borsh-encoded fields.
+//! - Error codes are `CasinoError` variant indices, as in
+//!   `ProgramError::Custom`.
+//!
+//! Names are kept as they are in Rust. The IDL checked in at `IDL_PATH` is
+//! compared against the generated one by the tests, so it can't drift from
+//! the program.
+use casino_game::{id, CasinoState, Commitment, CrashBet, CrashRound, GameState, OperatorState, ProgramAccount, Round, RoundBet};
+use quote::ToTokens;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use syn::{Attribute, Expr, ExprLit, Fields, GenericArgument, Item, ItemEnum, ItemStruct, Lit, LitStr, Meta, PathArguments, Type};
+use thiserror::Error;
+
+// Test module
+#[cfg(test)]
+mod test;
+
+/// Where the IDL is checked in, relative to the program's crate
+pub const IDL_PATH: &str = "idl/casino_game.json";
+
+// Program sources declaring the types the IDL describes
+const SOURCES: [(&str, &str); 5] = [
+    ("src/lib.rs", include_str!("../../src/lib.rs")),
+    ("src/events.rs", include_str!("../../src/events.rs")),
+    ("src/roulette.rs", include_str!("../../src/roulette.rs")),
+    ("src/european_roulette.rs", include_str!("../../src/european_roulette.rs")),
+    ("src/coinflip.rs", include_str!("../../src/coinflip.rs")),
+];
+
+// The program's manifest, for its version
+const PROGRAM_MANIFEST: &str = include_str!("../../Cargo.toml");
+
+/// Errors generating the IDL
+#[derive(Error, Debug)]
+pub enum IdlError {
+    #[error("Could not parse {0}: {1}")]
+    Parse(&'static str, syn::Error),
+
+    #[error("{0} is not declared in the program's source")]
+    UnknownType(String),
+
+    #[error("Unsupported type {0}")]
+    UnsupportedType(String),
+
+    #[error("Instruction {0} takes the accounts of unknown instruction {1}")]
+    UnknownAccounts(String, String),
+}
+
+/// Generates the program's IDL
+pub fn generate() -> Result<Value, IdlError> {
+    let mut items = HashMap::new();
+    for (path, source) in SOURCES {
+        let file = syn::parse_file(source).map_err(|err| IdlError::Parse(path, err))?;
+        for item in file.items {
+            match &item {
+                Item::Struct(item_struct) => items.insert(item_struct.ident.to_string(), item),
+                Item::Enum(item_enum) => items.insert(item_enum.ident.to_string(), item),
+                _ => None,
+            };
+        }
+    }
+
+    let mut generator = Generator {
+        items: &items,
+        types: BTreeMap::new(),
+    };
+    let instructions = generator.instructions()?;
+    let accounts = vec![
+        generator.account::<CasinoState>("CasinoState")?,
+        generator.account::<OperatorState>("OperatorState")?,
+        generator.account::<GameState>("GameState")?,
+        generator.account::<CrashRound>("CrashRound")?,
+        generator.account::<CrashBet>("CrashBet")?,
+        generator.account::<Round>("Round")?,
+        generator.account::<RoundBet>("RoundBet")?,
+        generator.account::<Commitment>("Commitment")?,
+    ];
+    let events = generator.events()?;
+    let errors = generator.errors()?;
+
+    Ok(json!({
+        "version": program_version(),
+        "name": "casino_game",
+        "instructions": instructions,
+        "accounts": accounts,
+        "events": events,
+        "types": generator.types.into_values().collect::<Vec<_>>(),
+        "errors": errors,
+        "metadata": {
+            "address": id::id().to_string(),
+        },
+    }))
+}
+
+/// Generates the IDL as the pretty-printed JSON that is checked in
+pub fn to_json() -> Result<String, IdlError> {
+    let mut json = serde_json::to_string_pretty(&generate()?).expect("JSON values always serialize");
+    json.push('\n');
+    Ok(json)
+}
+
+// Accounts an instruction's doc comment lists
+enum AccountList {
+    Listed(Vec<Value>),
+    SameAs(String),
+}
+
+struct Generator<'a> {
+    // Structs and enums declared in the program's source, by name
+    items: &'a HashMap<String, Item>,
+    // Descriptions of the types the IDL refers to, by name
+    types: BTreeMap<String, Value>,
+}
+
+impl<'a> Generator<'a> {
+    fn instructions(&mut self) -> Result<Vec<Value>, IdlError> {
+        let instruction_enum = self.enum_item("CasinoInstruction")?;
+
+        let mut account_lists = HashMap::new();
+        let mut instructions = Vec::new();
+        for (discriminant, variant) in instruction_enum.variants.iter().enumerate() {
+            let name = variant.ident.to_string();
+            let (docs, accounts) = instruction_docs(&docs(&variant.attrs));
+            let accounts = match accounts {
+                AccountList::Listed(accounts) => accounts,
+                AccountList::SameAs(other) => account_lists
+                    .get(&other)
+                    .cloned()
+                    .ok_or_else(|| IdlError::UnknownAccounts(name.clone(), other))?,
+            };
+            account_lists.insert(name.clone(), accounts.clone());
+
+            let mut instruction = Map::new();
+            instruction.insert("name".to_string(), json!(name));
+            instruction.insert("discriminant".to_string(), json!(discriminant));
+            insert_docs(&mut instruction, docs);
+            instruction.insert("accounts".to_string(), json!(accounts));
+            instruction.insert("args".to_string(), self.fields(&variant.fields)?);
+            instructions.push(Value::Object(instruction));
+        }
+        Ok(instructions)
+    }
+
+    fn account<T: ProgramAccount>(&mut self, name: &str) -> Result<Value, IdlError> {
+        let item_struct = self.struct_item(name)?;
+
+        let mut account = Map::new();
+        account.insert("name".to_string(), json!(name));
+        insert_docs(&mut account, docs(&item_struct.attrs));
+        account.insert("discriminator".to_string(), json!(T::DISCRIMINATOR));
+        account.insert("version".to_string(), json!(T::VERSION));
+        account.insert("type".to_string(), json!({
+            "kind": "struct",
+            "fields": self.fields(&item_struct.fields)?,
+        }));
+        Ok(Value::Object(account))
+    }
+
+    // Each `CasinoEvent` variant wraps a struct of the same name, whose fields
+    // are listed in its place
+    fn events(&mut self) -> Result<Vec<Value>, IdlError> {
+        let event_enum = self.enum_item("CasinoEvent")?;
+
+        let mut events = Vec::new();
+        for (discriminant, variant) in event_enum.variants.iter().enumerate() {
+            let name = variant.ident.to_string();
+            let item_struct = self.struct_item(&name)?;
+
+            let mut event = Map::new();
+            event.insert("name".to_string(), json!(name));
+            event.insert("discriminant".to_string(), json!(discriminant));
+            insert_docs(&mut event, docs(&item_struct.attrs));
+            event.insert("fields".to_string(), self.fields(&item_struct.fields)?);
+            events.push(Value::Object(event));
+        }
+        Ok(events)
+    }
+
+    fn errors(&self) -> Result<Vec<Value>, IdlError> {
+        let error_enum = self.enum_item("CasinoError")?;
+
+        let mut errors = Vec::new();
+        for (code, variant) in error_enum.variants.iter().enumerate() {
+            let message = variant
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("error"))
+                .map(|attr| attr.parse_args::<LitStr>())
+                .transpose()
+                .map_err(|err| IdlError::Parse("src/lib.rs", err))?;
+
+            let mut error = Map::new();
+            error.insert("code".to_string(), json!(code));
+            error.insert("name".to_string(), json!(variant.ident.to_string()));
+            if let Some(message) = message {
+                error.insert("msg".to_string(), json!(message.value()));
+            }
+            errors.push(Value::Object(error));
+        }
+        Ok(errors)
+    }
+
+    fn fields(&mut self, fields: &Fields) -> Result<Value, IdlError> {
+        let mut described = Vec::new();
+        for field in fields {
+            let ty = self.type_of(&field.ty)?;
+            match &field.ident {
+                Some(ident) => {
+                    let mut named = Map::new();
+                    named.insert("name".to_string(), json!(ident.to_string()));
+                    insert_docs(&mut named, docs(&field.attrs));
+                    named.insert("type".to_string(), ty);
+                    described.push(Value::Object(named));
+                },
+                None => described.push(ty),
+            }
+        }
+        Ok(Value::Array(described))
+    }
+
+    fn type_of(&mut self, ty: &Type) -> Result<Value, IdlError> {
+        let unsupported = || IdlError::UnsupportedType(ty.to_token_stream().to_string());
+
+        match ty {
+            Type::Array(array) => {
+                let Expr::Lit(ExprLit { lit: Lit::Int(len), .. }) = &array.len else {
+                    return Err(unsupported());
+                };
+                let len: u64 = len.base10_parse().map_err(|_| unsupported())?;
+                Ok(json!({ "array": [self.type_of(&array.elem)?, len] }))
+            },
+            Type::Path(path) => {
+                let segment = path.path.segments.last().ok_or_else(unsupported)?;
+                let name = segment.ident.to_string();
+                match name.as_str() {
+                    "bool" | "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                        Ok(json!(name))
+                    },
+                    "String" => Ok(json!("string")),
+                    "Pubkey" => Ok(json!("publicKey")),
+                    "Option" | "Vec" => {
+                        let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+                            return Err(unsupported());
+                        };
+                        let Some(GenericArgument::Type(inner)) = arguments.args.first() else {
+                            return Err(unsupported());
+                        };
+                        let key = if name == "Option" { "option" } else { "vec" };
+                        Ok(json!({ key: self.type_of(inner)? }))
+                    },
+                    _ => {
+                        self.define(&name)?;
+                        Ok(json!({ "defined": name }))
+                    },
+                }
+            },
+            _ => Err(unsupported()),
+        }
+    }
+
+    // Adds a struct or enum to the IDL's types
+    fn define(&mut self, name: &str) -> Result<(), IdlError> {
+        if self.types.contains_key(name) {
+            return Ok(());
+        }
+        // Reserve the name first so recursive types terminate
+        self.types.insert(name.to_string(), Value::Null);
+
+        let items = self.items;
+        let (attrs, ty) = match items.get(name) {
+            Some(Item::Struct(item_struct)) => (&item_struct.attrs, json!({
+                "kind": "struct",
+                "fields": self.fields(&item_struct.fields)?,
+            })),
+            Some(Item::Enum(item_enum)) => {
+                let mut variants = Vec::new();
+                for variant in &item_enum.variants {
+                    let mut described = Map::new();
+                    described.insert("name".to_string(), json!(variant.ident.to_string()));
+                    insert_docs(&mut described, docs(&variant.attrs));
+                    if !variant.fields.is_empty() {
+                        described.insert("fields".to_string(), self.fields(&variant.fields)?);
+                    }
+                    variants.push(Value::Object(described));
+                }
+                (&item_enum.attrs, json!({ "kind": "enum", "variants": variants }))
+            },
+            _ => return Err(IdlError::UnknownType(name.to_string())),
+        };
+
+        let mut defined = Map::new();
+        defined.insert("name".to_string(), json!(name));
+        insert_docs(&mut defined, docs(attrs));
+        defined.insert("type".to_string(), ty);
+        self.types.insert(name.to_string(), Value::Object(defined));
+        Ok(())
+    }
+
+    fn enum_item(&self, name: &str) -> Result<&'a ItemEnum, IdlError> {
+        match self.items.get(name) {
+            Some(Item::Enum(item_enum)) => Ok(item_enum),
+            _ => Err(IdlError::UnknownType(name.to_string())),
+        }
+    }
+
+    fn struct_item(&self, name: &str) -> Result<&'a ItemStruct, IdlError> {
+        match self.items.get(name) {
+            Some(Item::Struct(item_struct)) => Ok(item_struct),
+            _ => Err(IdlError::UnknownType(name.to_string())),
+        }
+    }
+}
+
+// The lines of an item's doc comment
+fn docs(attrs: &[Attribute]) -> Vec<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(doc) => match &doc.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(line), .. }) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).trim_end().to_string())
+        .collect();
+    trim_blank_lines(&lines)
+}
+
+fn trim_blank_lines(lines: &[String]) -> Vec<String> {
+    let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.is_empty()).map_or(start, |end| end + 1);
+    lines[start..end].to_vec()
+}
+
+fn insert_docs(object: &mut Map<String, Value>, docs: Vec<String>) {
+    if !docs.is_empty() {
+        object.insert("docs".to_string(), json!(docs));
+    }
+}
+
+// Splits an instruction's doc comment into its description and the accounts
+// listed under `Accounts expected:`. Accounts after a further heading, such as
+// `Additionally, for SPL token casinos:`, are optional.
+fn instruction_docs(lines: &[String]) -> (Vec<String>, AccountList) {
+    let Some(start) = lines.iter().position(|line| line.starts_with("Accounts expected:")) else {
+        return (lines.to_vec(), AccountList::Listed(Vec::new()));
+    };
+    let description = trim_blank_lines(&lines[..start]);
+    if let Some(other) = lines[start].strip_prefix("Accounts expected: same as `") {
+        return (description, AccountList::SameAs(other.trim_end_matches('`').to_string()));
+    }
+
+    let mut accounts: Vec<Map<String, Value>> = Vec::new();
+    let mut optional = false;
+    for line in &lines[start + 1..] {
+        if let Some((flags, name)) = account_line(line) {
+            let mut account = Map::new();
+            account.insert("name".to_string(), json!(name));
+            account.insert("isMut".to_string(), json!(flags.contains(&"writable")));
+            account.insert("isSigner".to_string(), json!(flags.contains(&"signer")));
+            if optional {
+                account.insert("isOptional".to_string(), json!(true));
+            }
+            accounts.push(account);
+        } else if line.starts_with(' ') {
+            // A description continued on the next line
+            if let Some(Value::String(name)) = accounts.last_mut().and_then(|account| account.get_mut("name")) {
+                name.push(' ');
+                name.push_str(line.trim());
+            }
+        } else if !line.is_empty() {
+            optional = true;
+        }
+    }
+    (description, AccountList::Listed(accounts.into_iter().map(Value::Object).collect()))
+}
+
+// Parses a line such as "0. `[signer, writable]` The player account"
+fn account_line(line: &str) -> Option<(Vec<&str>, &str)> {
+    let (index, rest) = line.split_once(". ")?;
+    index.parse::<usize>().ok()?;
+    let (flags, name) = rest.strip_prefix("`[")?.split_once("]`")?;
+    let flags = flags.split(',').map(str::trim).filter(|flag| !flag.is_empty()).collect();
+    Some((flags, name.trim()))
+}
+
+fn program_version() -> &'static str {
+    PROGRAM_MANIFEST
+        .lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .map_or("", |version| version.trim_matches('"'))
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: ISC
+use std::{env, error::Error, fs};
+
+/// Prints the casino program's IDL, or writes it to the path given as the
+/// first argument, e.g. `cargo run -p casino-idl -- idl/casino_game.json`
+fn main() -> Result<(), Box<dyn Error>> {
+    let json = casino_idl::to_json()?;
+    match env::args().nth(1) {
+        Some(path) => fs::write(path, json)?,
+        None => print!("{}", json),
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{generate, to_json, IDL_PATH},
+        borsh::BorshSerialize,
+        casino_game::{client, events::{self, CasinoEvent}, CasinoError, GameState, ProgramAccount},
+        serde_json::Value,
+        solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    };
+
+    // Helper function to find an entry of one of the IDL's lists by name
+    fn entry<'a>(idl: &'a Value, list: &str, name: &str) -> &'a Value {
+        idl[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["name"] == name)
+            .unwrap_or_else(|| panic!("{} has no {}", list, name))
+    }
+
+    #[test]
+    fn test_checked_in_idl_is_current() {
+        let checked_in = include_str!("../casino_game.json");
+        assert!(
+            to_json().unwrap() == checked_in,
+            "{} is out of date, regenerate it with `cargo run -p casino-idl -- {}`",
+            IDL_PATH,
+            IDL_PATH,
+        );
+    }
+
+    #[test]
+    fn test_idl_matches_program_encoding() {
+        let idl = generate().unwrap();
+        let casino = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+
+        // Instruction discriminants are the first byte of the instruction data
+        let remove_operator = client::remove_operator(&operator, &casino, &operator);
+        assert_eq!(entry(&idl, "instructions", "RemoveOperator")["discriminant"], remove_operator.data[0]);
+        let update_params = client::update_params(&operator, &casino, Some(300), None, None, None);
+        let instruction = entry(&idl, "instructions", "UpdateParams");
+        assert_eq!(instruction["discriminant"], update_params.data[0]);
+        assert_eq!(instruction["args"][0]["type"]["option"], "u16");
+        assert_eq!(instruction["accounts"].as_array().unwrap().len(), update_params.accounts.len());
+        assert_eq!(instruction["accounts"][0]["isSigner"], true);
+
+        // Token accounts are optional
+        let place_bet = entry(&idl, "instructions", "PlaceBet");
+        let optional: Vec<bool> = place_bet["accounts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|account| account["isOptional"] == true)
+            .collect();
+        assert_eq!(optional, [false, false, false, false, false, false, true, true, true]);
+        // An instruction documented with "same as" gets the other's accounts
+        assert_eq!(entry(&idl, "instructions", "PlaceRouletteBets")["accounts"], place_bet["accounts"]);
+
+        // Accounts carry their discriminator and version
+        let game_state = entry(&idl, "accounts", "GameState");
+        assert_eq!(game_state["discriminator"], serde_json::json!(GameState::DISCRIMINATOR));
+        assert_eq!(game_state["version"], GameState::VERSION);
+        assert_eq!(game_state["type"]["fields"][0]["type"], "publicKey");
+
+        // Event discriminants are the first byte of the logged data
+        let event = CasinoEvent::OperatorRemoved(events::OperatorRemoved { casino, operator });
+        assert_eq!(entry(&idl, "events", "OperatorRemoved")["discriminant"], event.try_to_vec().unwrap()[0]);
+
+        // Error codes are the custom program error codes
+        let error = entry(&idl, "errors", "PayoutCapExceeded");
+        assert_eq!(
+            ProgramError::Custom(error["code"].as_u64().unwrap() as u32),
+            ProgramError::from(CasinoError::PayoutCapExceeded),
+        );
+        assert_eq!(error["msg"], CasinoError::PayoutCapExceeded.to_string());
+
+        // Types used by the instructions and accounts are described once
+        assert_eq!(entry(&idl, "types", "RouletteWager")["type"]["kind"], "struct");
+        assert_eq!(entry(&idl, "types", "GameType")["type"]["variants"][1]["fields"][0]["type"]["defined"], "RouletteColor");
+    }
+}
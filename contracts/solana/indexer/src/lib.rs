@@ -0,0 +1,877 @@
+// SPDX-License-Identifier: ISC
+//! Off-chain indexer for the casino program.
+//!
+//! Reads the transactions that invoked the program, decodes their
+//! instructions and events and keeps a SQLite database of games, players,
+//! payouts and operator actions (see `schema`).
+//!
+//! Events are read from the `Program data:` log lines only. A successful
+//! transaction whose logs are missing or truncated is marked `undecodable`
+//! and changes nothing else, since the program's effects can't be told
+//! without its events.
+//!
+//! Only slots the cluster has finalized are kept for good: `Indexer::sync`
+//! rolls back everything indexed after the last finalized slot and reads it
+//! again, so a reorg never leaves transactions from an abandoned fork behind.
+use borsh::BorshDeserialize;
+use casino_game::{
+    client::decode_events,
+    events::CasinoEvent,
+    roulette,
+    CasinoInstruction, GameType, DEFAULT_SETTLE_TIMEOUT,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use std::path::Path;
+use thiserror::Error;
+
+// JSON-RPC transaction source
+pub mod rpc;
+
+// SQLite schema
+pub mod schema;
+
+// Transactions and their JSON form
+pub mod transaction;
+
+// Test module
+#[cfg(test)]
+mod test;
+
+pub use transaction::TransactionRecord;
+
+/// Errors the indexer can return
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+}
+
+/// A transaction signature and the slot it landed in
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+}
+
+/// Where `Indexer::sync` reads transactions from
+pub trait TransactionSource {
+    /// The highest slot that can no longer be rolled back
+    fn finalized_slot(&self) -> Result<u64, IndexerError>;
+
+    /// Signatures of the confirmed transactions that invoked `program_id`
+    /// after the one signed `until` (or all of them), oldest first. A source
+    /// may return only the oldest page of them; the next sync reads on from it.
+    fn signatures(&self, program_id: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, IndexerError>;
+
+    /// Fetches a transaction, or `None` if the source no longer has it
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexerError>;
+}
+
+/// A fixed set of transactions, such as a dump
+pub struct TransactionDump {
+    pub transactions: Vec<TransactionRecord>,
+    /// Transactions up to this slot are treated as finalized
+    pub finalized_slot: u64,
+}
+
+impl TransactionDump {
+    /// Orders the transactions by slot and treats all of them as finalized
+    pub fn new(mut transactions: Vec<TransactionRecord>) -> Self {
+        transactions.sort_by_key(|transaction| transaction.slot);
+        let finalized_slot = transactions.last().map_or(0, |transaction| transaction.slot);
+        Self { transactions, finalized_slot }
+    }
+}
+
+impl TransactionSource for TransactionDump {
+    fn finalized_slot(&self) -> Result<u64, IndexerError> {
+        Ok(self.finalized_slot)
+    }
+
+    fn signatures(&self, program_id: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, IndexerError> {
+        let start = until
+            .and_then(|until| self.transactions.iter().position(|transaction| transaction.signature == until))
+            .map_or(0, |position| position + 1);
+
+        Ok(self.transactions[start..]
+            .iter()
+            .filter(|transaction| transaction.account_keys.contains(program_id))
+            .map(|transaction| SignatureInfo {
+                signature: transaction.signature.clone(),
+                slot: transaction.slot,
+            })
+            .collect())
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexerError> {
+        Ok(self.transactions.iter().find(|transaction| transaction.signature == signature).cloned())
+    }
+}
+
+/// Writes casino transactions into a SQLite database
+pub struct Indexer {
+    conn: Connection,
+    program_id: Pubkey,
+}
+
+impl Indexer {
+    /// Opens (or creates) the database at `path`
+    pub fn open<P: AsRef<Path>>(path: P, program_id: Pubkey) -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open(path)?, program_id)
+    }
+
+    /// Creates an in-memory database
+    pub fn open_in_memory(program_id: Pubkey) -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open_in_memory()?, program_id)
+    }
+
+    fn with_connection(conn: Connection, program_id: Pubkey) -> Result<Self, IndexerError> {
+        conn.execute_batch(schema::SCHEMA)?;
+        Ok(Self { conn, program_id })
+    }
+
+    /// The underlying connection, to query the indexed data
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// The last slot `sync` saw finalized
+    pub fn finalized_slot(&self) -> Result<Option<u64>, IndexerError> {
+        Ok(self.conn
+            .query_row("SELECT value FROM sync_state WHERE key = 'finalized_slot'", [], |row| row.get(0))
+            .optional()?)
+    }
+
+    /// Indexes a transaction. Returns `false` if it had already been indexed.
+    pub fn index_transaction(&mut self, transaction: &TransactionRecord) -> Result<bool, IndexerError> {
+        let instructions = decode_instructions(&self.program_id, transaction);
+        let truncated = transaction.log_messages.iter().any(|message| message.starts_with("Log truncated"));
+        let undecodable = transaction.succeeded && (transaction.log_messages.is_empty() || truncated);
+        let db = self.conn.transaction()?;
+
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, succeeded, undecodable)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![transaction.signature, transaction.slot, transaction.block_time, transaction.succeeded, undecodable],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        // Failed transactions change nothing on chain
+        if transaction.succeeded {
+            for instruction in &instructions {
+                let accounts: Vec<String> = instruction.accounts.iter().map(|key| key.to_string()).collect();
+                db.execute(
+                    "INSERT INTO instructions (signature, instruction_index, slot, name, accounts, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        transaction.signature,
+                        instruction.index,
+                        transaction.slot,
+                        instruction_name(&instruction.instruction),
+                        Value::from(accounts).to_string(),
+                        instruction.data,
+                    ],
+                )?;
+            }
+
+        }
+
+        // Without all of its events a transaction's effects are unknown
+        if transaction.succeeded && !undecodable {
+            let mut writer = Writer {
+                db: &db,
+                slot: transaction.slot,
+                signature: &transaction.signature,
+                event_index: 0,
+            };
+            for event in &decode_events(&self.program_id, &transaction.log_messages) {
+                writer.apply(event)?;
+            }
+            for instruction in &instructions {
+                writer.record_seeds(instruction)?;
+            }
+        }
+
+        db.commit()?;
+        Ok(true)
+    }
+
+    /// Removes everything indexed at `slot` or later
+    pub fn rollback(&mut self, slot: u64) -> Result<(), IndexerError> {
+        let db = self.conn.transaction()?;
+        for table in ["transactions", "instructions", "casino_states", "payouts", "operator_actions"] {
+            db.execute(&format!("DELETE FROM {} WHERE slot >= ?1", table), [slot])?;
+        }
+        db.execute("DELETE FROM games WHERE placed_slot >= ?1", [slot])?;
+        db.execute(
+            "UPDATE games SET opponent = NULL, slot_hash = NULL, accepted_slot = NULL WHERE accepted_slot >= ?1",
+            [slot],
+        )?;
+        db.execute("UPDATE games SET cashout = NULL, cashout_slot = NULL, cashout_time = NULL WHERE cashout_slot >= ?1", [slot])?;
+        db.execute(
+            "UPDATE games SET settled_slot = NULL, settled_by = NULL, server_seed = NULL,
+                result_hash = NULL, is_win = NULL, win_amount = NULL
+             WHERE settled_slot >= ?1",
+            [slot],
+        )?;
+        db.execute("UPDATE games SET closed_slot = NULL WHERE closed_slot >= ?1", [slot])?;
+        db.execute("DELETE FROM crash_rounds WHERE opened_slot >= ?1", [slot])?;
+        db.execute(
+            "UPDATE crash_rounds SET settled_slot = NULL, settled_by = NULL, server_seed = NULL,
+                result_hash = NULL, crash_point = NULL
+             WHERE settled_slot >= ?1",
+            [slot],
+        )?;
+        db.execute("UPDATE crash_rounds SET closed_slot = NULL WHERE closed_slot >= ?1", [slot])?;
+        db.execute("DELETE FROM rounds WHERE opened_slot >= ?1", [slot])?;
+        db.execute("UPDATE rounds SET locked_slot = NULL WHERE locked_slot >= ?1", [slot])?;
+        db.execute(
+            "UPDATE rounds SET spun_slot = NULL, spun_by = NULL, server_seed = NULL, result_hash = NULL,
+                winning_slot = NULL
+             WHERE spun_slot >= ?1",
+            [slot],
+        )?;
+        db.execute("UPDATE rounds SET closed_slot = NULL WHERE closed_slot >= ?1", [slot])?;
+        db.commit()?;
+        Ok(())
+    }
+
+    /// Brings the database up to date with `source`: rolls back whatever was
+    /// indexed after the last finalized slot, then indexes the transactions
+    /// `source` lists since the last finalized one. The finalized slot only
+    /// advances as far as the finalized transactions read. Returns how many
+    /// were indexed.
+    pub fn sync<S: TransactionSource>(&mut self, source: &S) -> Result<usize, IndexerError> {
+        let finalized_slot = source.finalized_slot()?;
+        let previous_finalized_slot = self.finalized_slot()?;
+        self.rollback(previous_finalized_slot.map_or(0, |slot| slot + 1))?;
+
+        let until: Option<String> = self.conn
+            .query_row("SELECT value FROM sync_state WHERE key = 'finalized_signature'", [], |row| row.get(0))
+            .optional()?;
+
+        let mut indexed = 0;
+        let mut finalized = None;
+        for info in source.signatures(&self.program_id, until.as_deref())? {
+            if let Some(transaction) = source.transaction(&info.signature)? {
+                if self.index_transaction(&transaction)? {
+                    indexed += 1;
+                }
+            }
+            if info.slot <= finalized_slot {
+                finalized = Some((info.signature, info.slot));
+            }
+        }
+
+        // Slots past the last finalized transaction read may still hold ones
+        // the source did not list yet, so they stay open to a rollback
+        if let Some((signature, slot)) = finalized {
+            let slot = previous_finalized_slot.map_or(slot, |previous| previous.max(slot));
+            self.conn.execute(
+                "INSERT OR REPLACE INTO sync_state (key, value) VALUES ('finalized_signature', ?1)",
+                [signature],
+            )?;
+            self.conn.execute(
+                "INSERT OR REPLACE INTO sync_state (key, value) VALUES ('finalized_slot', ?1)",
+                [slot],
+            )?;
+        }
+
+        Ok(indexed)
+    }
+}
+
+// A top-level casino instruction of a transaction
+struct DecodedInstruction {
+    index: usize,
+    instruction: CasinoInstruction,
+    accounts: Vec<Pubkey>,
+    data: Vec<u8>,
+}
+
+impl DecodedInstruction {
+    fn account(&self, index: usize) -> Pubkey {
+        self.accounts.get(index).copied().unwrap_or_default()
+    }
+}
+
+fn decode_instructions(program_id: &Pubkey, transaction: &TransactionRecord) -> Vec<DecodedInstruction> {
+    transaction.instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| transaction.program_id(instruction) == Some(program_id))
+        .filter_map(|(index, instruction)| {
+            let decoded = CasinoInstruction::try_from_slice(&instruction.data).ok()?;
+            Some(DecodedInstruction {
+                index,
+                instruction: decoded,
+                accounts: transaction.instruction_accounts(instruction),
+                data: instruction.data.clone(),
+            })
+        })
+        .collect()
+}
+
+fn instruction_name(instruction: &CasinoInstruction) -> &'static str {
+    match instruction {
+        CasinoInstruction::Initialize { .. } => "initialize",
+        CasinoInstruction::PlaceBet { .. } => "place_bet",
+        CasinoInstruction::SettleGame { .. } => "settle_game",
+        CasinoInstruction::PlaceRouletteBet { .. } => "place_roulette_bet",
+        CasinoInstruction::PlaceRouletteBets { .. } => "place_roulette_bets",
+        CasinoInstruction::UpdateParams { .. } => "update_params",
+        CasinoInstruction::AddOperator { .. } => "add_operator",
+        CasinoInstruction::RemoveOperator => "remove_operator",
+        CasinoInstruction::FundVault { .. } => "fund_vault",
+        CasinoInstruction::WithdrawFromVault { .. } => "withdraw_from_vault",
+        CasinoInstruction::CloseGame => "close_game",
+        CasinoInstruction::ClaimRefund => "claim_refund",
+        CasinoInstruction::ProposeAuthority { .. } => "propose_authority",
+        CasinoInstruction::AcceptAuthority => "accept_authority",
+        CasinoInstruction::Pause { .. } => "pause",
+        CasinoInstruction::Unpause { .. } => "unpause",
+        CasinoInstruction::MigrateCasinoState => "migrate_casino_state",
+        CasinoInstruction::PlaceDiceBet { .. } => "place_dice_bet",
+        CasinoInstruction::PlaceCoinflipBet { .. } => "place_coinflip_bet",
+        CasinoInstruction::OpenCoinflipChallenge { .. } => "open_coinflip_challenge",
+        CasinoInstruction::AcceptCoinflipChallenge => "accept_coinflip_challenge",
+        CasinoInstruction::CancelCoinflipChallenge => "cancel_coinflip_challenge",
+        CasinoInstruction::OpenCrashRound { .. } => "open_crash_round",
+        CasinoInstruction::JoinCrashRound { .. } => "join_crash_round",
+        CasinoInstruction::CashOut { .. } => "cash_out",
+        CasinoInstruction::SettleCrashRound { .. } => "settle_crash_round",
+        CasinoInstruction::ClaimCrashRefund => "claim_crash_refund",
+        CasinoInstruction::OpenRound { .. } => "open_round",
+        CasinoInstruction::PlaceRoundBet { .. } => "place_round_bet",
+        CasinoInstruction::LockRound => "lock_round",
+        CasinoInstruction::SpinRound { .. } => "spin_round",
+        CasinoInstruction::SettleRoundBets => "settle_round_bets",
+        CasinoInstruction::ClaimRoundRefund => "claim_round_refund",
+        CasinoInstruction::SettleBatch { .. } => "settle_batch",
+        CasinoInstruction::PostCommitment { .. } => "post_commitment",
+        CasinoInstruction::CloseCrashRound => "close_crash_round",
+        CasinoInstruction::CloseRound => "close_round",
+        CasinoInstruction::ClaimLegacyRefund { .. } => "claim_legacy_refund",
+    }
+}
+
+// A casino's state as recorded in `casino_states`
+#[derive(Default)]
+struct CasinoRow {
+    authority: Option<String>,
+    mint: Option<String>,
+    house_edge: Option<u16>,
+    min_bet: Option<u64>,
+    max_bet: Option<u64>,
+    settle_timeout: Option<i64>,
+    betting_paused: Option<bool>,
+    settlement_paused: Option<bool>,
+}
+
+// Writes the rows of one transaction
+struct Writer<'a> {
+    db: &'a Connection,
+    slot: u64,
+    signature: &'a str,
+    event_index: u32,
+}
+
+impl Writer<'_> {
+    fn apply(&mut self, event: &CasinoEvent) -> Result<(), IndexerError> {
+        match event {
+            CasinoEvent::CasinoInitialized(event) => {
+                self.record_casino_state(&event.casino, |state| {
+                    state.authority = Some(event.authority.to_string());
+                    state.mint = event.mint.map(|mint| mint.to_string());
+                    state.house_edge = Some(event.house_edge);
+                    state.min_bet = Some(event.min_bet);
+                    state.max_bet = Some(event.max_bet);
+                    state.settle_timeout = Some(DEFAULT_SETTLE_TIMEOUT);
+                    state.betting_paused = Some(false);
+                    state.settlement_paused = Some(false);
+                })?;
+                self.record_action(&event.casino, "initialize", Some(&event.authority), json!({
+                    "house_edge": event.house_edge,
+                    "min_bet": event.min_bet,
+                    "max_bet": event.max_bet,
+                    "mint": event.mint.map(|mint| mint.to_string()),
+                }))?;
+            },
+            CasinoEvent::BetPlaced(event) => {
+                let (game_type, wager) = match &event.game_type {
+                    GameType::Standard => ("standard", None),
+                    GameType::Roulette { color } => ("roulette", Some(format!("{:?}", color))),
+                    GameType::EuropeanRoulette { bets } => ("european_roulette", Some(format!("{:?}", bets))),
+                    GameType::Dice { target } => ("dice", Some(target.to_string())),
+                    GameType::Coinflip { side } => ("coinflip", Some(format!("{:?}", side))),
+                    GameType::CoinflipChallenge { side, .. } => ("coinflip_challenge", Some(format!("{:?}", side))),
+                };
+                self.db.execute(
+                    "INSERT INTO games (address, casino, player, game_id, game_type, wager, bet_amount,
+                        placed_slot, placed_signature)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        event.game.to_string(),
+                        event.casino.to_string(),
+                        event.player.to_string(),
+                        &event.game_id[..],
+                        game_type,
+                        wager,
+                        event.bet_amount,
+                        self.slot,
+                        self.signature,
+                    ],
+                )?;
+            },
+            CasinoEvent::GameSettled(event) => {
+                self.db.execute(
+                    "UPDATE games SET settled_slot = ?2, settled_by = ?3, result_hash = ?4, is_win = ?5,
+                        win_amount = CASE WHEN ?5 THEN ?6 ELSE 0 END
+                     WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)",
+                    params![
+                        event.game.to_string(),
+                        self.slot,
+                        event.operator.to_string(),
+                        &event.result_hash[..],
+                        event.is_win,
+                        event.win_amount,
+                    ],
+                )?;
+                // A coinflip challenge the player lost pays the opponent
+                let payee = match event.is_win {
+                    true => Some(event.player),
+                    false => self.game_opponent(&event.game)?,
+                };
+                if let Some(payee) = payee.filter(|_| event.win_amount > 0) {
+                    self.record_payout(&event.casino, &event.game, &payee, "win", event.win_amount)?;
+                }
+            },
+            CasinoEvent::GameClosed(event) => {
+                self.close_game(&event.game)?;
+            },
+            CasinoEvent::RefundClaimed(event) => {
+                self.record_payout(&event.casino, &event.game, &event.player, "refund", event.amount)?;
+                self.close_game(&event.game)?;
+            },
+            CasinoEvent::ParamsUpdated(event) => {
+                self.record_casino_state(&event.casino, |state| {
+                    state.house_edge = Some(event.house_edge);
+                    state.min_bet = Some(event.min_bet);
+                    state.max_bet = Some(event.max_bet);
+                    state.settle_timeout = Some(event.settle_timeout);
+                })?;
+                self.record_action(&event.casino, "update_params", None, json!({
+                    "house_edge": event.house_edge,
+                    "min_bet": event.min_bet,
+                    "max_bet": event.max_bet,
+                    "settle_timeout": event.settle_timeout,
+                }))?;
+            },
+            CasinoEvent::OperatorAdded(event) => {
+                self.record_action(&event.casino, "add_operator", Some(&event.operator), json!({
+                    "roles": event.roles,
+                    "daily_payout_cap": event.daily_payout_cap,
+                }))?;
+            },
+            CasinoEvent::OperatorRemoved(event) => {
+                self.record_action(&event.casino, "remove_operator", Some(&event.operator), json!({}))?;
+            },
+            CasinoEvent::VaultFunded(event) => {
+                self.record_action(&event.casino, "fund_vault", Some(&event.funder), json!({
+                    "amount": event.amount,
+                }))?;
+            },
+            CasinoEvent::VaultWithdrawn(event) => {
+                self.record_action(&event.casino, "withdraw_from_vault", Some(&event.recipient), json!({
+                    "amount": event.amount,
+                }))?;
+            },
+            CasinoEvent::AuthorityProposed(event) => {
+                self.record_action(&event.casino, "propose_authority", Some(&event.new_authority), json!({
+                    "authority": event.authority.to_string(),
+                }))?;
+            },
+            CasinoEvent::AuthorityTransferred(event) => {
+                self.record_casino_state(&event.casino, |state| {
+                    state.authority = Some(event.new_authority.to_string());
+                })?;
+                self.record_action(&event.casino, "accept_authority", Some(&event.new_authority), json!({
+                    "previous_authority": event.previous_authority.to_string(),
+                }))?;
+            },
+            CasinoEvent::PauseUpdated(event) => {
+                self.record_casino_state(&event.casino, |state| {
+                    state.betting_paused = Some(event.betting_paused);
+                    state.settlement_paused = Some(event.settlement_paused);
+                })?;
+                self.record_action(&event.casino, "pause", Some(&event.operator), json!({
+                    "betting_paused": event.betting_paused,
+                    "settlement_paused": event.settlement_paused,
+                }))?;
+            },
+            CasinoEvent::CasinoMigrated(event) => {
+                self.record_action(&event.casino, "migrate_casino_state", None, json!({
+                    "version": event.version,
+                }))?;
+            },
+            CasinoEvent::ChallengeAccepted(event) => {
+                self.db.execute(
+                    "UPDATE games SET opponent = ?2, slot_hash = ?3, accepted_slot = ?4
+                     WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)",
+                    params![event.game.to_string(), event.opponent.to_string(), &event.slot_hash[..], self.slot],
+                )?;
+            },
+            CasinoEvent::CrashRoundOpened(event) => {
+                self.db.execute(
+                    "INSERT OR REPLACE INTO crash_rounds (address, casino, round_id, operator, server_seed_hash, house_edge,
+                        starts_at, opened_slot, opened_signature)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        event.round.to_string(),
+                        event.casino.to_string(),
+                        &event.round_id[..],
+                        event.operator.to_string(),
+                        &event.server_seed_hash[..],
+                        event.house_edge,
+                        event.starts_at,
+                        self.slot,
+                        self.signature,
+                    ],
+                )?;
+            },
+            CasinoEvent::CrashBetPlaced(event) => {
+                self.db.execute(
+                    "INSERT INTO games (address, casino, player, game_id, game_type, wager, bet_amount,
+                        placed_slot, placed_signature)
+                     VALUES (?1, ?2, ?3, ?4, 'crash', ?5, ?6, ?7, ?8)",
+                    params![
+                        event.bet.to_string(),
+                        event.casino.to_string(),
+                        event.player.to_string(),
+                        &event.round_id[..],
+                        event.auto_cashout.map(|multiplier| multiplier.to_string()),
+                        event.bet_amount,
+                        self.slot,
+                        self.signature,
+                    ],
+                )?;
+            },
+            CasinoEvent::CashedOut(event) => {
+                self.db.execute(
+                    "UPDATE games SET cashout = ?2, cashout_slot = ?3, cashout_time = ?4
+                     WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)",
+                    params![event.bet.to_string(), event.multiplier, self.slot, event.time],
+                )?;
+            },
+            CasinoEvent::CrashRoundSettled(event) => {
+                self.db.execute(
+                    "UPDATE crash_rounds SET settled_slot = ?2, settled_by = ?3, result_hash = ?4, crash_point = ?5
+                     WHERE address = ?1",
+                    params![
+                        event.round.to_string(),
+                        self.slot,
+                        event.operator.to_string(),
+                        &event.result_hash[..],
+                        event.crash_point,
+                    ],
+                )?;
+            },
+            CasinoEvent::CrashBetSettled(event) => {
+                // Settling a crash bet closes its account
+                self.db.execute(
+                    "UPDATE games SET settled_slot = ?2, settled_by = ?3, is_win = ?4, win_amount = ?5, closed_slot = ?2
+                     WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)",
+                    params![
+                        event.bet.to_string(),
+                        self.slot,
+                        event.operator.to_string(),
+                        event.win_amount > 0,
+                        event.win_amount,
+                    ],
+                )?;
+                if event.win_amount > 0 {
+                    self.record_payout(&event.casino, &event.bet, &event.player, "win", event.win_amount)?;
+                }
+            },
+            CasinoEvent::RoundOpened(event) => {
+                self.db.execute(
+                    "INSERT OR REPLACE INTO rounds (address, casino, round_id, operator, server_seed_hash, betting_ends_at,
+                        opened_slot, opened_signature)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        event.round.to_string(),
+                        event.casino.to_string(),
+                        &event.round_id[..],
+                        event.operator.to_string(),
+                        &event.server_seed_hash[..],
+                        event.betting_ends_at,
+                        self.slot,
+                        self.signature,
+                    ],
+                )?;
+            },
+            CasinoEvent::RoundBetPlaced(event) => {
+                // Every colour bet on gets a row of its own
+                self.db.execute(
+                    "INSERT INTO games (address, casino, player, game_id, game_type, wager, bet_amount,
+                        placed_slot, placed_signature)
+                     VALUES (?1, ?2, ?3, ?4, 'roulette_round', ?5, ?6, ?7, ?8)",
+                    params![
+                        event.bet.to_string(),
+                        event.casino.to_string(),
+                        event.player.to_string(),
+                        &event.round_id[..],
+                        format!("{:?}", event.color),
+                        event.amount,
+                        self.slot,
+                        self.signature,
+                    ],
+                )?;
+            },
+            CasinoEvent::RoundLocked(event) => {
+                self.db.execute(
+                    "UPDATE rounds SET locked_slot = ?2 WHERE address = ?1",
+                    params![event.round.to_string(), self.slot],
+                )?;
+            },
+            CasinoEvent::RoundSpun(event) => {
+                self.db.execute(
+                    "UPDATE rounds SET spun_slot = ?2, spun_by = ?3, result_hash = ?4, winning_slot = ?5,
+                        locked_slot = COALESCE(locked_slot, ?2)
+                     WHERE address = ?1",
+                    params![
+                        event.round.to_string(),
+                        self.slot,
+                        event.operator.to_string(),
+                        &event.result_hash[..],
+                        event.winning_slot,
+                    ],
+                )?;
+            },
+            CasinoEvent::RoundBetSettled(event) => {
+                // Settling the bets closes their account, so all of the
+                // player's rows in the round are settled together
+                if let Some(result_hash) = self.round_result_hash(&event.round)? {
+                    let color = roulette::slot_color(roulette::winning_slot(&result_hash));
+                    self.db.execute(
+                        "UPDATE games SET settled_slot = ?2, settled_by = ?3, result_hash = ?4, is_win = wager = ?5,
+                            win_amount = CASE WHEN wager = ?5 THEN bet_amount * ?6 ELSE 0 END, closed_slot = ?2
+                         WHERE address = ?1 AND closed_slot IS NULL",
+                        params![
+                            event.bet.to_string(),
+                            self.slot,
+                            event.operator.to_string(),
+                            &result_hash[..],
+                            format!("{:?}", color),
+                            color.multiplier(),
+                        ],
+                    )?;
+                }
+                if event.win_amount > 0 {
+                    self.record_payout(&event.casino, &event.bet, &event.player, "win", event.win_amount)?;
+                }
+            },
+            // Commitments are consumed by the bets placed against them, and
+            // only the bets are indexed
+            CasinoEvent::CommitmentPosted(_) => {},
+            // The round is in one of the tables, depending on its game
+            CasinoEvent::RoundClosed(event) => {
+                for table in ["crash_rounds", "rounds"] {
+                    self.db.execute(
+                        &format!("UPDATE {} SET closed_slot = ?2 WHERE address = ?1", table),
+                        params![event.round.to_string(), self.slot],
+                    )?;
+                }
+            },
+        }
+
+        self.event_index += 1;
+        Ok(())
+    }
+
+    // The game accounts of a `SettleBatch`, which follow the fixed accounts with
+    // the payee (and its token account in token casinos) after each
+    fn batch_games(&self, instruction: &DecodedInstruction) -> Result<Vec<Pubkey>, IndexerError> {
+        let (first, stride) = match self.casino_state(&instruction.account(1))?.mint {
+            Some(_) => (7, 3),
+            None => (5, 2),
+        };
+        Ok(instruction.accounts.get(first..).unwrap_or_default().iter().step_by(stride).copied().collect())
+    }
+
+    // Records the seeds of a bet or settlement, which the events don't carry
+    fn record_seeds(&self, instruction: &DecodedInstruction) -> Result<(), IndexerError> {
+        if let CasinoInstruction::SettleCrashRound { server_seed } = &instruction.instruction {
+            self.db.execute(
+                "UPDATE crash_rounds SET server_seed = ?2 WHERE address = ?1",
+                params![instruction.account(3).to_string(), &server_seed[..]],
+            )?;
+            return Ok(());
+        }
+        if let CasinoInstruction::SpinRound { server_seed } = &instruction.instruction {
+            self.db.execute(
+                "UPDATE rounds SET server_seed = ?2 WHERE address = ?1",
+                params![instruction.account(3).to_string(), &server_seed[..]],
+            )?;
+            return Ok(());
+        }
+        if let CasinoInstruction::SettleBatch { results } = &instruction.instruction {
+            for (game, result) in self.batch_games(instruction)?.iter().zip(results) {
+                self.db.execute(
+                    "UPDATE games SET server_seed = ?2 WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)",
+                    params![game.to_string(), &result.server_seed[..]],
+                )?;
+            }
+            return Ok(());
+        }
+
+        let (column, game, seed) = match &instruction.instruction {
+            CasinoInstruction::PlaceBet { client_seed, .. }
+            | CasinoInstruction::PlaceRouletteBet { client_seed, .. }
+            | CasinoInstruction::PlaceRouletteBets { client_seed, .. }
+            | CasinoInstruction::PlaceDiceBet { client_seed, .. }
+            | CasinoInstruction::PlaceCoinflipBet { client_seed, .. }
+            | CasinoInstruction::OpenCoinflipChallenge { client_seed, .. } => ("client_seed", instruction.account(2), client_seed),
+            CasinoInstruction::SettleGame { server_seed, .. } => ("server_seed", instruction.account(3), server_seed),
+            _ => return Ok(()),
+        };
+        self.db.execute(
+            &format!("UPDATE games SET {} = ?2 WHERE id = (SELECT MAX(id) FROM games WHERE address = ?1)", column),
+            params![game.to_string(), &seed[..]],
+        )?;
+        Ok(())
+    }
+
+    // The opponent of the latest game at `address`, if it was a challenge
+    fn game_opponent(&self, address: &Pubkey) -> Result<Option<Pubkey>, IndexerError> {
+        let opponent = self.db
+            .query_row(
+                "SELECT opponent FROM games WHERE address = ?1 ORDER BY id DESC LIMIT 1",
+                [address.to_string()],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?
+            .flatten();
+        opponent.as_deref().map(transaction::parse_pubkey).transpose()
+    }
+
+    fn round_result_hash(&self, address: &Pubkey) -> Result<Option<[u8; 32]>, IndexerError> {
+        let hash = self.db
+            .query_row(
+                "SELECT result_hash FROM rounds WHERE address = ?1",
+                [address.to_string()],
+                |row| row.get::<_, Option<Vec<u8>>>(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(hash.and_then(|hash| hash.try_into().ok()))
+    }
+
+    fn casino_state(&self, casino: &Pubkey) -> Result<CasinoRow, IndexerError> {
+        Ok(self.db
+            .query_row(
+                "SELECT authority, mint, house_edge, min_bet, max_bet, settle_timeout, betting_paused,
+                    settlement_paused
+                 FROM casinos WHERE casino = ?1",
+                [casino.to_string()],
+                |row| {
+                    Ok(CasinoRow {
+                        authority: row.get(0)?,
+                        mint: row.get(1)?,
+                        house_edge: row.get(2)?,
+                        min_bet: row.get(3)?,
+                        max_bet: row.get(4)?,
+                        settle_timeout: row.get(5)?,
+                        betting_paused: row.get(6)?,
+                        settlement_paused: row.get(7)?,
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    // Appends a row with the casino's latest state after `update`
+    fn record_casino_state<F: FnOnce(&mut CasinoRow)>(&self, casino: &Pubkey, update: F) -> Result<(), IndexerError> {
+        let mut state = self.casino_state(casino)?;
+        update(&mut state);
+        self.db.execute(
+            "INSERT INTO casino_states (casino, slot, signature, event_index, authority, mint, house_edge,
+                min_bet, max_bet, settle_timeout, betting_paused, settlement_paused)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                casino.to_string(),
+                self.slot,
+                self.signature,
+                self.event_index,
+                state.authority,
+                state.mint,
+                state.house_edge,
+                state.min_bet,
+                state.max_bet,
+                state.settle_timeout,
+                state.betting_paused,
+                state.settlement_paused,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn record_action(&self, casino: &Pubkey, action: &str, account: Option<&Pubkey>, details: Value) -> Result<(), IndexerError> {
+        self.db.execute(
+            "INSERT INTO operator_actions (signature, event_index, slot, casino, action, account, details)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                self.signature,
+                self.event_index,
+                self.slot,
+                casino.to_string(),
+                action,
+                account.map(|account| account.to_string()),
+                details.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn record_payout(&self, casino: &Pubkey, game: &Pubkey, player: &Pubkey, kind: &str, amount: u64) -> Result<(), IndexerError> {
+        self.db.execute(
+            "INSERT INTO payouts (signature, event_index, slot, casino, game, player, kind, amount)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.signature,
+                self.event_index,
+                self.slot,
+                casino.to_string(),
+                game.to_string(),
+                player.to_string(),
+                kind,
+                amount,
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Closes the open rows of a game account, of which a roulette round bet has
+    // one per colour
+    fn close_game(&self, game: &Pubkey) -> Result<(), IndexerError> {
+        self.db.execute(
+            "UPDATE games SET closed_slot = ?2 WHERE address = ?1 AND closed_slot IS NULL",
+            params![game.to_string(), self.slot],
+        )?;
+        Ok(())
+    }
+}
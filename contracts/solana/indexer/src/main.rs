@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: ISC
+use casino_indexer::{rpc::RpcClient, Indexer, TransactionDump, TransactionRecord};
+use clap::{Parser, Subcommand};
+use solana_program::pubkey::Pubkey;
+use std::{error::Error, fs, path::PathBuf, thread, time::Duration};
+
+/// Builds a SQLite bet history from the casino program's transactions
+#[derive(Parser)]
+#[command(name = "casino-indexer")]
+struct Args {
+    /// SQLite database to write to
+    #[arg(long, default_value = "casino-index.sqlite")]
+    db: PathBuf,
+
+    /// Address of the casino program
+    #[arg(long, default_value_t = casino_game::id::id())]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index JSON files holding an array of `getTransaction` results
+    Dump {
+        files: Vec<PathBuf>,
+    },
+
+    /// Index transactions from a validator's JSON-RPC API
+    Rpc {
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+
+        /// Keep polling for new transactions, waiting this many seconds in between
+        #[arg(long)]
+        poll: Option<u64>,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let mut indexer = Indexer::open(&args.db, args.program_id)?;
+
+    match args.command {
+        Command::Dump { files } => {
+            let mut transactions = Vec::new();
+            for file in files {
+                transactions.extend(TransactionRecord::parse_dump(&fs::read_to_string(file)?)?);
+            }
+            let indexed = indexer.sync(&TransactionDump::new(transactions))?;
+            println!("Indexed {} transactions", indexed);
+        },
+        Command::Rpc { url, poll } => {
+            let client = RpcClient::new(&url);
+            loop {
+                let indexed = indexer.sync(&client)?;
+                println!(
+                    "Indexed {} transactions, finalized up to slot {}",
+                    indexed,
+                    indexer.finalized_slot()?.unwrap_or_default(),
+                );
+
+                match poll {
+                    Some(seconds) => thread::sleep(Duration::from_secs(seconds)),
+                    None => break,
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: ISC
+//! Reads transactions from a validator's JSON-RPC API, e.g. a local
+//! `solana-test-validator` at `http://127.0.0.1:8899`.
+use serde_json::{json, Map, Value};
+use solana_program::pubkey::Pubkey;
+
+use crate::{IndexerError, SignatureInfo, TransactionRecord, TransactionSource};
+
+// Most signatures `getSignaturesForAddress` returns per request
+const SIGNATURES_PAGE_SIZE: usize = 1000;
+
+/// A minimal blocking JSON-RPC client
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, IndexerError> {
+        let mut response: Value = self.agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|err| IndexerError::Rpc(err.to_string()))?
+            .into_json()
+            .map_err(|err| IndexerError::Rpc(err.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(IndexerError::Rpc(error.to_string()));
+        }
+        Ok(response["result"].take())
+    }
+}
+
+impl TransactionSource for RpcClient {
+    fn finalized_slot(&self) -> Result<u64, IndexerError> {
+        self.call("getSlot", json!([{ "commitment": "finalized" }]))?
+            .as_u64()
+            .ok_or_else(|| IndexerError::Rpc("getSlot did not return a slot".to_string()))
+    }
+
+    fn signatures(&self, program_id: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, IndexerError> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        // Signatures come newest first, a page at a time
+        loop {
+            let mut config = Map::new();
+            config.insert("commitment".to_string(), json!("confirmed"));
+            config.insert("limit".to_string(), json!(SIGNATURES_PAGE_SIZE));
+            if let Some(before) = &before {
+                config.insert("before".to_string(), json!(before));
+            }
+            if let Some(until) = until {
+                config.insert("until".to_string(), json!(until));
+            }
+
+            let page: Vec<Value> = serde_json::from_value(
+                self.call("getSignaturesForAddress", json!([program_id.to_string(), config]))?,
+            )?;
+            let page_len = page.len();
+
+            for entry in page {
+                let (Some(signature), Some(slot)) = (entry["signature"].as_str(), entry["slot"].as_u64()) else {
+                    return Err(IndexerError::Rpc(format!("unexpected signature entry {}", entry)));
+                };
+                signatures.push(SignatureInfo {
+                    signature: signature.to_string(),
+                    slot,
+                });
+            }
+
+            if page_len < SIGNATURES_PAGE_SIZE {
+                break;
+            }
+            before = signatures.last().map(|info| info.signature.clone());
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexerError> {
+        let result = self.call("getTransaction", json!([signature, {
+            "encoding": "json",
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0,
+        }]))?;
+
+        if result.is_null() {
+            return Ok(None);
+        }
+        TransactionRecord::from_json(result).map(Some)
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: ISC
+//! SQLite schema of the bet history database.
+//!
+//! Every row records the slot it was written in, which is what lets
+//! `Indexer::rollback` undo everything from an abandoned fork.
+
+/// Creates the tables and views if they don't exist yet
+pub const SCHEMA: &str = r#"
+-- Indexer progress: `finalized_slot` and `finalized_signature`
+CREATE TABLE IF NOT EXISTS sync_state (
+    key TEXT PRIMARY KEY,
+    value NOT NULL
+);
+
+-- Every transaction that invoked the program, including failed ones.
+-- `undecodable` marks successful ones whose logs were missing or truncated,
+-- of which only the instructions are recorded.
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    succeeded INTEGER NOT NULL,
+    undecodable INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transactions_slot ON transactions (slot);
+
+-- Decoded top-level casino instructions of successful transactions
+CREATE TABLE IF NOT EXISTS instructions (
+    signature TEXT NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    -- JSON array of the instruction's accounts
+    accounts TEXT NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (signature, instruction_index)
+);
+CREATE INDEX IF NOT EXISTS instructions_slot ON instructions (slot);
+
+-- One row per change to a casino; the latest row is its current state.
+-- Columns are NULL when the change happened before the indexer saw the casino.
+CREATE TABLE IF NOT EXISTS casino_states (
+    casino TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    authority TEXT,
+    mint TEXT,
+    house_edge INTEGER,
+    min_bet INTEGER,
+    max_bet INTEGER,
+    settle_timeout INTEGER,
+    betting_paused INTEGER,
+    settlement_paused INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS casino_states_casino ON casino_states (casino, slot);
+
+-- One row per bet. A game account can be reused once closed, so `address`
+-- is only unique among games that aren't closed. A crash bet's row has the
+-- bet account as its `address` and the round ID as its `game_id`, as do the
+-- rows of a roulette round bet, one for every colour bet on.
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    casino TEXT NOT NULL,
+    player TEXT NOT NULL,
+    game_id BLOB NOT NULL,
+    -- `standard`, `roulette`, `european_roulette`, `dice`, `coinflip`,
+    -- `coinflip_challenge`, `crash` or `roulette_round`
+    game_type TEXT NOT NULL,
+    -- The roulette colour or bets, the dice target, the coin side called or
+    -- the auto cash-out multiplier of a crash bet
+    wager TEXT,
+    bet_amount INTEGER NOT NULL,
+    client_seed BLOB,
+    placed_slot INTEGER NOT NULL,
+    placed_signature TEXT NOT NULL,
+    -- The opponent who accepted a coinflip challenge, and the slot hash
+    -- mixed into the flip when they did
+    opponent TEXT,
+    slot_hash BLOB,
+    accepted_slot INTEGER,
+    -- The multiplier a crash bet was cashed out at with `CashOut`, and when;
+    -- a cash-out at or after the crash isn't paid
+    cashout INTEGER,
+    cashout_slot INTEGER,
+    cashout_time INTEGER,
+    settled_slot INTEGER,
+    settled_by TEXT,
+    server_seed BLOB,
+    result_hash BLOB,
+    is_win INTEGER,
+    -- What the player won; an opponent's winnings are only in `payouts`
+    win_amount INTEGER,
+    closed_slot INTEGER
+);
+CREATE INDEX IF NOT EXISTS games_address ON games (address);
+CREATE INDEX IF NOT EXISTS games_player ON games (player);
+
+-- One row per crash round. Multipliers are in basis points. A round closed
+-- and opened again under the same ID replaces its row.
+CREATE TABLE IF NOT EXISTS crash_rounds (
+    address TEXT PRIMARY KEY,
+    casino TEXT NOT NULL,
+    round_id BLOB NOT NULL,
+    operator TEXT NOT NULL,
+    server_seed_hash BLOB NOT NULL,
+    house_edge INTEGER NOT NULL,
+    starts_at INTEGER NOT NULL,
+    opened_slot INTEGER NOT NULL,
+    opened_signature TEXT NOT NULL,
+    settled_slot INTEGER,
+    settled_by TEXT,
+    server_seed BLOB,
+    result_hash BLOB,
+    crash_point INTEGER,
+    closed_slot INTEGER
+);
+CREATE INDEX IF NOT EXISTS crash_rounds_casino ON crash_rounds (casino, opened_slot);
+
+-- One row per shared roulette round, replaced like a crash round's if the
+-- round is closed and opened again
+CREATE TABLE IF NOT EXISTS rounds (
+    address TEXT PRIMARY KEY,
+    casino TEXT NOT NULL,
+    round_id BLOB NOT NULL,
+    operator TEXT NOT NULL,
+    server_seed_hash BLOB NOT NULL,
+    betting_ends_at INTEGER NOT NULL,
+    opened_slot INTEGER NOT NULL,
+    opened_signature TEXT NOT NULL,
+    locked_slot INTEGER,
+    spun_slot INTEGER,
+    spun_by TEXT,
+    server_seed BLOB,
+    result_hash BLOB,
+    winning_slot INTEGER,
+    closed_slot INTEGER
+);
+CREATE INDEX IF NOT EXISTS rounds_casino ON rounds (casino, opened_slot);
+
+-- Lamports (or token base units) paid to players: `win` or `refund`
+CREATE TABLE IF NOT EXISTS payouts (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    casino TEXT NOT NULL,
+    game TEXT NOT NULL,
+    player TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS payouts_player ON payouts (player);
+
+-- Administrative actions. `account` is the key the action is about: the
+-- operator added, removed or pausing, the funder, the recipient, ...
+CREATE TABLE IF NOT EXISTS operator_actions (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    casino TEXT NOT NULL,
+    action TEXT NOT NULL,
+    account TEXT,
+    -- JSON object with the action's parameters
+    details TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS operator_actions_casino ON operator_actions (casino, slot);
+
+CREATE VIEW IF NOT EXISTS casinos AS
+SELECT s.* FROM casino_states s
+WHERE s.rowid = (
+    SELECT latest.rowid FROM casino_states latest
+    WHERE latest.casino = s.casino
+    ORDER BY latest.slot DESC, latest.rowid DESC
+    LIMIT 1
+);
+
+CREATE VIEW IF NOT EXISTS players AS
+SELECT
+    g.player AS player,
+    COUNT(*) AS games,
+    SUM(g.bet_amount) AS total_wagered,
+    SUM(CASE WHEN g.is_win = 1 THEN 1 ELSE 0 END) AS wins,
+    COALESCE(SUM(g.win_amount), 0) AS total_won,
+    (SELECT COALESCE(SUM(p.amount), 0) FROM payouts p
+     WHERE p.player = g.player AND p.kind = 'refund') AS total_refunded,
+    MIN(g.placed_slot) AS first_slot,
+    MAX(g.placed_slot) AS last_slot
+FROM games g
+GROUP BY g.player;
+"#;
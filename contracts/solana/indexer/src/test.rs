@@ -0,0 +1,863 @@
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{Indexer, IndexerError, SignatureInfo, TransactionDump, TransactionRecord, TransactionSource},
+        base64::{engine::general_purpose::STANDARD, Engine as _},
+        borsh::BorshSerialize,
+        casino_game::{
+            client,
+            events::{self, CasinoEvent},
+            id, process_instruction, randomness,
+            roulette::{self, RouletteColor},
+            GameType, SettleResult,
+        },
+        solana_program::{
+            account_info::AccountInfo,
+            entrypoint::ProgramResult,
+            instruction::Instruction,
+            program_stubs::{self, SyscallStubs},
+            pubkey::Pubkey,
+        },
+        solana_program_test::*,
+        solana_sdk::{
+            account::Account,
+            hash::Hash,
+            signature::{Keypair, Signer},
+            transaction::Transaction,
+        },
+        std::sync::Once,
+    };
+
+    // A source that lists at most `page_size` signatures per call, like an
+    // RPC node capping a page
+    struct PagedSource {
+        dump: TransactionDump,
+        page_size: usize,
+    }
+
+    impl TransactionSource for PagedSource {
+        fn finalized_slot(&self) -> Result<u64, IndexerError> {
+            self.dump.finalized_slot()
+        }
+
+        fn signatures(&self, program_id: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, IndexerError> {
+            let mut signatures = self.dump.signatures(program_id, until)?;
+            signatures.truncate(self.page_size);
+            Ok(signatures)
+        }
+
+        fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexerError> {
+            self.dump.transaction(signature)
+        }
+    }
+
+    // Helper function to create a program test environment
+    fn program_test() -> ProgramTest {
+        ProgramTest::new(
+            "casino_game",
+            id::id(),
+            processor!(process_instruction),
+        )
+    }
+
+    // Marks the messages `LogDataStubs` logs in place of `Program data:` lines
+    const LOG_DATA_TAG: &str = "sol_log_data: ";
+
+    // `solana-program-test` runs the program natively, where `sol_log_data`
+    // only prints to stdout. These stubs write the data to the program's log
+    // instead, from which `process` restores the `Program data:` lines.
+    struct LogDataStubs(Box<dyn SyscallStubs>);
+
+    impl SyscallStubs for LogDataStubs {
+        fn sol_log(&self, message: &str) {
+            self.0.sol_log(message)
+        }
+
+        fn sol_log_compute_units(&self) {
+            self.0.sol_log_compute_units()
+        }
+
+        fn sol_remaining_compute_units(&self) -> u64 {
+            self.0.sol_remaining_compute_units()
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+        }
+
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_clock_sysvar(var_addr)
+        }
+
+        fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_epoch_schedule_sysvar(var_addr)
+        }
+
+        fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_fees_sysvar(var_addr)
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_rent_sysvar(var_addr)
+        }
+
+        fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_epoch_rewards_sysvar(var_addr)
+        }
+
+        fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_last_restart_slot(var_addr)
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            self.0.sol_get_return_data()
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            self.0.sol_set_return_data(data)
+        }
+
+        fn sol_log_data(&self, fields: &[&[u8]]) {
+            let fields: Vec<String> = fields.iter().map(|field| STANDARD.encode(field)).collect();
+            self.0.sol_log(&format!("{}{}", LOG_DATA_TAG, fields.join(" ")))
+        }
+
+        fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+            self.0.sol_get_processed_sibling_instruction(index)
+        }
+
+        fn sol_get_stack_height(&self) -> u64 {
+            self.0.sol_get_stack_height()
+        }
+    }
+
+    // Helper function to start a program test whose transactions log their
+    // events. `ProgramTest::start` installs its stubs the first time it runs,
+    // and no test processes a transaction before they're wrapped here.
+    async fn start(program_test: ProgramTest) -> (BanksClient, Keypair, Hash) {
+        static WRAP_STUBS: Once = Once::new();
+
+        let started = program_test.start().await;
+        WRAP_STUBS.call_once(|| {
+            struct NoStubs;
+            impl SyscallStubs for NoStubs {}
+
+            let stubs = program_stubs::set_syscall_stubs(Box::new(NoStubs));
+            program_stubs::set_syscall_stubs(Box::new(LogDataStubs(stubs)));
+        });
+        started
+    }
+
+    // Helper function to process a transaction and record it the way the
+    // indexer reads it
+    async fn process(
+        banks_client: &mut BanksClient,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+        recent_blockhash: Hash,
+    ) -> TransactionRecord {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+        transaction.sign(signers, recent_blockhash);
+        let signature = transaction.signatures[0].to_string();
+        let message = transaction.message.clone();
+
+        let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        let slot = banks_client.get_root_slot().await.unwrap();
+        let log_messages = result
+            .metadata
+            .map(|metadata| metadata.log_messages)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|message| match message.strip_prefix("Program log: ").and_then(|message| message.strip_prefix(LOG_DATA_TAG)) {
+                Some(data) => format!("Program data: {}", data),
+                None => message,
+            })
+            .collect();
+        TransactionRecord::from_message(signature, slot, &message, result.result.is_ok(), log_messages)
+    }
+
+    // Helper function to build a transaction whose logs carry `events`
+    fn logged_transaction(signature: &str, slot: u64, events: &[CasinoEvent]) -> TransactionRecord {
+        program_logged_transaction(&id::id(), signature, slot, events)
+    }
+
+    // Helper function to build a transaction in which the program deployed at
+    // `program_id` logged `events`
+    fn program_logged_transaction(
+        program_id: &Pubkey,
+        signature: &str,
+        slot: u64,
+        events: &[CasinoEvent],
+    ) -> TransactionRecord {
+        let mut log_messages = vec![format!("Program {} invoke [1]", program_id)];
+        for event in events {
+            log_messages.push(format!("Program data: {}", STANDARD.encode(event.try_to_vec().unwrap())));
+        }
+        log_messages.push(format!("Program {} success", program_id));
+
+        TransactionRecord {
+            signature: signature.to_string(),
+            slot,
+            block_time: None,
+            succeeded: true,
+            account_keys: vec![*program_id],
+            instructions: Vec::new(),
+            log_messages,
+        }
+    }
+
+    fn bet_placed(casino: &Pubkey, game: &Pubkey, bet_amount: u64) -> CasinoEvent {
+        CasinoEvent::BetPlaced(events::BetPlaced {
+            casino: *casino,
+            game: *game,
+            player: Pubkey::new_unique(),
+            game_id: [1; 32],
+            bet_amount,
+            game_type: GameType::Standard,
+        })
+    }
+
+    fn game_addresses(indexer: &Indexer) -> Vec<String> {
+        let mut statement = indexer.connection().prepare("SELECT address FROM games ORDER BY id").unwrap();
+        let addresses = statement.query_map([], |row| row.get(0)).unwrap();
+        addresses.map(|address| address.unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_index_program_test_transactions() {
+        let mut program_test = program_test();
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        for account in [&authority, &player] {
+            program_test.add_account(
+                account.pubkey(),
+                Account {
+                    lamports: 10_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+
+        let (mut banks_client, payer, recent_blockhash) = start(program_test).await;
+
+        // Pick server seeds so the first game wins and the second loses
+        let client_seed = [7; 32];
+        let (winning_game, _) = client::find_game_address(&casino_state_pubkey, &player.pubkey(), &[1; 32]);
+        let (losing_game, _) = client::find_game_address(&casino_state_pubkey, &player.pubkey(), &[2; 32]);
+        let winning_seed = (0..=255u8)
+            .map(|byte| [byte; 32])
+            .find(|seed| client::game_outcome(seed, &client_seed, &winning_game))
+            .unwrap();
+        let losing_seed = (0..=255u8)
+            .map(|byte| [byte; 32])
+            .find(|seed| !client::game_outcome(seed, &client_seed, &losing_game))
+            .unwrap();
+        let commitment = |seed: &[u8; 32]| solana_program::hash::hash(seed).to_bytes();
+
+        let bet_amount = 10_000_000;
+        let win_amount = 19_500_000;
+        let mut records = Vec::new();
+
+        records.push(process(
+            &mut banks_client,
+            &[client::initialize(&authority.pubkey(), &casino_state_pubkey, 250, 100_000, 1_000_000_000)],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::fund_vault(&authority.pubkey(), &casino_state_pubkey, 1_000_000_000)],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[
+                client::post_commitment(&authority.pubkey(), &casino_state_pubkey, commitment(&winning_seed)),
+                client::place_bet(
+                    &player.pubkey(),
+                    &casino_state_pubkey,
+                    &winning_game,
+                    [1; 32],
+                    bet_amount,
+                    client_seed,
+                    commitment(&winning_seed),
+                ),
+            ],
+            &payer,
+            &[&payer, &authority, &player],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::settle_game(
+                &authority.pubkey(),
+                &casino_state_pubkey,
+                &winning_game,
+                &player.pubkey(),
+                true,
+                win_amount,
+                winning_seed,
+            )],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+
+        // Place and settle the second game in one transaction
+        records.push(process(
+            &mut banks_client,
+            &[
+                client::post_commitment(&authority.pubkey(), &casino_state_pubkey, commitment(&losing_seed)),
+                client::place_bet(
+                    &player.pubkey(),
+                    &casino_state_pubkey,
+                    &losing_game,
+                    [2; 32],
+                    bet_amount,
+                    client_seed,
+                    commitment(&losing_seed),
+                ),
+                client::settle_game(
+                    &authority.pubkey(),
+                    &casino_state_pubkey,
+                    &losing_game,
+                    &player.pubkey(),
+                    false,
+                    0,
+                    losing_seed,
+                ),
+            ],
+            &payer,
+            &[&payer, &player, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::close_game(&authority.pubkey(), &casino_state_pubkey, &winning_game, &player.pubkey())],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::update_params(&authority.pubkey(), &casino_state_pubkey, Some(300), None, None, None)],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::pause(&authority.pubkey(), &casino_state_pubkey, true, false)],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+
+        // Settling the closed game again fails and must change nothing
+        let failed = process(
+            &mut banks_client,
+            &[client::settle_game(
+                &authority.pubkey(),
+                &casino_state_pubkey,
+                &winning_game,
+                &player.pubkey(),
+                false,
+                0,
+                winning_seed,
+            )],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await;
+        assert!(!failed.succeeded);
+        records.push(failed);
+
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        for record in &records {
+            assert!(indexer.index_transaction(record).unwrap());
+        }
+        assert!(!indexer.index_transaction(&records[0]).unwrap());
+        let db = indexer.connection();
+
+        // Verify the games
+        let (is_win, paid, closed, stored_client_seed, server_seed, result_hash): (
+            bool,
+            u64,
+            Option<u64>,
+            Vec<u8>,
+            Vec<u8>,
+            Vec<u8>,
+        ) = db
+            .query_row(
+                "SELECT is_win, win_amount, closed_slot, client_seed, server_seed, result_hash
+                 FROM games WHERE address = ?1",
+                [winning_game.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+            )
+            .unwrap();
+        assert!(is_win);
+        assert_eq!(paid, win_amount);
+        assert!(closed.is_some());
+        assert_eq!(stored_client_seed, client_seed);
+        assert_eq!(server_seed, winning_seed);
+        assert_eq!(result_hash, randomness::result_hash(&winning_seed, &client_seed, &winning_game));
+
+        let (is_win, settled_by): (bool, String) = db
+            .query_row(
+                "SELECT is_win, settled_by FROM games WHERE address = ?1",
+                [losing_game.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(!is_win);
+        assert_eq!(settled_by, authority.pubkey().to_string());
+
+        // Verify the payouts and the player's totals
+        let (kind, amount): (String, u64) = db
+            .query_row("SELECT kind, amount FROM payouts", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(kind, "win");
+        assert_eq!(amount, win_amount);
+
+        let (games, wagered, wins, won): (u64, u64, u64, u64) = db
+            .query_row(
+                "SELECT games, total_wagered, wins, total_won FROM players WHERE player = ?1",
+                [player.pubkey().to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((games, wagered, wins, won), (2, 2 * bet_amount, 1, win_amount));
+
+        // Verify the casino and its operator actions
+        let (house_edge, min_bet, betting_paused, settlement_paused): (u16, u64, bool, bool) = db
+            .query_row(
+                "SELECT house_edge, min_bet, betting_paused, settlement_paused FROM casinos WHERE casino = ?1",
+                [casino_state_pubkey.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((house_edge, min_bet, betting_paused, settlement_paused), (300, 100_000, true, false));
+
+        let mut statement = db.prepare("SELECT action FROM operator_actions ORDER BY slot, rowid").unwrap();
+        let actions: Vec<String> = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|action| action.unwrap())
+            .collect();
+        assert_eq!(actions, ["initialize", "fund_vault", "update_params", "pause"]);
+
+        let failed_count: u64 = db
+            .query_row("SELECT COUNT(*) FROM transactions WHERE succeeded = 0", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(failed_count, 1);
+        let instruction_count: u64 = db
+            .query_row("SELECT COUNT(*) FROM instructions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(instruction_count, 11);
+        drop(statement);
+
+        // A JSON dump of the same transactions indexes the same way
+        let dump = serde_json::Value::from(records.iter().map(TransactionRecord::to_json).collect::<Vec<_>>());
+        let parsed = TransactionRecord::parse_dump(&dump.to_string()).unwrap();
+        assert_eq!(parsed, records);
+
+        let mut from_dump = Indexer::open_in_memory(id::id()).unwrap();
+        assert_eq!(from_dump.sync(&TransactionDump::new(parsed)).unwrap(), records.len());
+        assert_eq!(game_addresses(&from_dump), game_addresses(&indexer));
+    }
+
+    #[tokio::test]
+    async fn test_index_settle_batch() {
+        let mut program_test = program_test();
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        let (casino_state_pubkey, _) = client::find_casino_state_address(&authority.pubkey());
+        for account in [&authority, &player] {
+            program_test.add_account(
+                account.pubkey(),
+                Account {
+                    lamports: 10_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+
+        let (mut banks_client, payer, recent_blockhash) = start(program_test).await;
+
+        // One game the player wins and one it loses
+        let client_seed = [7; 32];
+        let bet_amount = 1_000_000;
+        let win_amount = 1_950_000;
+        let mut instructions = vec![
+            client::initialize(&authority.pubkey(), &casino_state_pubkey, 250, 100_000, 1_000_000_000),
+            client::fund_vault(&authority.pubkey(), &casino_state_pubkey, 1_000_000_000),
+        ];
+        let mut settlements = Vec::new();
+        for (game_id, is_win) in [([1; 32], true), ([2; 32], false)] {
+            let (game, _) = client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+            let server_seed = (0..=255u8)
+                .map(|byte| [byte; 32])
+                .find(|seed| client::game_outcome(seed, &client_seed, &game) == is_win)
+                .unwrap();
+            let server_seed_hash = randomness::commit(&server_seed);
+            instructions.push(client::post_commitment(&authority.pubkey(), &casino_state_pubkey, server_seed_hash));
+            instructions.push(client::place_bet(
+                &player.pubkey(),
+                &casino_state_pubkey,
+                &game,
+                game_id,
+                bet_amount,
+                client_seed,
+                server_seed_hash,
+            ));
+            let result = SettleResult {
+                is_win,
+                win_amount: if is_win { win_amount } else { 0 },
+                server_seed,
+            };
+            settlements.push((game, player.pubkey(), result));
+        }
+
+        let mut records = Vec::new();
+        records.push(process(
+            &mut banks_client,
+            &instructions,
+            &payer,
+            &[&payer, &authority, &player],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &client::settle_batch(&authority.pubkey(), &casino_state_pubkey, None, &settlements),
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        assert!(records.iter().all(|record| record.succeeded));
+
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        for record in &records {
+            assert!(indexer.index_transaction(record).unwrap());
+        }
+
+        let settled_games = |indexer: &Indexer| {
+            let mut statement = indexer
+                .connection()
+                .prepare("SELECT address, is_win, win_amount, server_seed, settled_by FROM games ORDER BY id")
+                .unwrap();
+            let games = statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+                .unwrap()
+                .map(|game| game.unwrap());
+            games.collect::<Vec<(String, bool, u64, Vec<u8>, String)>>()
+        };
+        let expected: Vec<(String, bool, u64, Vec<u8>, String)> = settlements
+            .iter()
+            .map(|(game, _, result)| {
+                (
+                    game.to_string(),
+                    result.is_win,
+                    result.win_amount,
+                    result.server_seed.to_vec(),
+                    authority.pubkey().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(settled_games(&indexer), expected);
+
+        let won: u64 = indexer
+            .connection()
+            .query_row("SELECT SUM(amount) FROM payouts WHERE kind = 'win'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(won, win_amount);
+
+        // Without the logs the settlements are recorded as undecodable
+        // rather than guessed from the instructions
+        let mut without_logs = Indexer::open_in_memory(id::id()).unwrap();
+        for record in &records {
+            let record = TransactionRecord {
+                log_messages: Vec::new(),
+                ..record.clone()
+            };
+            assert!(without_logs.index_transaction(&record).unwrap());
+        }
+        assert!(settled_games(&without_logs).is_empty());
+        let undecodable: u64 = without_logs
+            .connection()
+            .query_row("SELECT COUNT(*) FROM transactions WHERE undecodable", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(undecodable, records.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_index_roulette_round() {
+        let mut program_test = program_test();
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let (casino_state_pubkey, _) = client::find_casino_state_address(&authority.pubkey());
+        for account in [&authority, &alice, &bob] {
+            program_test.add_account(
+                account.pubkey(),
+                Account {
+                    lamports: 10_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+
+        let (mut banks_client, payer, recent_blockhash) = start(program_test).await;
+
+        // Pick a server seed that lands the round on red
+        let round_id = [3; 32];
+        let (round, _) = client::find_round_address(&casino_state_pubkey, &round_id);
+        let server_seed = (0..=255u8)
+            .map(|byte| [byte; 32])
+            .find(|seed| {
+                let result_hash = randomness::result_hash(seed, &round_id, &round);
+                roulette::slot_color(roulette::winning_slot(&result_hash)) == RouletteColor::Red
+            })
+            .unwrap();
+        let clock: solana_program::clock::Clock = banks_client.get_sysvar().await.unwrap();
+
+        let mut records = Vec::new();
+        records.push(process(
+            &mut banks_client,
+            &[
+                client::initialize(&authority.pubkey(), &casino_state_pubkey, 250, 100_000, 1_000_000_000),
+                client::fund_vault(&authority.pubkey(), &casino_state_pubkey, 1_000_000_000),
+                client::open_round(
+                    &authority.pubkey(),
+                    &casino_state_pubkey,
+                    round_id,
+                    randomness::commit(&server_seed),
+                    clock.unix_timestamp + 30,
+                ),
+            ],
+            &payer,
+            &[&payer, &authority],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[
+                client::place_round_bet(&alice.pubkey(), &casino_state_pubkey, &round, RouletteColor::Red, 1_000_000),
+                client::place_round_bet(&alice.pubkey(), &casino_state_pubkey, &round, RouletteColor::Green, 500_000),
+            ],
+            &payer,
+            &[&payer, &alice],
+            recent_blockhash,
+        ).await);
+        records.push(process(
+            &mut banks_client,
+            &[client::place_round_bet(&bob.pubkey(), &casino_state_pubkey, &round, RouletteColor::Black, 2_000_000)],
+            &payer,
+            &[&payer, &bob],
+            recent_blockhash,
+        ).await);
+
+        // Lock, spin and pay the round out in one transaction
+        let mut instructions = vec![
+            client::lock_round(&authority.pubkey(), &casino_state_pubkey, &round),
+            client::spin_round(&authority.pubkey(), &casino_state_pubkey, &round, server_seed),
+        ];
+        instructions.extend(client::settle_round_bets(
+            &authority.pubkey(),
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &round,
+            None,
+            &[alice.pubkey(), bob.pubkey()],
+        ));
+        records.push(process(&mut banks_client, &instructions, &payer, &[&payer, &authority], recent_blockhash).await);
+        assert!(records.iter().all(|record| record.succeeded));
+
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        for record in &records {
+            assert!(indexer.index_transaction(record).unwrap());
+        }
+        let db = indexer.connection();
+
+        // Every colour bet on has a row, settled by the one spin
+        let mut statement = db
+            .prepare(
+                "SELECT player, wager, bet_amount, is_win, win_amount, closed_slot IS NOT NULL FROM games
+                 WHERE game_type = 'roulette_round' ORDER BY id",
+            )
+            .unwrap();
+        let games: Vec<(String, String, u64, bool, u64, bool)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)))
+            .unwrap()
+            .map(|game| game.unwrap())
+            .collect();
+        assert_eq!(
+            games,
+            [
+                (alice.pubkey().to_string(), "Red".to_string(), 1_000_000, true, 2_000_000, true),
+                (alice.pubkey().to_string(), "Green".to_string(), 500_000, false, 0, true),
+                (bob.pubkey().to_string(), "Black".to_string(), 2_000_000, false, 0, true),
+            ]
+        );
+
+        let (player, amount): (String, u64) = db
+            .query_row("SELECT player, amount FROM payouts WHERE kind = 'win'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((player, amount), (alice.pubkey().to_string(), 2_000_000));
+
+        let (locked, winning_slot, stored_seed): (bool, u8, Vec<u8>) = db
+            .query_row(
+                "SELECT locked_slot IS NOT NULL, winning_slot, server_seed FROM rounds WHERE address = ?1",
+                [round.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert!(locked);
+        assert_eq!(roulette::slot_color(winning_slot), RouletteColor::Red);
+        assert_eq!(stored_seed, server_seed);
+    }
+
+    #[test]
+    fn test_index_logged_events() {
+        let casino = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+        let bet = bet_placed(&casino, &game, 5_000);
+        let CasinoEvent::BetPlaced(placed) = bet.clone() else { unreachable!() };
+
+        let settled = CasinoEvent::GameSettled(events::GameSettled {
+            casino,
+            game,
+            player: placed.player,
+            operator,
+            game_id: placed.game_id,
+            bet_amount: placed.bet_amount,
+            is_win: true,
+            win_amount: 9_000,
+            result_hash: [9; 32],
+        });
+        let refunded = CasinoEvent::RefundClaimed(events::RefundClaimed {
+            casino,
+            game: Pubkey::new_unique(),
+            player: placed.player,
+            amount: 1_000,
+        });
+
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        indexer.index_transaction(&logged_transaction("bet", 10, &[bet])).unwrap();
+        indexer.index_transaction(&logged_transaction("settle", 11, &[settled, refunded])).unwrap();
+        let db = indexer.connection();
+
+        let (settled_slot, settled_by, result_hash): (u64, String, Vec<u8>) = db
+            .query_row(
+                "SELECT settled_slot, settled_by, result_hash FROM games WHERE address = ?1",
+                [game.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(settled_slot, 11);
+        assert_eq!(settled_by, operator.to_string());
+        assert_eq!(result_hash, [9; 32]);
+
+        let (won, refunded): (u64, u64) = db
+            .query_row(
+                "SELECT total_won, total_refunded FROM players WHERE player = ?1",
+                [placed.player.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((won, refunded), (9_000, 1_000));
+    }
+
+    #[test]
+    fn test_index_other_program_id() {
+        let program_id = Pubkey::new_unique();
+        let casino = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let placed = program_logged_transaction(&program_id, "bet", 1, &[bet_placed(&casino, &game, 1_000)]);
+
+        // An indexer for a deployment at another address reads its events
+        let mut indexer = Indexer::open_in_memory(program_id).unwrap();
+        assert_eq!(indexer.sync(&TransactionDump::new(vec![placed.clone()])).unwrap(), 1);
+        assert_eq!(game_addresses(&indexer), [game.to_string()]);
+
+        // One for the default program ID finds nothing of its own in them
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        indexer.index_transaction(&placed).unwrap();
+        assert!(game_addresses(&indexer).is_empty());
+    }
+
+    #[test]
+    fn test_sync_rolls_back_unfinalized_slots() {
+        let casino = Pubkey::new_unique();
+        let (game_a, game_b, game_c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let placed_a = logged_transaction("a", 1, &[bet_placed(&casino, &game_a, 1_000)]);
+        let placed_b = logged_transaction("b", 2, &[bet_placed(&casino, &game_b, 1_000)]);
+        let placed_c = logged_transaction("c", 3, &[bet_placed(&casino, &game_c, 1_000)]);
+
+        // Slot 2 is confirmed but not finalized yet
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        let mut source = TransactionDump::new(vec![placed_a.clone(), placed_b]);
+        source.finalized_slot = 1;
+        assert_eq!(indexer.sync(&source).unwrap(), 2);
+        assert_eq!(indexer.finalized_slot().unwrap(), Some(1));
+        assert_eq!(game_addresses(&indexer), [game_a.to_string(), game_b.to_string()]);
+
+        // The cluster switched forks: slot 2 was skipped and slot 3 finalized
+        let source = TransactionDump::new(vec![placed_a, placed_c]);
+        assert_eq!(indexer.sync(&source).unwrap(), 1);
+        assert_eq!(indexer.finalized_slot().unwrap(), Some(3));
+        assert_eq!(game_addresses(&indexer), [game_a.to_string(), game_c.to_string()]);
+
+        // Nothing new to read, and finalized rows are kept
+        assert_eq!(indexer.sync(&source).unwrap(), 0);
+        assert_eq!(game_addresses(&indexer), [game_a.to_string(), game_c.to_string()]);
+    }
+
+    #[test]
+    fn test_sync_paged_signatures() {
+        let casino = Pubkey::new_unique();
+        let games: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let transactions = games
+            .iter()
+            .zip(1..)
+            .map(|(game, slot)| logged_transaction(&slot.to_string(), slot, &[bet_placed(&casino, game, 1_000)]))
+            .collect();
+
+        // Every slot is finalized, but only two signatures come per sync
+        let source = PagedSource {
+            dump: TransactionDump::new(transactions),
+            page_size: 2,
+        };
+        let mut indexer = Indexer::open_in_memory(id::id()).unwrap();
+        assert_eq!(indexer.sync(&source).unwrap(), 2);
+        assert_eq!(indexer.finalized_slot().unwrap(), Some(2));
+
+        // The next sync reads on from the last transaction indexed
+        assert_eq!(indexer.sync(&source).unwrap(), 1);
+        assert_eq!(indexer.finalized_slot().unwrap(), Some(3));
+        assert_eq!(game_addresses(&indexer), games.iter().map(Pubkey::to_string).collect::<Vec<_>>());
+
+        assert_eq!(indexer.sync(&source).unwrap(), 0);
+        assert_eq!(indexer.finalized_slot().unwrap(), Some(3));
+    }
+}
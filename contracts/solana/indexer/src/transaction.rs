@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: ISC
+//! The transactions the indexer reads, and their JSON form.
+//!
+//! The JSON form is the one `getTransaction` returns with `"encoding": "json"`,
+//! so a dump is simply a JSON array of `getTransaction` results.
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_program::{instruction::CompiledInstruction, message::Message, pubkey::Pubkey};
+use std::str::FromStr;
+
+use crate::IndexerError;
+
+/// A confirmed transaction, reduced to what the indexer needs
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionRecord {
+    /// First signature of the transaction, base58 encoded
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether the transaction succeeded; failed transactions change nothing
+    pub succeeded: bool,
+    /// The message's account keys followed by any addresses loaded from
+    /// lookup tables, so instruction account indexes resolve against it
+    pub account_keys: Vec<Pubkey>,
+    /// Top-level instructions
+    pub instructions: Vec<CompiledInstruction>,
+    pub log_messages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedTransactionWithMeta {
+    slot: u64,
+    block_time: Option<i64>,
+    transaction: EncodedTransaction,
+    meta: Option<EncodedMeta>,
+}
+
+#[derive(Deserialize)]
+struct EncodedTransaction {
+    signatures: Vec<String>,
+    message: EncodedMessage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedMessage {
+    account_keys: Vec<String>,
+    instructions: Vec<EncodedInstruction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedInstruction {
+    program_id_index: u8,
+    accounts: Vec<u8>,
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedMeta {
+    err: Option<Value>,
+    log_messages: Option<Vec<String>>,
+    loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Deserialize)]
+struct LoadedAddresses {
+    writable: Vec<String>,
+    readonly: Vec<String>,
+}
+
+impl TransactionRecord {
+    /// Builds a record from a transaction message, e.g. one processed by
+    /// `solana-program-test`
+    pub fn from_message(
+        signature: String,
+        slot: u64,
+        message: &Message,
+        succeeded: bool,
+        log_messages: Vec<String>,
+    ) -> Self {
+        Self {
+            signature,
+            slot,
+            block_time: None,
+            succeeded,
+            account_keys: message.account_keys.clone(),
+            instructions: message.instructions.clone(),
+            log_messages,
+        }
+    }
+
+    /// Parses a `getTransaction` result
+    pub fn from_json(value: Value) -> Result<Self, IndexerError> {
+        let encoded: EncodedTransactionWithMeta = serde_json::from_value(value)?;
+        let signature = encoded.transaction.signatures.into_iter().next()
+            .ok_or_else(|| IndexerError::InvalidTransaction("no signatures".to_string()))?;
+
+        let (succeeded, log_messages, loaded_addresses) = match encoded.meta {
+            Some(meta) => (meta.err.is_none(), meta.log_messages.unwrap_or_default(), meta.loaded_addresses),
+            None => (true, Vec::new(), None),
+        };
+
+        let mut account_keys = encoded.transaction.message.account_keys;
+        if let Some(loaded) = loaded_addresses {
+            account_keys.extend(loaded.writable);
+            account_keys.extend(loaded.readonly);
+        }
+        let account_keys = account_keys
+            .iter()
+            .map(|key| parse_pubkey(key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let instructions = encoded.transaction.message.instructions
+            .into_iter()
+            .map(|instruction| {
+                let data = bs58::decode(&instruction.data).into_vec().map_err(|err| {
+                    IndexerError::InvalidTransaction(format!("instruction data: {}", err))
+                })?;
+                Ok(CompiledInstruction {
+                    program_id_index: instruction.program_id_index,
+                    accounts: instruction.accounts,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>, IndexerError>>()?;
+
+        Ok(Self {
+            signature,
+            slot: encoded.slot,
+            block_time: encoded.block_time,
+            succeeded,
+            account_keys,
+            instructions,
+            log_messages,
+        })
+    }
+
+    /// Encodes the record the way `getTransaction` would, so records built
+    /// from `solana-program-test` transactions can be written out as a dump
+    pub fn to_json(&self) -> Value {
+        let instructions: Vec<Value> = self.instructions
+            .iter()
+            .map(|instruction| json!({
+                "programIdIndex": instruction.program_id_index,
+                "accounts": instruction.accounts,
+                "data": bs58::encode(&instruction.data).into_string(),
+            }))
+            .collect();
+
+        json!({
+            "slot": self.slot,
+            "blockTime": self.block_time,
+            "transaction": {
+                "signatures": [self.signature],
+                "message": {
+                    "accountKeys": self.account_keys.iter().map(|key| key.to_string()).collect::<Vec<_>>(),
+                    "instructions": instructions,
+                },
+            },
+            "meta": {
+                "err": if self.succeeded { Value::Null } else { json!("InstructionError") },
+                "logMessages": self.log_messages,
+            },
+        })
+    }
+
+    /// Parses a dump: a JSON array of `getTransaction` results
+    pub fn parse_dump(json: &str) -> Result<Vec<Self>, IndexerError> {
+        let values: Vec<Value> = serde_json::from_str(json)?;
+        values.into_iter().map(Self::from_json).collect()
+    }
+
+    /// The program an instruction invokes
+    pub fn program_id(&self, instruction: &CompiledInstruction) -> Option<&Pubkey> {
+        self.account_keys.get(instruction.program_id_index as usize)
+    }
+
+    /// The accounts an instruction was given, in order
+    pub fn instruction_accounts(&self, instruction: &CompiledInstruction) -> Vec<Pubkey> {
+        instruction.accounts
+            .iter()
+            .filter_map(|index| self.account_keys.get(*index as usize).copied())
+            .collect()
+    }
+}
+
+pub(crate) fn parse_pubkey(key: &str) -> Result<Pubkey, IndexerError> {
+    Pubkey::from_str(key)
+        .map_err(|_| IndexerError::InvalidTransaction(format!("invalid account key {}", key)))
+}
@@ -1,115 +1,62 @@
 const {
-  Connection,
   PublicKey,
-  Keypair,
   Transaction,
   SystemProgram,
   TransactionInstruction,
   sendAndConfirmTransaction,
 } = require('@solana/web3.js');
 const { Buffer } = require('buffer');
-const borsh = require('borsh');
-
-// Program ID (replace with actual program ID after deployment)
-const PROGRAM_ID = new PublicKey('Casin0Game111111111111111111111111111111111');
-
-// Borsh schema for serializing instructions
-const CasinoInstructionSchema = new Map([
-  [
-    'Initialize',
-    {
-      kind: 'struct',
-      fields: [
-        ['house_edge', 'u16'],
-        ['min_bet', 'u64'],
-        ['max_bet', 'u64'],
-      ],
-    },
-  ],
-  [
-    'PlaceBet',
-    {
-      kind: 'struct',
-      fields: [
-        ['game_id', [32]],
-        ['bet_amount', 'u64'],
-      ],
-    },
-  ],
-  [
-    'SettleGame',
-    {
-      kind: 'struct',
-      fields: [
-        ['is_win', 'u8'],
-        ['win_amount', 'u64'],
-        ['result_hash', [32]],
-      ],
-    },
-  ],
-  [
-    'UpdateParams',
-    {
-      kind: 'struct',
-      fields: [
-        ['house_edge_present', 'u8'],
-        ['house_edge', 'u16'],
-        ['min_bet_present', 'u8'],
-        ['min_bet', 'u64'],
-        ['max_bet_present', 'u8'],
-        ['max_bet', 'u64'],
-      ],
-    },
-  ],
-  [
-    'AddOperator',
-    {
-      kind: 'struct',
-      fields: [],
-    },
-  ],
-  [
-    'RemoveOperator',
-    {
-      kind: 'struct',
-      fields: [],
-    },
-  ],
-]);
-
-// Borsh schema for deserializing state
-const CasinoStateSchema = new Map([
-  [
-    'CasinoState',
-    {
-      kind: 'struct',
-      fields: [
-        ['authority', [32]],
-        ['house_edge', 'u16'],
-        ['min_bet', 'u64'],
-        ['max_bet', 'u64'],
-        ['operators', ['pubkey']],
-      ],
-    },
-  ],
-]);
-
-const GameStateSchema = new Map([
-  [
-    'GameState',
-    {
-      kind: 'struct',
-      fields: [
-        ['player', [32]],
-        ['bet_amount', 'u64'],
-        ['is_settled', 'u8'],
-        ['is_win', 'u8'],
-        ['win_amount', 'u64'],
-        ['result_hash', [32]],
-      ],
-    },
-  ],
-]);
+const { createHash } = require('crypto');
+const idl = require('../idl/casino_game.json');
+const { IdlCodec } = require('./idl-codec');
+
+// Program ID, taken from the IDL so it always matches `declare_id!`
+const PROGRAM_ID = new PublicKey(idl.metadata.address);
+
+// Instruction data and account layouts come from the IDL
+const codec = new IdlCodec(idl, { publicKey: (bytes) => new PublicKey(bytes) });
+
+// Roles an operator can be granted, the program's `ROLE_*` flags
+const ROLE_SETTLE = 1 << 0;
+const ROLE_PAUSE = 1 << 1;
+const ROLE_PARAMS = 1 << 2;
+const ROLE_ALL = ROLE_SETTLE | ROLE_PAUSE | ROLE_PARAMS;
+
+/**
+ * Hashes a server seed into the commitment a bet is placed against
+ * @param {Buffer} serverSeed - 32-byte server seed
+ * @returns {Buffer} - `sha256(serverSeed)`
+ */
+function commit(serverSeed) {
+  return createHash('sha256').update(serverSeed).digest();
+}
+
+/**
+ * Derives the result hash of a game from the revealed server seed, the
+ * player's client seed and the game account address
+ * @param {Buffer} serverSeed - 32-byte server seed
+ * @param {Buffer} clientSeed - 32-byte client seed
+ * @param {PublicKey} game - Game account address
+ * @returns {Buffer} - The result hash
+ */
+function resultHash(serverSeed, clientSeed, game) {
+  return createHash('sha256').update(serverSeed).update(clientSeed).update(game.toBuffer()).digest();
+}
+
+/**
+ * Payout of a standard game: a win (an even roll) pays even odds less the
+ * house edge, a loss nothing
+ * @param {bigint} betAmount - Stake in lamports
+ * @param {number} houseEdge - House edge in basis points
+ * @param {Buffer} hash - The game's result hash
+ * @returns {bigint} - Win amount, 0 on a loss
+ */
+function standardPayout(betAmount, houseEdge, hash) {
+  if (hash.readUInt32BE(0) % 2 !== 0) {
+    return 0n;
+  }
+  return (2n * BigInt(betAmount) * BigInt(10000 - houseEdge)) / 10000n;
+}
 
 /**
  * CasinoClient class for interacting with the casino program
@@ -119,39 +66,110 @@ class CasinoClient {
    * Constructor for CasinoClient
    * @param {Connection} connection - Solana connection
    * @param {Keypair} payer - Payer account
+   * @param {PublicKey} [programId] - Program ID, the IDL's by default
    */
-  constructor(connection, payer) {
+  constructor(connection, payer, programId = PROGRAM_ID) {
     this.connection = connection;
     this.payer = payer;
-    this.programId = PROGRAM_ID;
+    this.programId = programId;
   }
 
   /**
    * Find the casino state PDA
-   * @param {PublicKey} authority - Authority public key
+   * @param {PublicKey} creator - Key that initialized the casino
    * @returns {[PublicKey, number]} - Casino state address and bump seed
    */
-  findCasinoStateAddress(authority) {
+  findCasinoStateAddress(creator) {
     return PublicKey.findProgramAddressSync(
-      [Buffer.from('casino'), authority.toBuffer()],
+      [Buffer.from('casino'), creator.toBuffer()],
       this.programId
     );
   }
 
   /**
-   * Find the game account PDA
+   * Find the house vault PDA of a casino
+   * @param {PublicKey} casino - Casino state address
+   * @returns {[PublicKey, number]} - Vault address and bump seed
+   */
+  findVaultAddress(casino) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('vault'), casino.toBuffer()],
+      this.programId
+    );
+  }
+
+  /**
+   * Find the operator account PDA of a key in a casino
+   * @param {PublicKey} casino - Casino state address
+   * @param {PublicKey} operator - Operator key
+   * @returns {[PublicKey, number]} - Operator account address and bump seed
+   */
+  findOperatorAddress(casino, operator) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('operator'), casino.toBuffer(), operator.toBuffer()],
+      this.programId
+    );
+  }
+
+  /**
+   * Find the game account PDA of a player's game
+   * @param {PublicKey} casino - Casino state address
+   * @param {PublicKey} player - Player public key
    * @param {Buffer} gameId - Game ID as a 32-byte buffer
    * @returns {[PublicKey, number]} - Game address and bump seed
    */
-  findGameAddress(gameId) {
+  findGameAddress(casino, player, gameId) {
     return PublicKey.findProgramAddressSync(
-      [gameId],
+      [Buffer.from('game'), casino.toBuffer(), player.toBuffer(), gameId],
       this.programId
     );
   }
 
   /**
-   * Initialize the casino
+   * Find the commitment account PDA of a server seed hash
+   * @param {PublicKey} casino - Casino state address
+   * @param {Buffer} serverSeedHash - `commit(serverSeed)`
+   * @returns {[PublicKey, number]} - Commitment address and bump seed
+   */
+  findCommitmentAddress(casino, serverSeedHash) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('commitment'), casino.toBuffer(), serverSeedHash],
+      this.programId
+    );
+  }
+
+  /**
+   * Build an instruction from its IDL entry
+   * @param {string} name - Instruction name, e.g. `PlaceBet`
+   * @param {PublicKey[]} accounts - Accounts in the order the IDL lists them
+   * @param {Object} [args] - Arguments keyed by camelCase name
+   * @returns {TransactionInstruction}
+   */
+  instruction(name, accounts, args = {}) {
+    return new TransactionInstruction({
+      keys: codec.accountMetas(name, accounts),
+      programId: this.programId,
+      data: codec.encodeInstruction(name, args),
+    });
+  }
+
+  /**
+   * Send instructions in one transaction, paid for by the client's payer
+   * @param {TransactionInstruction[]} instructions
+   * @param {Keypair[]} signers - Signers besides the payer
+   * @returns {Promise<string>} - Transaction signature
+   */
+  async send(instructions, signers) {
+    const transaction = new Transaction().add(...instructions);
+    return await sendAndConfirmTransaction(
+      this.connection,
+      transaction,
+      [this.payer, ...signers.filter((signer) => !signer.publicKey.equals(this.payer.publicKey))]
+    );
+  }
+
+  /**
+   * Initialize a SOL casino
    * @param {Keypair} authority - Authority keypair
    * @param {number} houseEdge - House edge in basis points (e.g., 250 = 2.5%)
    * @param {number} minBet - Minimum bet amount in lamports
@@ -159,326 +177,224 @@ class CasinoClient {
    * @returns {Promise<string>} - Transaction signature
    */
   async initialize(authority, houseEdge, minBet, maxBet) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority.publicKey);
-
-    // Create instruction data
-    const instructionData = {
-      house_edge: houseEdge,
-      min_bet: BigInt(minBet),
-      max_bet: BigInt(maxBet),
-    };
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([0]); // Instruction index 0 = Initialize
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      instructionData,
-      'Initialize'
+    const [casino] = this.findCasinoStateAddress(authority.publicKey);
+    const [vault] = this.findVaultAddress(casino);
+    const [operatorAccount] = this.findOperatorAddress(casino, authority.publicKey);
+
+    const instruction = this.instruction(
+      'Initialize',
+      [authority.publicKey, casino, vault, operatorAccount, SystemProgram.programId],
+      { houseEdge, minBet, maxBet }
     );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: authority.publicKey, isSigner: true, isWritable: true },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: this.programId,
-      data,
-    });
+    return await this.send([instruction], [authority]);
+  }
 
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, authority]
+  /**
+   * Fund the house vault of a SOL casino
+   * @param {Keypair} funder - Funding keypair
+   * @param {PublicKey} casino - Casino state address
+   * @param {number} amount - Amount in lamports
+   * @returns {Promise<string>} - Transaction signature
+   */
+  async fundVault(funder, casino, amount) {
+    const [vault] = this.findVaultAddress(casino);
+
+    const instruction = this.instruction(
+      'FundVault',
+      [funder.publicKey, casino, vault, SystemProgram.programId],
+      { amount }
+    );
+    return await this.send([instruction], [funder]);
+  }
+
+  /**
+   * Post an operator's commitment to a server seed, which one bet can then be
+   * placed against
+   * @param {Keypair} operator - Operator keypair with the settle role
+   * @param {PublicKey} casino - Casino state address
+   * @param {Buffer} serverSeedHash - `commit(serverSeed)`
+   * @returns {Promise<string>} - Transaction signature
+   */
+  async postCommitment(operator, casino, serverSeedHash) {
+    const [operatorAccount] = this.findOperatorAddress(casino, operator.publicKey);
+    const [commitment] = this.findCommitmentAddress(casino, serverSeedHash);
+
+    const instruction = this.instruction(
+      'PostCommitment',
+      [operator.publicKey, casino, operatorAccount, commitment, SystemProgram.programId],
+      { serverSeedHash }
     );
+    return await this.send([instruction], [operator]);
   }
 
   /**
-   * Place a bet
+   * Place a standard bet in a SOL casino against a posted commitment
    * @param {Keypair} player - Player keypair
+   * @param {PublicKey} casino - Casino state address
    * @param {Buffer} gameId - Game ID as a 32-byte buffer
    * @param {number} betAmount - Bet amount in lamports
+   * @param {Buffer} clientSeed - 32-byte seed chosen by the player
+   * @param {Buffer} serverSeedHash - The operator's commitment
    * @returns {Promise<string>} - Transaction signature
    */
-  async placeBet(player, gameId, betAmount) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(this.payer.publicKey);
-    const [gameAddress] = this.findGameAddress(gameId);
-
-    // Create instruction data
-    const instructionData = {
-      game_id: Array.from(gameId),
-      bet_amount: BigInt(betAmount),
-    };
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([1]); // Instruction index 1 = PlaceBet
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      instructionData,
-      'PlaceBet'
-    );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: player.publicKey, isSigner: true, isWritable: true },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: false },
-        { pubkey: gameAddress, isSigner: false, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: this.programId,
-      data,
-    });
-
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, player]
+  async placeBet(player, casino, gameId, betAmount, clientSeed, serverSeedHash) {
+    const [game] = this.findGameAddress(casino, player.publicKey, gameId);
+    const [vault] = this.findVaultAddress(casino);
+    const [commitment] = this.findCommitmentAddress(casino, serverSeedHash);
+
+    const instruction = this.instruction(
+      'PlaceBet',
+      [player.publicKey, casino, game, vault, SystemProgram.programId, commitment],
+      { gameId, betAmount, clientSeed, serverSeedHash }
     );
+    return await this.send([instruction], [player]);
   }
 
   /**
-   * Settle a game
-   * @param {Keypair} authority - Authority keypair
-   * @param {Buffer} gameId - Game ID as a 32-byte buffer
-   * @param {PublicKey} player - Player public key
-   * @param {boolean} isWin - Whether the player won
-   * @param {number} winAmount - Win amount in lamports
-   * @param {Buffer} resultHash - Result hash as a 32-byte buffer
+   * Settle a standard game in a SOL casino by revealing its server seed,
+   * claiming the outcome and payout the program derives from the seeds
+   * @param {Keypair} operator - Operator keypair with the settle role
+   * @param {PublicKey} casino - Casino state address
+   * @param {PublicKey} game - Game account address
+   * @param {Buffer} serverSeed - The server seed committed to
    * @returns {Promise<string>} - Transaction signature
    */
-  async settleGame(authority, gameId, player, isWin, winAmount, resultHash) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority.publicKey);
-    const [gameAddress] = this.findGameAddress(gameId);
-
-    // Create instruction data
-    const instructionData = {
-      is_win: isWin ? 1 : 0,
-      win_amount: BigInt(winAmount),
-      result_hash: Array.from(resultHash),
-    };
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([2]); // Instruction index 2 = SettleGame
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      instructionData,
-      'SettleGame'
+  async settleGame(operator, casino, game, serverSeed) {
+    const casinoState = await this.getCasinoState(casino);
+    const gameState = await this.getGameState(game);
+    const winAmount = standardPayout(
+      gameState.betAmount,
+      casinoState.houseEdge,
+      resultHash(serverSeed, gameState.clientSeed, game)
     );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: authority.publicKey, isSigner: true, isWritable: false },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: false },
-        { pubkey: gameAddress, isSigner: false, isWritable: true },
-        { pubkey: player, isSigner: false, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: this.programId,
-      data,
-    });
+    const [operatorAccount] = this.findOperatorAddress(casino, operator.publicKey);
+    const [vault] = this.findVaultAddress(casino);
 
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, authority]
+    const instruction = this.instruction(
+      'SettleGame',
+      [operator.publicKey, casino, operatorAccount, game, gameState.player, vault, SystemProgram.programId],
+      { isWin: winAmount > 0n, winAmount, serverSeed }
     );
+    return await this.send([instruction], [operator]);
   }
 
   /**
-   * Update casino parameters
-   * @param {Keypair} authority - Authority keypair
-   * @param {number|null} houseEdge - New house edge in basis points
-   * @param {number|null} minBet - New minimum bet amount in lamports
-   * @param {number|null} maxBet - New maximum bet amount in lamports
+   * Update casino parameters, leaving those not given unchanged
+   * @param {Keypair} operator - Operator keypair with the params role
+   * @param {PublicKey} casino - Casino state address
+   * @param {Object} params
+   * @param {number} [params.houseEdge] - New house edge in basis points
+   * @param {number} [params.minBet] - New minimum bet amount in lamports
+   * @param {number} [params.maxBet] - New maximum bet amount in lamports
+   * @param {number} [params.settleTimeout] - New settlement timeout in seconds
    * @returns {Promise<string>} - Transaction signature
    */
-  async updateParams(authority, houseEdge, minBet, maxBet) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority.publicKey);
-
-    // Create instruction data
-    const instructionData = {
-      house_edge_present: houseEdge !== null ? 1 : 0,
-      house_edge: houseEdge !== null ? houseEdge : 0,
-      min_bet_present: minBet !== null ? 1 : 0,
-      min_bet: minBet !== null ? BigInt(minBet) : BigInt(0),
-      max_bet_present: maxBet !== null ? 1 : 0,
-      max_bet: maxBet !== null ? BigInt(maxBet) : BigInt(0),
-    };
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([3]); // Instruction index 3 = UpdateParams
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      instructionData,
-      'UpdateParams'
-    );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: authority.publicKey, isSigner: true, isWritable: false },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: true },
-      ],
-      programId: this.programId,
-      data,
-    });
+  async updateParams(operator, casino, params) {
+    const [operatorAccount] = this.findOperatorAddress(casino, operator.publicKey);
 
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, authority]
+    const instruction = this.instruction(
+      'UpdateParams',
+      [operator.publicKey, casino, operatorAccount],
+      params
     );
+    return await this.send([instruction], [operator]);
   }
 
   /**
-   * Add an operator
+   * Add an operator, or update the roles and payout cap of an existing one
    * @param {Keypair} authority - Authority keypair
+   * @param {PublicKey} casino - Casino state address
    * @param {PublicKey} operator - Operator public key
+   * @param {number} roles - Bitmask of `ROLE_*` flags
+   * @param {number|bigint} dailyPayoutCap - Most the operator may pay out per UTC day
    * @returns {Promise<string>} - Transaction signature
    */
-  async addOperator(authority, operator) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority.publicKey);
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([4]); // Instruction index 4 = AddOperator
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      {},
-      'AddOperator'
-    );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: authority.publicKey, isSigner: true, isWritable: false },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: true },
-        { pubkey: operator, isSigner: false, isWritable: false },
-      ],
-      programId: this.programId,
-      data,
-    });
+  async addOperator(authority, casino, operator, roles, dailyPayoutCap) {
+    const [operatorAccount] = this.findOperatorAddress(casino, operator);
 
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, authority]
+    const instruction = this.instruction(
+      'AddOperator',
+      [authority.publicKey, casino, operator, operatorAccount, SystemProgram.programId],
+      { roles, dailyPayoutCap }
     );
+    return await this.send([instruction], [authority]);
   }
 
   /**
    * Remove an operator
    * @param {Keypair} authority - Authority keypair
+   * @param {PublicKey} casino - Casino state address
    * @param {PublicKey} operator - Operator public key
    * @returns {Promise<string>} - Transaction signature
    */
-  async removeOperator(authority, operator) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority.publicKey);
-
-    // Serialize instruction
-    const dataBuffer = Buffer.from([5]); // Instruction index 5 = RemoveOperator
-    const instructionBuffer = borsh.serialize(
-      CasinoInstructionSchema,
-      {},
-      'RemoveOperator'
-    );
-    const data = Buffer.concat([dataBuffer, instructionBuffer]);
-
-    // Create instruction
-    const instruction = new TransactionInstruction({
-      keys: [
-        { pubkey: authority.publicKey, isSigner: true, isWritable: false },
-        { pubkey: casinoStateAddress, isSigner: false, isWritable: true },
-        { pubkey: operator, isSigner: false, isWritable: false },
-      ],
-      programId: this.programId,
-      data,
-    });
+  async removeOperator(authority, casino, operator) {
+    const [operatorAccount] = this.findOperatorAddress(casino, operator);
 
-    // Create and send transaction
-    const transaction = new Transaction().add(instruction);
-    return await sendAndConfirmTransaction(
-      this.connection,
-      transaction,
-      [this.payer, authority]
+    const instruction = this.instruction(
+      'RemoveOperator',
+      [authority.publicKey, casino, operator, operatorAccount]
     );
+    return await this.send([instruction], [authority]);
   }
 
   /**
-   * Get casino state
-   * @param {PublicKey} authority - Authority public key
-   * @returns {Promise<Object>} - Casino state
+   * Fetch and decode a program account
+   * @param {string} name - Account type name, e.g. `GameState`
+   * @param {PublicKey} address - Account address
+   * @returns {Promise<Object>} - The account's fields keyed by camelCase name
    */
-  async getCasinoState(authority) {
-    const [casinoStateAddress] = this.findCasinoStateAddress(authority);
-    const accountInfo = await this.connection.getAccountInfo(casinoStateAddress);
-    
+  async getAccount(name, address) {
+    const accountInfo = await this.connection.getAccountInfo(address);
+
     if (!accountInfo) {
-      throw new Error('Casino state account not found');
+      throw new Error(`${name} account ${address.toBase58()} not found`);
+    }
+    if (!accountInfo.owner.equals(this.programId)) {
+      throw new Error(`${name} account ${address.toBase58()} is not owned by the program`);
     }
 
-    // Deserialize state
-    const state = borsh.deserialize(
-      CasinoStateSchema,
-      'CasinoState',
-      accountInfo.data
-    );
+    return codec.decodeAccount(name, accountInfo.data);
+  }
 
-    return {
-      authority: new PublicKey(state.authority),
-      houseEdge: state.house_edge,
-      minBet: state.min_bet,
-      maxBet: state.max_bet,
-      operators: state.operators.map(op => new PublicKey(op)),
-    };
+  /**
+   * Get casino state
+   * @param {PublicKey} casino - Casino state address
+   * @returns {Promise<Object>} - Casino state
+   */
+  async getCasinoState(casino) {
+    return await this.getAccount('CasinoState', casino);
+  }
+
+  /**
+   * Get an operator's state
+   * @param {PublicKey} casino - Casino state address
+   * @param {PublicKey} operator - Operator public key
+   * @returns {Promise<Object>} - Operator state
+   */
+  async getOperatorState(casino, operator) {
+    const [operatorAccount] = this.findOperatorAddress(casino, operator);
+    return await this.getAccount('OperatorState', operatorAccount);
   }
 
   /**
    * Get game state
-   * @param {Buffer} gameId - Game ID as a 32-byte buffer
+   * @param {PublicKey} game - Game account address
    * @returns {Promise<Object>} - Game state
    */
-  async getGameState(gameId) {
-    const [gameAddress] = this.findGameAddress(gameId);
-    const accountInfo = await this.connection.getAccountInfo(gameAddress);
-    
-    if (!accountInfo) {
-      throw new Error('Game account not found');
-    }
-
-    // Deserialize state
-    const state = borsh.deserialize(
-      GameStateSchema,
-      'GameState',
-      accountInfo.data
-    );
-
-    return {
-      player: new PublicKey(state.player),
-      betAmount: state.bet_amount,
-      isSettled: state.is_settled === 1,
-      isWin: state.is_win === 1,
-      winAmount: state.win_amount,
-      resultHash: Buffer.from(state.result_hash),
-    };
+  async getGameState(game) {
+    return await this.getAccount('GameState', game);
   }
 }
 
-module.exports = { CasinoClient }; 
\ No newline at end of file
+module.exports = {
+  CasinoClient,
+  PROGRAM_ID,
+  ROLE_SETTLE,
+  ROLE_PAUSE,
+  ROLE_PARAMS,
+  ROLE_ALL,
+  codec,
+  commit,
+  resultHash,
+  standardPayout,
+};
@@ -4,7 +4,7 @@ const {
   PublicKey,
   clusterApiUrl,
 } = require('@solana/web3.js');
-const { CasinoClient } = require('./casino-client');
+const { CasinoClient, commit } = require('./casino-client');
 const fs = require('fs');
 const crypto = require('crypto');
 
@@ -67,59 +67,68 @@ async function main() {
     );
     
     console.log('Casino initialized:', initTxId);
+    const [casino] = casinoClient.findCasinoStateAddress(authority.publicKey);
     
     // Get casino state
-    const casinoState = await casinoClient.getCasinoState(authority.publicKey);
+    const casinoState = await casinoClient.getCasinoState(casino);
     console.log('\nCasino state:');
     console.log('Authority:', casinoState.authority.toString());
     console.log('House edge:', casinoState.houseEdge / 100, '%');
     console.log('Min bet:', casinoState.minBet.toString(), 'lamports');
     console.log('Max bet:', casinoState.maxBet.toString(), 'lamports');
-    console.log('Operators:', casinoState.operators.map(op => op.toString()));
     
-    // Place a bet
+    // Fund the vault so it can pay out wins
+    const fundTxId = await casinoClient.fundVault(authority, casino, 100_000_000);
+    console.log('Vault funded:', fundTxId);
+    
+    // The authority, an operator since initialization, commits to a server seed
+    console.log('\nPosting commitment...');
+    const serverSeed = crypto.randomBytes(32);
+    const serverSeedHash = commit(serverSeed);
+    
+    const commitTxId = await casinoClient.postCommitment(authority, casino, serverSeedHash);
+    console.log('Commitment posted:', commitTxId);
+    
+    // Place a bet against it
     console.log('\nPlacing bet...');
     const gameId = crypto.randomBytes(32);
+    const clientSeed = crypto.randomBytes(32);
     const betAmount = 500_000; // 0.0005 SOL
     
     const betTxId = await casinoClient.placeBet(
       player,
+      casino,
       gameId,
-      betAmount
+      betAmount,
+      clientSeed,
+      serverSeedHash
     );
     
     console.log('Bet placed:', betTxId);
     console.log('Game ID:', gameId.toString('hex'));
     
     // Get game state
-    const gameState = await casinoClient.getGameState(gameId);
+    const [game] = casinoClient.findGameAddress(casino, player.publicKey, gameId);
+    const gameState = await casinoClient.getGameState(game);
     console.log('\nGame state:');
     console.log('Player:', gameState.player.toString());
     console.log('Bet amount:', gameState.betAmount.toString(), 'lamports');
     console.log('Is settled:', gameState.isSettled);
     
-    // Simulate a random outcome
-    const isWin = Math.random() > 0.5;
-    const winAmount = isWin ? 900_000 : 0; // 0.0009 SOL if win, 0 if loss
-    const resultHash = crypto.randomBytes(32);
-    
-    // Settle the game
+    // Settle the game by revealing the server seed; the outcome follows from the seeds
     console.log('\nSettling game...');
-    console.log('Outcome:', isWin ? 'WIN' : 'LOSS');
     
     const settleTxId = await casinoClient.settleGame(
       authority,
-      gameId,
-      player.publicKey,
-      isWin,
-      winAmount,
-      resultHash
+      casino,
+      game,
+      serverSeed
     );
     
     console.log('Game settled:', settleTxId);
     
     // Get updated game state
-    const updatedGameState = await casinoClient.getGameState(gameId);
+    const updatedGameState = await casinoClient.getGameState(game);
     console.log('\nUpdated game state:');
     console.log('Is settled:', updatedGameState.isSettled);
     console.log('Is win:', updatedGameState.isWin);
@@ -132,15 +141,14 @@ async function main() {
     
     const updateTxId = await casinoClient.updateParams(
       authority,
-      newHouseEdge,
-      null,
-      null
+      casino,
+      { houseEdge: newHouseEdge }
     );
     
     console.log('Parameters updated:', updateTxId);
     
     // Get updated casino state
-    const updatedCasinoState = await casinoClient.getCasinoState(authority.publicKey);
+    const updatedCasinoState = await casinoClient.getCasinoState(casino);
     console.log('\nUpdated casino state:');
     console.log('House edge:', updatedCasinoState.houseEdge / 100, '%');
     
@@ -0,0 +1,384 @@
+// Borsh encoding of the casino program's instructions, accounts and events,
+// driven by the generated IDL (`idl/casino_game.json`) so it can't drift from
+// the program the way hand-written schemas did.
+//
+// Values use the IDL's names in camelCase. `u64` and `i64` decode to BigInt and
+// encode from a BigInt or a number. Fixed-size byte arrays decode to Buffers.
+// Enums are `{ Variant: fields }`, where unit variants may also be given as the
+// variant name alone and tuple variants take an array of fields.
+const { Buffer } = require('buffer');
+
+// Size of the discriminator that starts every program account
+const DISCRIMINATOR_LEN = 8;
+
+// Discriminator plus the layout version byte
+const ACCOUNT_HEADER_LEN = DISCRIMINATOR_LEN + 1;
+
+const INTEGER_SIZES = { u8: 1, u16: 2, u32: 4 };
+
+/**
+ * Converts an IDL name to the camelCase key used in JavaScript values
+ * @param {string} name - Snake case name
+ * @returns {string} - Camel case name
+ */
+function camelCase(name) {
+  return name.replace(/_([a-z0-9])/g, (_, letter) => letter.toUpperCase());
+}
+
+// Whether enum variant fields are named rather than a tuple
+function isNamed(fields) {
+  return fields.length > 0 && fields[0].name !== undefined && fields[0].type !== undefined;
+}
+
+class Writer {
+  constructor() {
+    this.chunks = [];
+  }
+
+  push(bytes) {
+    this.chunks.push(Buffer.from(bytes));
+  }
+
+  integer(value, size) {
+    const bytes = Buffer.alloc(size);
+    bytes.writeUIntLE(value, 0, size);
+    this.push(bytes);
+  }
+
+  bigint(value, signed) {
+    const bytes = Buffer.alloc(8);
+    if (signed) {
+      bytes.writeBigInt64LE(BigInt(value));
+    } else {
+      bytes.writeBigUInt64LE(BigInt(value));
+    }
+    this.push(bytes);
+  }
+
+  toBuffer() {
+    return Buffer.concat(this.chunks);
+  }
+}
+
+class Reader {
+  constructor(data, offset = 0) {
+    this.data = Buffer.from(data);
+    this.offset = offset;
+  }
+
+  take(length) {
+    if (this.offset + length > this.data.length) {
+      throw new Error('Unexpected end of data');
+    }
+    const bytes = this.data.subarray(this.offset, this.offset + length);
+    this.offset += length;
+    return bytes;
+  }
+
+  integer(size) {
+    return this.take(size).readUIntLE(0, size);
+  }
+}
+
+/**
+ * Encodes and decodes the program's data from its IDL
+ */
+class IdlCodec {
+  /**
+   * Constructor for IdlCodec
+   * @param {Object} idl - The parsed IDL
+   * @param {Object} [options]
+   * @param {function(Buffer): *} [options.publicKey] - Maps decoded public keys,
+   *   e.g. to a `PublicKey`; they are left as 32-byte Buffers by default
+   */
+  constructor(idl, { publicKey = (bytes) => bytes } = {}) {
+    this.idl = idl;
+    this.publicKey = publicKey;
+    this.types = new Map(idl.types.map((definition) => [definition.name, definition.type]));
+  }
+
+  /**
+   * Looks up an entry of the IDL
+   * @param {string} section - `instructions`, `accounts`, `events` or `errors`
+   * @param {string} name - Name of the entry
+   * @returns {Object} - The entry
+   */
+  entry(section, name) {
+    const entry = this.idl[section].find((candidate) => candidate.name === name);
+    if (!entry) {
+      throw new Error(`Unknown ${section} entry ${name}`);
+    }
+    return entry;
+  }
+
+  /**
+   * Encodes instruction data: the instruction's discriminant followed by its
+   * arguments
+   * @param {string} name - Instruction name, e.g. `PlaceBet`
+   * @param {Object} [args] - Arguments keyed by camelCase name
+   * @returns {Buffer} - Instruction data
+   */
+  encodeInstruction(name, args = {}) {
+    const instruction = this.entry('instructions', name);
+    const writer = new Writer();
+    writer.integer(instruction.discriminant, 1);
+    this.encodeFields(writer, instruction.args, args);
+    return writer.toBuffer();
+  }
+
+  /**
+   * Decodes instruction data
+   * @param {Buffer} data - Instruction data
+   * @returns {{name: string, args: Object}} - Instruction name and arguments
+   */
+  decodeInstruction(data) {
+    const reader = new Reader(data);
+    const discriminant = reader.integer(1);
+    const instruction = this.idl.instructions.find((candidate) => candidate.discriminant === discriminant);
+    if (!instruction) {
+      throw new Error(`Unknown instruction discriminant ${discriminant}`);
+    }
+    return { name: instruction.name, args: this.decodeFields(reader, instruction.args) };
+  }
+
+  /**
+   * Builds the account metas of an instruction from the accounts in the
+   * order the IDL lists them. Optional (token) accounts may be left off the end.
+   * @param {string} name - Instruction name
+   * @param {Array<*>} keys - Account public keys
+   * @returns {Array<{pubkey: *, isSigner: boolean, isWritable: boolean}>}
+   */
+  accountMetas(name, keys) {
+    const { accounts } = this.entry('instructions', name);
+    const required = accounts.filter((account) => !account.isOptional).length;
+    if (keys.length < required || keys.length > accounts.length) {
+      throw new Error(`${name} takes ${required} to ${accounts.length} accounts, got ${keys.length}`);
+    }
+    return keys.map((pubkey, index) => ({
+      pubkey,
+      isSigner: accounts[index].isSigner,
+      isWritable: accounts[index].isMut,
+    }));
+  }
+
+  /**
+   * Encodes account data: the discriminator, the layout version and the fields
+   * @param {string} name - Account type name, e.g. `GameState`
+   * @param {Object} value - Fields keyed by camelCase name
+   * @returns {Buffer} - Account data
+   */
+  encodeAccount(name, value) {
+    const account = this.entry('accounts', name);
+    const writer = new Writer();
+    writer.push(account.discriminator);
+    writer.integer(account.version, 1);
+    this.encodeFields(writer, account.type.fields, value);
+    return writer.toBuffer();
+  }
+
+  /**
+   * Decodes account data, rejecting accounts of any other type or layout
+   * version
+   * @param {string} name - Account type name, e.g. `GameState`
+   * @param {Buffer} data - Account data
+   * @returns {Object} - Fields keyed by camelCase name
+   */
+  decodeAccount(name, data) {
+    const account = this.entry('accounts', name);
+    const bytes = Buffer.from(data);
+    if (bytes.length < ACCOUNT_HEADER_LEN || !bytes.subarray(0, DISCRIMINATOR_LEN).equals(Buffer.from(account.discriminator))) {
+      throw new Error(`Account is not a ${name}`);
+    }
+    if (bytes[DISCRIMINATOR_LEN] !== account.version) {
+      throw new Error(`Unsupported ${name} version ${bytes[DISCRIMINATOR_LEN]}, expected ${account.version}`);
+    }
+    return this.decodeFields(new Reader(bytes, ACCOUNT_HEADER_LEN), account.type.fields);
+  }
+
+  /**
+   * Decodes an event logged by the program
+   * @param {Buffer} data - The logged event data
+   * @returns {{name: string, data: Object}} - Event name and fields
+   */
+  decodeEvent(data) {
+    const reader = new Reader(data);
+    const discriminant = reader.integer(1);
+    const event = this.idl.events.find((candidate) => candidate.discriminant === discriminant);
+    if (!event) {
+      throw new Error(`Unknown event discriminant ${discriminant}`);
+    }
+    return { name: event.name, data: this.decodeFields(reader, event.fields) };
+  }
+
+  /**
+   * Looks up a custom program error by its code
+   * @param {number} code - The custom error code
+   * @returns {{code: number, name: string, msg: string}|undefined}
+   */
+  error(code) {
+    return this.idl.errors.find((candidate) => candidate.code === code);
+  }
+
+  encodeFields(writer, fields, value) {
+    for (const field of fields) {
+      const key = camelCase(field.name);
+      if (!(key in value) && field.type.option === undefined) {
+        throw new Error(`Missing field ${key}`);
+      }
+      this.encode(writer, field.type, value[key]);
+    }
+  }
+
+  decodeFields(reader, fields) {
+    const value = {};
+    for (const field of fields) {
+      value[camelCase(field.name)] = this.decode(reader, field.type);
+    }
+    return value;
+  }
+
+  encode(writer, type, value) {
+    if (typeof type === 'string') {
+      switch (type) {
+        case 'bool':
+          writer.integer(value ? 1 : 0, 1);
+          return;
+        case 'u8':
+        case 'u16':
+        case 'u32':
+          writer.integer(value, INTEGER_SIZES[type]);
+          return;
+        case 'u64':
+          writer.bigint(value, false);
+          return;
+        case 'i64':
+          writer.bigint(value, true);
+          return;
+        case 'publicKey': {
+          const bytes = typeof value.toBuffer === 'function' ? value.toBuffer() : Buffer.from(value);
+          if (bytes.length !== 32) {
+            throw new Error('A public key is 32 bytes');
+          }
+          writer.push(bytes);
+          return;
+        }
+        default:
+          throw new Error(`Unsupported IDL type ${type}`);
+      }
+    }
+    if (type.option !== undefined) {
+      if (value === null || value === undefined) {
+        writer.integer(0, 1);
+      } else {
+        writer.integer(1, 1);
+        this.encode(writer, type.option, value);
+      }
+    } else if (type.vec !== undefined) {
+      writer.integer(value.length, 4);
+      for (const item of value) {
+        this.encode(writer, type.vec, item);
+      }
+    } else if (type.array !== undefined) {
+      const [item, length] = type.array;
+      if (value.length !== length) {
+        throw new Error(`Expected ${length} items, got ${value.length}`);
+      }
+      for (const element of value) {
+        this.encode(writer, item, element);
+      }
+    } else if (type.defined !== undefined) {
+      this.encodeDefined(writer, type.defined, value);
+    } else {
+      throw new Error(`Unsupported IDL type ${JSON.stringify(type)}`);
+    }
+  }
+
+  encodeDefined(writer, name, value) {
+    const definition = this.types.get(name);
+    if (!definition) {
+      throw new Error(`Unknown type ${name}`);
+    }
+    if (definition.kind === 'struct') {
+      this.encodeFields(writer, definition.fields, value);
+      return;
+    }
+
+    const [variantName, fields] = typeof value === 'string' ? [value, {}] : Object.entries(value)[0];
+    const index = definition.variants.findIndex((variant) => variant.name === variantName);
+    if (index < 0) {
+      throw new Error(`Unknown ${name} variant ${variantName}`);
+    }
+    writer.integer(index, 1);
+    const variantFields = definition.variants[index].fields || [];
+    if (isNamed(variantFields)) {
+      this.encodeFields(writer, variantFields, fields);
+    } else {
+      variantFields.forEach((type, position) => this.encode(writer, type, fields[position]));
+    }
+  }
+
+  decode(reader, type) {
+    if (typeof type === 'string') {
+      switch (type) {
+        case 'bool':
+          return reader.integer(1) !== 0;
+        case 'u8':
+        case 'u16':
+        case 'u32':
+          return reader.integer(INTEGER_SIZES[type]);
+        case 'u64':
+          return reader.take(8).readBigUInt64LE();
+        case 'i64':
+          return reader.take(8).readBigInt64LE();
+        case 'publicKey':
+          return this.publicKey(Buffer.from(reader.take(32)));
+        default:
+          throw new Error(`Unsupported IDL type ${type}`);
+      }
+    }
+    if (type.option !== undefined) {
+      return reader.integer(1) === 0 ? null : this.decode(reader, type.option);
+    }
+    if (type.vec !== undefined) {
+      const length = reader.integer(4);
+      return Array.from({ length }, () => this.decode(reader, type.vec));
+    }
+    if (type.array !== undefined) {
+      const [item, length] = type.array;
+      if (item === 'u8') {
+        return Buffer.from(reader.take(length));
+      }
+      return Array.from({ length }, () => this.decode(reader, item));
+    }
+    if (type.defined !== undefined) {
+      return this.decodeDefined(reader, type.defined);
+    }
+    throw new Error(`Unsupported IDL type ${JSON.stringify(type)}`);
+  }
+
+  decodeDefined(reader, name) {
+    const definition = this.types.get(name);
+    if (!definition) {
+      throw new Error(`Unknown type ${name}`);
+    }
+    if (definition.kind === 'struct') {
+      return this.decodeFields(reader, definition.fields);
+    }
+
+    const index = reader.integer(1);
+    const variant = definition.variants[index];
+    if (!variant) {
+      throw new Error(`Unknown ${name} variant ${index}`);
+    }
+    const variantFields = variant.fields || [];
+    let fields = {};
+    if (isNamed(variantFields)) {
+      fields = this.decodeFields(reader, variantFields);
+    } else if (variantFields.length > 0) {
+      fields = variantFields.map((type) => this.decode(reader, type));
+    }
+    return { [variant.name]: fields };
+  }
+}
+
+module.exports = { IdlCodec, camelCase, ACCOUNT_HEADER_LEN };
@@ -0,0 +1,98 @@
+// Checks the IDL-driven encoding against bytes produced by the program's
+// Rust client. Run with `npm test`.
+const test = require('node:test');
+const assert = require('node:assert');
+const { Buffer } = require('buffer');
+const idl = require('../idl/casino_game.json');
+const { IdlCodec } = require('./idl-codec');
+
+const codec = new IdlCodec(idl);
+
+const filled = (byte) => Buffer.alloc(32, byte);
+
+test('encodes instructions like the Rust client', () => {
+  // `client::place_roulette_bets` with a straight-up bet on 17 and a bet on red
+  const rouletteBets = codec.encodeInstruction('PlaceRouletteBets', {
+    gameId: filled(2),
+    bets: [
+      { bet: { Number: [17] }, amount: 1_000_000 },
+      { bet: 'Red', amount: 250_000n },
+    ],
+    clientSeed: filled(3),
+    serverSeedHash: filled(4),
+  });
+  assert.strictEqual(
+    rouletteBets.toString('hex'),
+    '04' + '02'.repeat(32) + '02000000' + '00' + '11' + '40420f0000000000' + '01' + '90d0030000000000'
+      + '03'.repeat(32) + '04'.repeat(32)
+  );
+
+  // `client::update_params(.., Some(300), None, Some(5_000_000_000), Some(-1))`
+  const updateParams = codec.encodeInstruction('UpdateParams', {
+    houseEdge: 300,
+    maxBet: 5_000_000_000n,
+    settleTimeout: -1,
+  });
+  assert.strictEqual(updateParams.toString('hex'), '05012c01000100f2052a0100000001ffffffffffffffff');
+
+  assert.deepStrictEqual(codec.decodeInstruction(updateParams), {
+    name: 'UpdateParams',
+    args: { houseEdge: 300, minBet: null, maxBet: 5_000_000_000n, settleTimeout: -1n },
+  });
+});
+
+test('decodes accounts written by the program', () => {
+  const gameState = {
+    player: filled(5),
+    betAmount: 500_000n,
+    isSettled: true,
+    isWin: false,
+    winAmount: 0n,
+    resultHash: filled(6),
+    serverSeedHash: filled(7),
+    clientSeed: filled(8),
+    serverSeed: filled(9),
+    gameType: { Dice: { target: 42 } },
+    houseEdge: 250,
+    createdAt: 1_700_000_000n,
+    refundableAt: 1_700_086_400n,
+    casino: filled(10),
+    gameId: filled(11),
+    bump: 254,
+  };
+  // `GameState::to_account_data` of the same game
+  const data = Buffer.from(
+    '905ed0acf8638678' + '02' + '05'.repeat(32) + '20a1070000000000' + '01' + '00' + '0000000000000000'
+      + '06'.repeat(32) + '07'.repeat(32) + '08'.repeat(32) + '09'.repeat(32) + '032a' + 'fa00' + '00f1536500000000'
+      + '8042556500000000' + '0a'.repeat(32) + '0b'.repeat(32) + 'fe',
+    'hex'
+  );
+
+  assert.deepStrictEqual(codec.encodeAccount('GameState', gameState), data);
+  assert.deepStrictEqual(codec.decodeAccount('GameState', data), gameState);
+
+  // Another account type or layout version is refused rather than misread
+  assert.throws(() => codec.decodeAccount('CasinoState', data), /not a CasinoState/);
+  const future = Buffer.from(data);
+  future[8] += 1;
+  assert.throws(() => codec.decodeAccount('GameState', future), /Unsupported GameState version/);
+});
+
+test('builds account metas from the IDL', () => {
+  const keys = [1, 2, 3, 4, 5, 6].map(filled);
+  const metas = codec.accountMetas('PlaceBet', keys);
+  assert.deepStrictEqual(metas[0], { pubkey: keys[0], isSigner: true, isWritable: true });
+  assert.deepStrictEqual(metas[1], { pubkey: keys[1], isSigner: false, isWritable: true });
+  assert.deepStrictEqual(metas[4], { pubkey: keys[4], isSigner: false, isWritable: false });
+
+  // The commitment account is required, the token accounts aren't
+  assert.throws(() => codec.accountMetas('PlaceBet', keys.slice(0, 5)), /takes 6 to 9 accounts/);
+});
+
+test('decodes events and errors', () => {
+  const removed = idl.events.find((event) => event.name === 'OperatorRemoved');
+  const event = codec.decodeEvent(Buffer.concat([Buffer.from([removed.discriminant]), filled(1), filled(2)]));
+  assert.deepStrictEqual(event, { name: 'OperatorRemoved', data: { casino: filled(1), operator: filled(2) } });
+
+  assert.strictEqual(codec.error(0).name, 'InvalidInstruction');
+});
@@ -26,4 +26,9 @@ Write-Host "`nRunning tokio edge case tests..." -ForegroundColor Cyan
 cargo test tokio_tests::test_zero_bet_amount -- --nocapture
 cargo test tokio_tests::test_max_win_amount_calculation -- --nocapture
 
+# Run the compute unit regression check against the SBF build
+Write-Host "`nRunning compute unit regression check..." -ForegroundColor Cyan
+cargo test-sbf compute_units
+if ($LASTEXITCODE -ne 0) { exit $LASTEXITCODE }
+
 Write-Host "`nAll tests completed!" -ForegroundColor Green 
\ No newline at end of file
@@ -23,4 +23,8 @@ echo -e "\n\033[0;36mRunning tokio edge case tests...\033[0m"
 cargo test tokio_tests::test_zero_bet_amount -- --nocapture
 cargo test tokio_tests::test_max_win_amount_calculation -- --nocapture
 
+# Run the compute unit regression check against the SBF build
+echo -e "\n\033[0;36mRunning compute unit regression check...\033[0m"
+cargo test-sbf compute_units || exit 1
+
 echo -e "\n\033[0;32mAll tests completed!\033[0m" 
\ No newline at end of file
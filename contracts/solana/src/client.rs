@@ -1,12 +1,28 @@
 // SPDX-License-Identifier: ISC
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
+    message::Message,
+    program_error::ProgramError,
     pubkey::Pubkey,
     system_program,
+    sysvar::slot_hashes,
 };
+use spl_associated_token_account::get_associated_token_address;
+use std::future::Future;
+use thiserror::Error;
 
-use crate::{id, CasinoInstruction};
+use crate::{
+    coinflip::{self, CoinSide},
+    crash, dice,
+    european_roulette::{self, RouletteWager},
+    events::CasinoEvent,
+    id, randomness, roulette,
+    roulette::RouletteColor,
+    CasinoInstruction, CasinoState, CrashBet, CrashRound, GameState, GameType, OperatorState, ProgramAccount, Round,
+    RoundBet, SettleResult,
+};
 
 /// Creates an instruction to initialize the casino
 pub fn initialize(
@@ -23,45 +39,325 @@ pub fn initialize(
     }
     .try_to_vec()
     .unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, authority);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*authority, true),
             AccountMeta::new(*casino_state, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(operator_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to initialize a casino whose bets are placed in an SPL token
+pub fn initialize_with_mint(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    mint: &Pubkey,
+    house_edge: u16,
+    min_bet: u64,
+    max_bet: u64,
+) -> Instruction {
+    let mut instruction = initialize(authority, casino_state, house_edge, min_bet, max_bet);
+    let vault_token_account = get_vault_token_address(casino_state, mint);
+
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(vault_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ]);
+    instruction
+}
+
+/// Creates an instruction for an operator to commit to a server seed that a
+/// bet can then be placed against
+pub fn post_commitment(operator: &Pubkey, casino_state: &Pubkey, server_seed_hash: [u8; 32]) -> Instruction {
+    let data = CasinoInstruction::PostCommitment { server_seed_hash }.try_to_vec().unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, operator);
+    let (commitment, _) = find_commitment_address(casino_state, &server_seed_hash);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*operator, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(commitment, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     }
 }
 
-/// Creates an instruction to place a bet
+/// Creates an instruction to place a bet against a commitment posted with
+/// `post_commitment`
 pub fn place_bet(
     player: &Pubkey,
     casino_state: &Pubkey,
     game_account: &Pubkey,
     game_id: [u8; 32],
     bet_amount: u64,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
 ) -> Instruction {
     let data = CasinoInstruction::PlaceBet {
         game_id,
         bet_amount,
+        client_seed,
+        server_seed_hash,
     }
     .try_to_vec()
     .unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (commitment, _) = find_commitment_address(casino_state, &server_seed_hash);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*player, true),
-            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*game_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(commitment, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to place a bet in an SPL token casino, paying from the
+/// player's associated token account
+#[allow(clippy::too_many_arguments)]
+pub fn place_token_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    mint: &Pubkey,
+    game_id: [u8; 32],
+    bet_amount: u64,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        bet_amount,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to place a bet on a colour of the roulette wheel
+#[allow(clippy::too_many_arguments)]
+pub fn place_roulette_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    game_id: [u8; 32],
+    bet_amount: u64,
+    color: RouletteColor,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        bet_amount,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.data = CasinoInstruction::PlaceRouletteBet {
+        game_id,
+        bet_amount,
+        color,
+        client_seed,
+        server_seed_hash,
+    }
+    .try_to_vec()
+    .unwrap();
+    instruction
+}
+
+/// Creates an instruction to place a set of European roulette bets on one spin
+pub fn place_roulette_bets(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    game_id: [u8; 32],
+    bets: Vec<RouletteWager>,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let total_stake = bets.iter().map(|wager| wager.amount).sum();
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        total_stake,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.data = CasinoInstruction::PlaceRouletteBets {
+        game_id,
+        bets,
+        client_seed,
+        server_seed_hash,
+    }
+    .try_to_vec()
+    .unwrap();
+    instruction
+}
+
+/// Creates an instruction to place a roll-under dice bet
+#[allow(clippy::too_many_arguments)]
+pub fn place_dice_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    game_id: [u8; 32],
+    bet_amount: u64,
+    target: u8,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        bet_amount,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.data = CasinoInstruction::PlaceDiceBet {
+        game_id,
+        bet_amount,
+        target,
+        client_seed,
+        server_seed_hash,
+    }
+    .try_to_vec()
+    .unwrap();
+    instruction
+}
+
+/// Creates an instruction to bet on a coinflip against the house
+#[allow(clippy::too_many_arguments)]
+pub fn place_coinflip_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    game_id: [u8; 32],
+    bet_amount: u64,
+    side: CoinSide,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        bet_amount,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.data = CasinoInstruction::PlaceCoinflipBet {
+        game_id,
+        bet_amount,
+        side,
+        client_seed,
+        server_seed_hash,
+    }
+    .try_to_vec()
+    .unwrap();
+    instruction
+}
+
+/// Creates an instruction to open a coinflip challenge for another player to
+/// accept
+#[allow(clippy::too_many_arguments)]
+pub fn open_coinflip_challenge(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    game_id: [u8; 32],
+    stake: u64,
+    side: CoinSide,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+) -> Instruction {
+    let mut instruction = place_bet(
+        player,
+        casino_state,
+        game_account,
+        game_id,
+        stake,
+        client_seed,
+        server_seed_hash,
+    );
+    instruction.data = CasinoInstruction::OpenCoinflipChallenge {
+        game_id,
+        stake,
+        side,
+        client_seed,
+        server_seed_hash,
+    }
+    .try_to_vec()
+    .unwrap();
+    instruction
+}
+
+/// Creates an instruction to accept a coinflip challenge by matching its stake
+pub fn accept_coinflip_challenge(
+    opponent: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::AcceptCoinflipChallenge.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*opponent, true),
+            AccountMeta::new(*casino_state, false),
             AccountMeta::new(*game_account, false),
+            AccountMeta::new(vault, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(slot_hashes::id(), false),
         ],
         data,
     }
 }
 
+/// Creates an instruction to accept a coinflip challenge in an SPL token
+/// casino, paying from the opponent's associated token account
+pub fn accept_token_coinflip_challenge(
+    opponent: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let mut instruction = accept_coinflip_challenge(opponent, casino_state, game_account);
+    instruction.accounts.extend(token_accounts(opponent, casino_state, mint));
+    instruction
+}
+
 /// Creates an instruction to settle a game
 pub fn settle_game(
     authority: &Pubkey,
@@ -70,29 +366,242 @@ pub fn settle_game(
     player: &Pubkey,
     is_win: bool,
     win_amount: u64,
-    result_hash: [u8; 32],
+    server_seed: [u8; 32],
 ) -> Instruction {
     let data = CasinoInstruction::SettleGame {
         is_win,
         win_amount,
-        result_hash,
+        server_seed,
     }
     .try_to_vec()
     .unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, authority);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*authority, true),
-            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(operator_account, false),
             AccountMeta::new(*game_account, false),
             AccountMeta::new(*player, false),
+            AccountMeta::new(vault, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     }
 }
 
+/// Creates an instruction to settle a game in an SPL token casino, paying any win
+/// to the player's associated token account
+#[allow(clippy::too_many_arguments)]
+pub fn settle_token_game(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    player: &Pubkey,
+    mint: &Pubkey,
+    is_win: bool,
+    win_amount: u64,
+    server_seed: [u8; 32],
+) -> Instruction {
+    let mut instruction = settle_game(
+        authority,
+        casino_state,
+        game_account,
+        player,
+        is_win,
+        win_amount,
+        server_seed,
+    );
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Largest serialized transaction the cluster accepts, `PACKET_DATA_SIZE`
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Most accounts one transaction may lock
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 64;
+
+/// Whether a transaction of just `instructions` stays within the size and
+/// account limits, signed by the instructions' signers and a separate fee
+/// payer
+pub fn fits_in_transaction(instructions: &[Instruction]) -> bool {
+    // A fee payer that isn't among the instructions' accounts, the largest case
+    let fee_payer = Pubkey::new_from_array([u8::MAX; 32]);
+    let message = Message::new(instructions, Some(&fee_payer));
+    let signatures = usize::from(message.header.num_required_signatures);
+    // The signatures are prefixed by their count, a single byte this small
+    message.account_keys.len() <= MAX_TRANSACTION_ACCOUNTS
+        && 1 + signatures * 64 + message.serialize().len() <= MAX_TRANSACTION_SIZE
+}
+
+/// Compute units a transaction of a single instruction gets without a
+/// compute budget instruction
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// Compute units budgeted for the accounts and checks a settling instruction
+/// (`SettleBatch`, `SettleCrashRound` or `SettleRoundBets`) starts with
+pub const SETTLE_BASE_COMPUTE_UNITS: u64 = 20_000;
+
+/// Compute units budgeted for each game or bet a settling instruction pays
+/// in lamports. `compute_units_test` settles the largest batches built with
+/// these budgets on the SBF build, which fails if they are too low.
+pub const SETTLE_GAME_COMPUTE_UNITS: u64 = 20_000;
+
+/// Compute units budgeted for each game or bet a settling instruction pays
+/// in tokens, a transfer through the SPL token program included
+pub const SETTLE_TOKEN_GAME_COMPUTE_UNITS: u64 = 35_000;
+
+/// Most games or bets one settling instruction may hold to stay within
+/// `DEFAULT_COMPUTE_UNIT_LIMIT`, in a token casino if `mint` is given
+pub fn max_settled_per_instruction(mint: Option<&Pubkey>) -> usize {
+    let per_game = match mint {
+        Some(_) => SETTLE_TOKEN_GAME_COMPUTE_UNITS,
+        None => SETTLE_GAME_COMPUTE_UNITS,
+    };
+    ((DEFAULT_COMPUTE_UNIT_LIMIT - SETTLE_BASE_COMPUTE_UNITS) / per_game) as usize
+}
+
+// Splits `items` into consecutive batches, growing each while it holds at
+// most `max_items` and `fits` accepts it
+fn split_batches<T>(items: &[T], max_items: usize, fits: impl Fn(&[T]) -> bool) -> Vec<&[T]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let mut end = start + 1;
+        while end < items.len() && end - start < max_items && fits(&items[start..=end]) {
+            end += 1;
+        }
+        batches.push(&items[start..end]);
+        start = end;
+    }
+    batches
+}
+
+// Builds an instruction for each batch of `items`, growing each batch while
+// its instruction still fits in a transaction and holds at most `max_items`
+fn batch_instructions<T>(items: &[T], max_items: usize, build: impl Fn(&[T]) -> Instruction) -> Vec<Instruction> {
+    split_batches(items, max_items, |batch| fits_in_transaction(&[build(batch)]))
+        .into_iter()
+        .map(build)
+        .collect()
+}
+
+/// Creates `SettleBatch` instructions for many `(game_account, payee, result)`
+/// settlements, as many games to an instruction as `fits_in_transaction` and
+/// `max_settled_per_instruction` allow. The payee is the player, or the
+/// opponent if it won a coinflip challenge; pass the casino's `mint` to pay
+/// the payees' associated token accounts.
+pub fn settle_batch(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    mint: Option<&Pubkey>,
+    games: &[(Pubkey, Pubkey, SettleResult)],
+) -> Vec<Instruction> {
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*casino_state, false),
+        AccountMeta::new(operator_account, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(mint) = mint {
+        accounts.push(AccountMeta::new(get_vault_token_address(casino_state, mint), false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    // A payee shared with an earlier game adds no account, so the count per
+    // batch varies
+    batch_instructions(games, max_settled_per_instruction(mint), |batch| {
+        let mut accounts = accounts.clone();
+        for (game_account, payee, _) in batch {
+            accounts.push(AccountMeta::new(*game_account, false));
+            accounts.push(AccountMeta::new(*payee, false));
+            if let Some(mint) = mint {
+                accounts.push(AccountMeta::new(get_associated_token_address(payee, mint), false));
+            }
+        }
+        let results = batch.iter().map(|(_, _, result)| result.clone()).collect();
+        Instruction {
+            program_id: id::id(),
+            accounts,
+            data: CasinoInstruction::SettleBatch { results }.try_to_vec().unwrap(),
+        }
+    })
+}
+
+/// Computes whether a game is won for a revealed server seed, so an operator can
+/// build a settlement the program will accept
+pub fn game_outcome(server_seed: &[u8; 32], client_seed: &[u8; 32], game_account: &Pubkey) -> bool {
+    randomness::is_win(&randomness::result_hash(server_seed, client_seed, game_account))
+}
+
+/// Computes the roulette slot a game lands on for a revealed server seed
+pub fn roulette_slot(server_seed: &[u8; 32], client_seed: &[u8; 32], game_account: &Pubkey) -> u8 {
+    roulette::winning_slot(&randomness::result_hash(server_seed, client_seed, game_account))
+}
+
+/// Computes the European roulette pocket a game lands on for a revealed server seed
+pub fn european_roulette_spin(server_seed: &[u8; 32], client_seed: &[u8; 32], game_account: &Pubkey) -> u8 {
+    european_roulette::spin(&randomness::result_hash(server_seed, client_seed, game_account))
+}
+
+/// Computes the dice roll (in hundredths) a game gets for a revealed server seed
+pub fn dice_roll(server_seed: &[u8; 32], client_seed: &[u8; 32], game_account: &Pubkey) -> u16 {
+    dice::roll(&randomness::result_hash(server_seed, client_seed, game_account))
+}
+
+/// Computes the side a coinflip lands on for a revealed server seed
+pub fn coin_flip(server_seed: &[u8; 32], client_seed: &[u8; 32], game_account: &Pubkey) -> CoinSide {
+    coinflip::flip(&randomness::result_hash(server_seed, client_seed, game_account))
+}
+
+/// Computes the `is_win` and `win_amount` a settlement must claim for a
+/// revealed server seed. Standard games pay even odds less the house edge,
+/// roulette games pay the wheel's odds, dice games their target's odds and
+/// coinflips the pot less the house edge the bet was placed under. A coinflip
+/// challenge pays its pot to `GameState::payee`, the opponent if `is_win` is
+/// false. Returns `None` if the payout overflows.
+pub fn settlement(
+    game_state: &GameState,
+    game_account: &Pubkey,
+    server_seed: &[u8; 32],
+) -> Option<(bool, u64)> {
+    let result_hash = randomness::result_hash(server_seed, &game_state.client_seed, game_account);
+    match &game_state.game_type {
+        GameType::Standard => {
+            let payout = randomness::payout(game_state.bet_amount, game_state.house_edge, &result_hash)?;
+            Some((payout > 0, payout))
+        },
+        GameType::Roulette { color } => {
+            let payout = roulette::payout(game_state.bet_amount, *color, &result_hash)?;
+            Some((payout > 0, payout))
+        },
+        GameType::EuropeanRoulette { bets } => {
+            let payout = european_roulette::payout(bets, european_roulette::spin(&result_hash))?;
+            Some((payout > 0, payout))
+        },
+        GameType::Dice { target } => {
+            let payout = dice::payout(game_state.bet_amount, *target, game_state.house_edge, &result_hash)?;
+            Some((payout > 0, payout))
+        },
+        GameType::Coinflip { side } => {
+            let payout = coinflip::payout(game_state.bet_amount, *side, game_state.house_edge, &result_hash)?;
+            Some((payout > 0, payout))
+        },
+        GameType::CoinflipChallenge { side, .. } => {
+            let pot = coinflip::pot_payout(game_state.bet_amount, game_state.house_edge)?;
+            Some((coinflip::flip(&result_hash) == *side, pot))
+        },
+    }
+}
+
 /// Creates an instruction to update casino parameters
 pub fn update_params(
     authority: &Pubkey,
@@ -100,39 +609,54 @@ pub fn update_params(
     house_edge: Option<u16>,
     min_bet: Option<u64>,
     max_bet: Option<u64>,
+    settle_timeout: Option<i64>,
 ) -> Instruction {
     let data = CasinoInstruction::UpdateParams {
         house_edge,
         min_bet,
         max_bet,
+        settle_timeout,
     }
     .try_to_vec()
     .unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, authority);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*authority, true),
             AccountMeta::new(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
         ],
         data,
     }
 }
 
-/// Creates an instruction to add an operator
+/// Creates an instruction to add an operator, or to update the roles and daily
+/// payout cap of an existing one
 pub fn add_operator(
     authority: &Pubkey,
     casino_state: &Pubkey,
     operator: &Pubkey,
+    roles: u8,
+    daily_payout_cap: u64,
 ) -> Instruction {
-    let data = CasinoInstruction::AddOperator.try_to_vec().unwrap();
+    let data = CasinoInstruction::AddOperator {
+        roles,
+        daily_payout_cap,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, operator);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*authority, true),
-            AccountMeta::new(*casino_state, false),
+            AccountMeta::new_readonly(*casino_state, false),
             AccountMeta::new_readonly(*operator, false),
+            AccountMeta::new(operator_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     }
@@ -145,24 +669,1009 @@ pub fn remove_operator(
     operator: &Pubkey,
 ) -> Instruction {
     let data = CasinoInstruction::RemoveOperator.try_to_vec().unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, operator);
 
     Instruction {
         program_id: id::id(),
         accounts: vec![
             AccountMeta::new(*authority, true),
-            AccountMeta::new(*casino_state, false),
+            AccountMeta::new_readonly(*casino_state, false),
             AccountMeta::new_readonly(*operator, false),
+            AccountMeta::new(operator_account, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to deposit lamports into the house vault
+pub fn fund_vault(
+    funder: &Pubkey,
+    casino_state: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = CasinoInstruction::FundVault { amount }.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     }
 }
 
-/// Finds the program-derived address for a casino state account
-pub fn find_casino_state_address(authority: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"casino", authority.as_ref()], &id::id())
+/// Creates an instruction to withdraw lamports from the house vault
+pub fn withdraw_from_vault(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = CasinoInstruction::WithdrawFromVault { amount }.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*recipient, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to deposit tokens into the house vault of an SPL token casino
+pub fn fund_vault_tokens(
+    funder: &Pubkey,
+    casino_state: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction = fund_vault(funder, casino_state, amount);
+    instruction.accounts.extend(token_accounts(funder, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to withdraw tokens from the house vault of an SPL token
+/// casino into the recipient's associated token account
+pub fn withdraw_vault_tokens(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let recipient_token_account = get_associated_token_address(recipient, mint);
+    let vault_token_account = get_vault_token_address(casino_state, mint);
+    let mut instruction =
+        withdraw_from_vault(authority, casino_state, &recipient_token_account, amount);
+
+    instruction.accounts.extend([
+        AccountMeta::new(vault_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    instruction
+}
+
+/// Creates an instruction to close a settled game and refund its rent to the player
+pub fn close_game(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    player: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::CloseGame.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(*game_account, false),
+            AccountMeta::new(*player, false),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    }
+}
+
+/// Creates `CloseGame` instructions for many `(game_account, player)` pairs,
+/// grouped into batches as large as `fits_in_transaction` allows
+pub fn close_games(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    games: &[(Pubkey, Pubkey)],
+) -> Vec<Vec<Instruction>> {
+    let instructions: Vec<Instruction> = games
+        .iter()
+        .map(|(game_account, player)| close_game(authority, casino_state, game_account, player))
+        .collect();
+    split_batches(&instructions, usize::MAX, fits_in_transaction)
+        .into_iter()
+        .map(<[Instruction]>::to_vec)
+        .collect()
+}
+
+/// Creates an instruction for a player to reclaim the stake of a game that was
+/// not settled within the casino's settlement timeout
+pub fn claim_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::ClaimRefund.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*game_account, false),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction for a player to reclaim the stake of an unsettled game
+/// in an SPL token casino
+pub fn claim_token_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_refund(player, casino_state, game_account);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
 }
 
-/// Finds the program-derived address for a game account
-pub fn find_game_address(game_id: &[u8; 32]) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[game_id], &id::id())
-} 
\ No newline at end of file
+/// Creates an instruction for a player to reclaim the stakes of an accepted
+/// coinflip challenge left unsettled, refunding the opponent too
+pub fn claim_challenge_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    opponent: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_refund(player, casino_state, game_account);
+    instruction.accounts.push(AccountMeta::new(*opponent, false));
+    instruction
+}
+
+/// Creates an instruction for a player to reclaim the stakes of an accepted
+/// coinflip challenge left unsettled in an SPL token casino
+pub fn claim_token_challenge_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    mint: &Pubkey,
+    opponent: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_token_refund(player, casino_state, game_account, mint);
+    instruction.accounts.push(AccountMeta::new(*opponent, false));
+    instruction.accounts.push(AccountMeta::new(get_associated_token_address(opponent, mint), false));
+    instruction
+}
+
+/// Creates an instruction to cancel a coinflip challenge nobody has accepted,
+/// refunding its stake
+pub fn cancel_coinflip_challenge(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_refund(player, casino_state, game_account);
+    instruction.data = CasinoInstruction::CancelCoinflipChallenge.try_to_vec().unwrap();
+    instruction
+}
+
+/// Creates an instruction to cancel a coinflip challenge nobody has accepted
+/// in an SPL token casino
+pub fn cancel_token_coinflip_challenge(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    game_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let mut instruction = cancel_coinflip_challenge(player, casino_state, game_account);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to open a crash round whose betting closes at
+/// `starts_at`
+pub fn open_crash_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round_id: [u8; 32],
+    server_seed_hash: [u8; 32],
+    starts_at: i64,
+) -> Instruction {
+    let data = CasinoInstruction::OpenCrashRound {
+        round_id,
+        server_seed_hash,
+        starts_at,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+    let (round, _) = find_crash_round_address(casino_state, &round_id);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(round, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to join a crash round, optionally cashing out
+/// automatically at `auto_cashout` (in basis points)
+pub fn join_crash_round(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    bet_amount: u64,
+    auto_cashout: Option<u64>,
+) -> Instruction {
+    let data = CasinoInstruction::JoinCrashRound {
+        bet_amount,
+        auto_cashout,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (bet, _) = find_crash_bet_address(round, player);
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*round, false),
+            AccountMeta::new(bet, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to join a crash round in an SPL token casino, paying
+/// from the player's associated token account
+pub fn join_token_crash_round(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: &Pubkey,
+    bet_amount: u64,
+    auto_cashout: Option<u64>,
+) -> Instruction {
+    let mut instruction = join_crash_round(player, casino_state, round, bet_amount, auto_cashout);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to cash out of a running crash round at
+/// `multiplier` (in basis points), which the round must have reached
+pub fn cash_out(player: &Pubkey, round: &Pubkey, multiplier: u64) -> Instruction {
+    let data = CasinoInstruction::CashOut { multiplier }.try_to_vec().unwrap();
+    let (bet, _) = find_crash_bet_address(round, player);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(*round, false),
+            AccountMeta::new(bet, false),
+        ],
+        data,
+    }
+}
+
+/// Creates the instructions to reveal a crash round's server seed and settle
+/// the bets of `players`, as many to an instruction as `fits_in_transaction`
+/// and `max_settled_per_instruction` allow. Pass the casino's `mint` to pay
+/// the players' associated token accounts.
+pub fn settle_crash_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: Option<&Pubkey>,
+    players: &[Pubkey],
+    server_seed: [u8; 32],
+) -> Vec<Instruction> {
+    settle_crash_bets(authority, authority, casino_state, round, mint, players, server_seed)
+}
+
+/// Creates the instructions for anyone to settle bets of a crash round whose
+/// seed has been revealed, like `settle_crash_round`. The payouts count
+/// against the cap of `round_operator`, the operator who opened the round,
+/// and are refused while it is not an operator.
+pub fn settle_crash_bets(
+    settler: &Pubkey,
+    round_operator: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: Option<&Pubkey>,
+    players: &[Pubkey],
+    server_seed: [u8; 32],
+) -> Vec<Instruction> {
+    let data = CasinoInstruction::SettleCrashRound { server_seed }.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, round_operator);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*settler, true),
+        AccountMeta::new(*casino_state, false),
+        AccountMeta::new(operator_account, false),
+        AccountMeta::new(*round, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(mint) = mint {
+        accounts.push(AccountMeta::new(get_vault_token_address(casino_state, mint), false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    let build = |batch: &[Pubkey]| {
+        let mut accounts = accounts.clone();
+        for player in batch {
+            let (bet, _) = find_crash_bet_address(round, player);
+            accounts.push(AccountMeta::new(bet, false));
+            accounts.push(AccountMeta::new(*player, false));
+            if let Some(mint) = mint {
+                accounts.push(AccountMeta::new(get_associated_token_address(player, mint), false));
+            }
+        }
+        Instruction {
+            program_id: id::id(),
+            accounts,
+            data: data.clone(),
+        }
+    };
+    // A round nobody joined still needs its seed revealed
+    match players.is_empty() {
+        true => vec![build(&[])],
+        false => batch_instructions(players, max_settled_per_instruction(mint), build),
+    }
+}
+
+/// Creates an instruction for a player to reclaim the stake of a crash bet
+/// whose round was not settled within the casino's settlement timeout
+pub fn claim_crash_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::ClaimCrashRefund.try_to_vec().unwrap();
+    let (bet, _) = find_crash_bet_address(round, player);
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*round, false),
+            AccountMeta::new(bet, false),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction for a player to reclaim the stake of an unsettled
+/// crash bet in an SPL token casino
+pub fn claim_token_crash_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_crash_refund(player, casino_state, round);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to close a crash round with no open bets,
+/// returning its rent to `round_operator`, the operator who opened it
+pub fn close_crash_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    round_operator: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::CloseCrashRound.try_to_vec().unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(*round, false),
+            AccountMeta::new(*round_operator, false),
+        ],
+        data,
+    }
+}
+
+/// Computes the multiplier (in basis points) a crash round crashes at for a
+/// revealed server seed
+pub fn crash_point(server_seed: &[u8; 32], round: &CrashRound, round_account: &Pubkey) -> u64 {
+    crash::crash_point(&randomness::result_hash(server_seed, &round.round_id, round_account), round.house_edge)
+}
+
+/// Creates an instruction to open a shared roulette round whose betting
+/// closes at `betting_ends_at`
+pub fn open_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round_id: [u8; 32],
+    server_seed_hash: [u8; 32],
+    betting_ends_at: i64,
+) -> Instruction {
+    let data = CasinoInstruction::OpenRound {
+        round_id,
+        server_seed_hash,
+        betting_ends_at,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+    let (round, _) = find_round_address(casino_state, &round_id);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(round, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to bet `amount` on a colour in a roulette round
+pub fn place_round_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    color: RouletteColor,
+    amount: u64,
+) -> Instruction {
+    let data = CasinoInstruction::PlaceRoundBet { color, amount }.try_to_vec().unwrap();
+    let (bet, _) = find_round_bet_address(round, player);
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*round, false),
+            AccountMeta::new(bet, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to bet on a colour in a roulette round of an SPL
+/// token casino, paying from the player's associated token account
+pub fn place_token_round_bet(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: &Pubkey,
+    color: RouletteColor,
+    amount: u64,
+) -> Instruction {
+    let mut instruction = place_round_bet(player, casino_state, round, color, amount);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to close betting on a roulette round
+pub fn lock_round(authority: &Pubkey, casino_state: &Pubkey, round: &Pubkey) -> Instruction {
+    let data = CasinoInstruction::LockRound.try_to_vec().unwrap();
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+            AccountMeta::new(*round, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to spin a locked roulette round by revealing its
+/// server seed
+pub fn spin_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    server_seed: [u8; 32],
+) -> Instruction {
+    let mut instruction = lock_round(authority, casino_state, round);
+    instruction.data = CasinoInstruction::SpinRound { server_seed }.try_to_vec().unwrap();
+    instruction
+}
+
+/// Creates the instructions to settle the bets of `players` in a spun
+/// roulette round, as many to an instruction as `fits_in_transaction` and
+/// `max_settled_per_instruction` allow. Pass the casino's `mint` to pay the
+/// players' associated token accounts. Anyone may sign as the `settler`; the payouts
+/// count against the cap of `round_operator`, the operator who opened the
+/// round, and are refused while it is not an operator.
+pub fn settle_round_bets(
+    settler: &Pubkey,
+    round_operator: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: Option<&Pubkey>,
+    players: &[Pubkey],
+) -> Vec<Instruction> {
+    let data = CasinoInstruction::SettleRoundBets.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, round_operator);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*settler, true),
+        AccountMeta::new(*casino_state, false),
+        AccountMeta::new(operator_account, false),
+        AccountMeta::new(*round, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(mint) = mint {
+        accounts.push(AccountMeta::new(get_vault_token_address(casino_state, mint), false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    batch_instructions(players, max_settled_per_instruction(mint), |batch| {
+        let mut accounts = accounts.clone();
+        for player in batch {
+            let (bet, _) = find_round_bet_address(round, player);
+            accounts.push(AccountMeta::new(bet, false));
+            accounts.push(AccountMeta::new(*player, false));
+            if let Some(mint) = mint {
+                accounts.push(AccountMeta::new(get_associated_token_address(player, mint), false));
+            }
+        }
+        Instruction {
+            program_id: id::id(),
+            accounts,
+            data: data.clone(),
+        }
+    })
+}
+
+/// Creates an instruction for a player to reclaim the stakes of a roulette
+/// round that was not spun within the casino's settlement timeout
+pub fn claim_round_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::ClaimRoundRefund.try_to_vec().unwrap();
+    let (bet, _) = find_round_bet_address(round, player);
+    let (vault, _) = find_vault_address(casino_state);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(*round, false),
+            AccountMeta::new(bet, false),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction for a player to reclaim the stakes of an unspun
+/// roulette round in an SPL token casino
+pub fn claim_token_round_refund(
+    player: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let mut instruction = claim_round_refund(player, casino_state, round);
+    instruction.accounts.extend(token_accounts(player, casino_state, mint));
+    instruction
+}
+
+/// Creates an instruction to close a roulette round with no open bets,
+/// returning its rent to `round_operator`, the operator who opened it
+pub fn close_round(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    round: &Pubkey,
+    round_operator: &Pubkey,
+) -> Instruction {
+    let mut instruction = close_crash_round(authority, casino_state, round, round_operator);
+    instruction.data = CasinoInstruction::CloseRound.try_to_vec().unwrap();
+    instruction
+}
+
+/// Computes the slot a roulette round lands on for a revealed server seed
+pub fn winning_slot(server_seed: &[u8; 32], round: &Round, round_account: &Pubkey) -> u8 {
+    roulette::winning_slot(&randomness::result_hash(server_seed, &round.round_id, round_account))
+}
+
+/// Creates an instruction to propose a new casino authority
+pub fn propose_authority(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::ProposeAuthority {
+        new_authority: *new_authority,
+    }
+    .try_to_vec()
+    .unwrap();
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*casino_state, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction for the proposed authority to take over the casino
+pub fn accept_authority(
+    new_authority: &Pubkey,
+    casino_state: &Pubkey,
+    previous_authority: &Pubkey,
+) -> Instruction {
+    let data = CasinoInstruction::AcceptAuthority.try_to_vec().unwrap();
+    let (new_operator_account, _) = find_operator_address(casino_state, new_authority);
+    let (previous_operator_account, _) = find_operator_address(casino_state, previous_authority);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*new_authority, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new(new_operator_account, false),
+            AccountMeta::new(*previous_authority, false),
+            AccountMeta::new(previous_operator_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to pause betting and/or settlement
+pub fn pause(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    betting: bool,
+    settlement: bool,
+) -> Instruction {
+    let data = CasinoInstruction::Pause { betting, settlement }.try_to_vec().unwrap();
+    pause_instruction(authority, casino_state, data)
+}
+
+/// Creates an instruction to resume betting and/or settlement
+pub fn unpause(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    betting: bool,
+    settlement: bool,
+) -> Instruction {
+    let data = CasinoInstruction::Unpause { betting, settlement }.try_to_vec().unwrap();
+    pause_instruction(authority, casino_state, data)
+}
+
+// Accounts shared by `Pause` and `Unpause`
+fn pause_instruction(authority: &Pubkey, casino_state: &Pubkey, data: Vec<u8>) -> Instruction {
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*casino_state, false),
+            AccountMeta::new_readonly(operator_account, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an instruction to upgrade a casino state account to the current
+/// layout. `legacy_operators` is the version 1 `operators` list of the casino.
+pub fn migrate_casino_state(
+    authority: &Pubkey,
+    casino_state: &Pubkey,
+    legacy_operators: &[Pubkey],
+) -> Instruction {
+    let data = CasinoInstruction::MigrateCasinoState.try_to_vec().unwrap();
+    let (vault, _) = find_vault_address(casino_state);
+    let (operator_account, _) = find_operator_address(casino_state, authority);
+
+    let mut accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*casino_state, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(operator_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(
+        legacy_operators
+            .iter()
+            .filter(|operator| *operator != authority)
+            .map(|operator| AccountMeta::new(find_operator_address(casino_state, operator).0, false)),
+    );
+
+    Instruction {
+        program_id: id::id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates an instruction for a player to reclaim the stake of a game placed
+/// with a version 1 program and never settled
+pub fn claim_legacy_refund(player: &Pubkey, game_id: [u8; 32]) -> Instruction {
+    let data = CasinoInstruction::ClaimLegacyRefund { game_id }.try_to_vec().unwrap();
+    let (game_account, _) = find_legacy_game_address(&game_id);
+
+    Instruction {
+        program_id: id::id(),
+        accounts: vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(game_account, false),
+        ],
+        data,
+    }
+}
+
+// Trailing accounts shared by the token variants: the wallet's associated token
+// account, the vault's associated token account and the token program
+fn token_accounts(wallet: &Pubkey, casino_state: &Pubkey, mint: &Pubkey) -> [AccountMeta; 3] {
+    let vault_token_account = get_vault_token_address(casino_state, mint);
+
+    [
+        AccountMeta::new(get_associated_token_address(wallet, mint), false),
+        AccountMeta::new(vault_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+/// Finds the program-derived address for a casino state account from the key
+/// that created it, which stays the casino's address after authority transfers
+pub fn find_casino_state_address(creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"casino", creator.as_ref()], &id::id())
+}
+
+/// Finds the program-derived address for a player's game account in a casino
+pub fn find_game_address(casino_state: &Pubkey, player: &Pubkey, game_id: &[u8; 32]) -> (Pubkey, u8) {
+    crate::find_game_address(&id::id(), casino_state, player, game_id)
+}
+
+/// Finds the address a version 1 program gave the game account of a game ID
+pub fn find_legacy_game_address(game_id: &[u8; 32]) -> (Pubkey, u8) {
+    crate::find_legacy_game_address(&id::id(), game_id)
+}
+
+/// Finds the program-derived address for an operator's account in a casino
+pub fn find_operator_address(casino_state: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+    crate::find_operator_address(&id::id(), casino_state, operator)
+}
+
+/// Finds the program-derived address for an operator's commitment to a server
+/// seed hash in a casino
+pub fn find_commitment_address(casino_state: &Pubkey, server_seed_hash: &[u8; 32]) -> (Pubkey, u8) {
+    crate::find_commitment_address(&id::id(), casino_state, server_seed_hash)
+}
+
+/// Finds the program-derived address for a casino's house vault
+pub fn find_vault_address(casino_state: &Pubkey) -> (Pubkey, u8) {
+    crate::find_vault_address(&id::id(), casino_state)
+}
+
+/// Finds the program-derived address for a crash round of a casino
+pub fn find_crash_round_address(casino_state: &Pubkey, round_id: &[u8; 32]) -> (Pubkey, u8) {
+    crate::find_crash_round_address(&id::id(), casino_state, round_id)
+}
+
+/// Finds the program-derived address for a player's bet in a crash round
+pub fn find_crash_bet_address(round: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    crate::find_crash_bet_address(&id::id(), round, player)
+}
+
+/// Finds the program-derived address for a roulette round of a casino
+pub fn find_round_address(casino_state: &Pubkey, round_id: &[u8; 32]) -> (Pubkey, u8) {
+    crate::find_round_address(&id::id(), casino_state, round_id)
+}
+
+/// Finds the program-derived address for a player's bets in a roulette round
+pub fn find_round_bet_address(round: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    crate::find_round_bet_address(&id::id(), round, player)
+}
+
+/// Gets the house vault's associated token account for a mint
+pub fn get_vault_token_address(casino_state: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (vault, _) = find_vault_address(casino_state);
+    get_associated_token_address(&vault, mint)
+}
+
+/// The one RPC call the fetch helpers need, so services and tests can share
+/// them whichever client they use (`RpcClient`, `BanksClient`, ...)
+pub trait AccountFetcher {
+    type Error;
+
+    /// Fetches the owner and data of an account, or `None` if it doesn't exist
+    fn get_account_data(
+        &mut self,
+        address: &Pubkey,
+    ) -> impl Future<Output = Result<Option<(Pubkey, Vec<u8>)>, Self::Error>>;
+}
+
+/// Errors returned by the fetch helpers
+#[derive(Error, Debug)]
+pub enum FetchError<E> {
+    #[error("RPC request failed: {0:?}")]
+    Rpc(E),
+
+    #[error("Account {0} does not exist")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {0} is not owned by the casino program")]
+    IllegalOwner(Pubkey),
+
+    #[error("Account {0} could not be decoded: {1}")]
+    InvalidAccount(Pubkey, ProgramError),
+}
+
+/// Fetches and decodes a casino state account
+pub async fn fetch_casino_state<F: AccountFetcher>(
+    fetcher: &mut F,
+    casino_state: &Pubkey,
+) -> Result<CasinoState, FetchError<F::Error>> {
+    fetch_account(fetcher, casino_state).await
+}
+
+/// Fetches and decodes a game account
+pub async fn fetch_game_state<F: AccountFetcher>(
+    fetcher: &mut F,
+    game_account: &Pubkey,
+) -> Result<GameState, FetchError<F::Error>> {
+    fetch_account(fetcher, game_account).await
+}
+
+/// Fetches and decodes the account of an operator of a casino
+pub async fn fetch_operator_state<F: AccountFetcher>(
+    fetcher: &mut F,
+    casino_state: &Pubkey,
+    operator: &Pubkey,
+) -> Result<OperatorState, FetchError<F::Error>> {
+    let (operator_account, _) = find_operator_address(casino_state, operator);
+    fetch_account(fetcher, &operator_account).await
+}
+
+/// Fetches and decodes a crash round account
+pub async fn fetch_crash_round<F: AccountFetcher>(
+    fetcher: &mut F,
+    round: &Pubkey,
+) -> Result<CrashRound, FetchError<F::Error>> {
+    fetch_account(fetcher, round).await
+}
+
+/// Fetches and decodes a player's bet in a crash round
+pub async fn fetch_crash_bet<F: AccountFetcher>(
+    fetcher: &mut F,
+    round: &Pubkey,
+    player: &Pubkey,
+) -> Result<CrashBet, FetchError<F::Error>> {
+    let (bet, _) = find_crash_bet_address(round, player);
+    fetch_account(fetcher, &bet).await
+}
+
+/// Fetches and decodes a roulette round account
+pub async fn fetch_round<F: AccountFetcher>(
+    fetcher: &mut F,
+    round: &Pubkey,
+) -> Result<Round, FetchError<F::Error>> {
+    fetch_account(fetcher, round).await
+}
+
+/// Fetches and decodes a player's bets in a roulette round
+pub async fn fetch_round_bet<F: AccountFetcher>(
+    fetcher: &mut F,
+    round: &Pubkey,
+    player: &Pubkey,
+) -> Result<RoundBet, FetchError<F::Error>> {
+    let (bet, _) = find_round_bet_address(round, player);
+    fetch_account(fetcher, &bet).await
+}
+
+// Fetches a program account, checking its owner, discriminator and version
+async fn fetch_account<T: ProgramAccount, F: AccountFetcher>(
+    fetcher: &mut F,
+    address: &Pubkey,
+) -> Result<T, FetchError<F::Error>> {
+    let (owner, data) = fetcher
+        .get_account_data(address)
+        .await
+        .map_err(FetchError::Rpc)?
+        .ok_or(FetchError::AccountNotFound(*address))?;
+
+    if owner != id::id() {
+        return Err(FetchError::IllegalOwner(*address));
+    }
+    T::from_account_data(&data).map_err(|err| FetchError::InvalidAccount(*address, err))
+}
+
+/// Decodes the events the casino program deployed at `program_id` logged in a
+/// transaction's log messages. Only `Program data:` lines logged while that
+/// program itself was running are read, and data that is not a `CasinoEvent`
+/// is skipped.
+pub fn decode_events<S: AsRef<str>>(program_id: &Pubkey, log_messages: &[S]) -> Vec<CasinoEvent> {
+    let program_id = program_id.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for message in log_messages {
+        let message = message.as_ref();
+        if let Some(data) = message.strip_prefix("Program data: ") {
+            if invocations.last() == Some(&program_id.as_str()) {
+                events.extend(decode_event(data));
+            }
+        } else if let Some(message) = message.strip_prefix("Program ") {
+            // Track which program is running from the invoke/success/failed lines
+            let mut words = message.split(' ');
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => invocations.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+// Decodes the base64 fields of a `Program data:` line as an event
+fn decode_event(data: &str) -> Option<CasinoEvent> {
+    let fields = data
+        .split(' ')
+        .map(|field| STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    CasinoEvent::try_from_slice(&fields.concat()).ok()
+}
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: ISC
+//! Coinflip, played against the house vault or against another player.
+//!
+//! Either way the winner takes a pot of two equal stakes less the casino's
+//! house edge as rake: a house bet puts up the player's stake against the
+//! vault's, a challenge the stakes of the player who opened it and of the
+//! opponent who accepted it.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::randomness;
+
+/// Side of the coin a bet is placed on
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoinSide {
+    Heads,
+    Tails,
+}
+
+impl CoinSide {
+    /// The side the opponent of a challenge takes
+    pub fn opposite(self) -> CoinSide {
+        match self {
+            CoinSide::Heads => CoinSide::Tails,
+            CoinSide::Tails => CoinSide::Heads,
+        }
+    }
+}
+
+/// Side the coin lands on for a result hash
+pub fn flip(result_hash: &[u8; 32]) -> CoinSide {
+    match randomness::roll(result_hash, 2) {
+        0 => CoinSide::Heads,
+        _ => CoinSide::Tails,
+    }
+}
+
+/// What the winner of two `stake`s takes once the house edge is raked off,
+/// or `None` if the pot overflows
+pub fn pot_payout(stake: u64, house_edge: u16) -> Option<u64> {
+    let pot = stake.checked_mul(2)?;
+    let rake = pot.checked_mul(house_edge as u64)? / 10_000;
+    pot.checked_sub(rake)
+}
+
+/// Amount paid for a house bet on `side` given a result hash (0 if it lost),
+/// or `None` if the payout overflows
+pub fn payout(bet_amount: u64, side: CoinSide, house_edge: u16, result_hash: &[u8; 32]) -> Option<u64> {
+    if flip(result_hash) == side {
+        pot_payout(bet_amount, house_edge)
+    } else {
+        Some(0)
+    }
+}
@@ -0,0 +1,843 @@
+// Compute-unit benchmarks. Every instruction is run on program-test and the
+// units it consumed are written to `target/compute_units.md`.
+//
+// Compute units are only metered for the SBF build: when `casino_game.so` is
+// found (`cargo test-sbf` points `SBF_OUT_DIR` at it) the measurements are
+// compared against `compute_units.txt` and the test fails if any instruction
+// got more expensive than the tolerance allows or has no baseline. Natively
+// built, the scenarios still run but the comparison is skipped with a warning.
+#[cfg(test)]
+mod compute_units_tests {
+    use {
+        crate::{
+            client,
+            coinflip::{self, CoinSide},
+            crash, dice,
+            european_roulette::{self, RouletteBet, RouletteWager, MAX_BETS},
+            id, process_instruction, randomness,
+            roulette::{self, RouletteColor},
+            test_utils::{self, CLIENT_SEED},
+            CasinoStateV1, GameStateV1, SettleResult, MIN_SETTLE_TIMEOUT, ROLE_ALL,
+        },
+        borsh::BorshSerialize,
+        solana_program::{
+            clock::Clock, instruction::Instruction, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+            system_instruction,
+        },
+        solana_program_test::*,
+        solana_sdk::{
+            account::Account,
+            signature::{Keypair, Signer},
+            transaction::Transaction,
+        },
+        spl_associated_token_account::get_associated_token_address,
+        std::{collections::BTreeMap, env, fmt::Write as _, fs, io::Write as _},
+    };
+
+    // Measurements of the SBF build, one `<units> <benchmark>` per line
+    const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/compute_units.txt");
+
+    // Report of the latest run
+    const REPORT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/compute_units.md");
+
+    // How much more than its baseline a benchmark may consume, in percent.
+    // Override with `COMPUTE_UNITS_TOLERANCE`.
+    const DEFAULT_TOLERANCE_PERCENT: u64 = 5;
+
+    // Legacy operator counts `MigrateCasinoState` is measured with
+    const MIGRATED_OPERATORS: [usize; 2] = [1, 10];
+
+    // Runs transactions against one program-test bank and records what the
+    // measured ones consumed
+    struct Bench {
+        context: ProgramTestContext,
+        measurements: Vec<(String, u64)>,
+    }
+
+    impl Bench {
+        // Processes a transaction without recording it
+        async fn run(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> u64 {
+            let payer = self.context.payer.insecure_clone();
+            let mut all_signers = vec![&payer];
+            all_signers.extend_from_slice(signers);
+
+            let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+            transaction.sign(&all_signers, self.context.last_blockhash);
+
+            let result = self.context
+                .banks_client
+                .process_transaction_with_metadata(transaction)
+                .await
+                .unwrap();
+            if let Err(err) = result.result {
+                panic!("transaction failed: {:?}\n{:#?}", err, result.metadata);
+            }
+            result.metadata.unwrap().compute_units_consumed
+        }
+
+        // Processes a single instruction and records the units it consumed
+        async fn measure(&mut self, name: &str, instruction: Instruction, signers: &[&Keypair]) {
+            let units = self.run(&[instruction], signers).await;
+            self.measurements.push((name.to_string(), units));
+        }
+
+        // Settles a game with the outcome the server seed gives
+        async fn settle(&mut self, name: &str, operator: &Keypair, casino: &Pubkey, game: &Pubkey, server_seed: [u8; 32]) {
+            let casino_state = client::fetch_casino_state(&mut self.context.banks_client, casino).await.unwrap();
+            let game_state = client::fetch_game_state(&mut self.context.banks_client, game).await.unwrap();
+            let (is_win, win_amount) = client::settlement(&game_state, game, &server_seed).unwrap();
+
+            let payee = game_state.payee(is_win);
+            let instruction = match casino_state.mint {
+                Some(mint) => client::settle_token_game(
+                    &operator.pubkey(),
+                    casino,
+                    game,
+                    &payee,
+                    &mint,
+                    is_win,
+                    win_amount,
+                    server_seed,
+                ),
+                None => client::settle_game(&operator.pubkey(), casino, game, &payee, is_win, win_amount, server_seed),
+            };
+            self.measure(name, instruction, &[operator]).await;
+        }
+
+        // Posts the operator's commitment to a server seed for a bet to be
+        // placed against, without recording it. Bets won alike may share a
+        // seed, so a fresh blockhash keeps a repeated post from being deduplicated
+        async fn commit(&mut self, operator: &Keypair, casino: &Pubkey, server_seed: &[u8; 32]) {
+            self.context.last_blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+            let instruction = client::post_commitment(&operator.pubkey(), casino, randomness::commit(server_seed));
+            self.run(&[instruction], &[operator]).await;
+        }
+
+        async fn advance_clock(&mut self, seconds: i64) {
+            let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+            clock.unix_timestamp += seconds;
+            self.context.set_sysvar(&clock);
+        }
+    }
+
+    fn funded_account() -> Account {
+        Account {
+            lamports: 100_000_000_000,
+            ..Account::default()
+        }
+    }
+
+    fn game_id(index: u8) -> [u8; 32] {
+        [index; 32]
+    }
+
+    // "1 bet", "16 bets"
+    fn count(count: usize, noun: &str) -> String {
+        format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+    }
+
+    fn read_baseline() -> Option<BTreeMap<String, u64>> {
+        let baseline = fs::read_to_string(BASELINE_PATH).ok()?;
+        let baseline = baseline
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (units, name) = line.split_once(' ')?;
+                Some((name.to_string(), units.parse().ok()?))
+            })
+            .collect();
+        Some(baseline)
+    }
+
+    fn write_baseline(measurements: &[(String, u64)]) {
+        let mut baseline = String::from(
+            "# Compute units consumed by the SBF build, one `<units> <benchmark>` per line.\n\
+             # Regenerate with `UPDATE_COMPUTE_UNITS=1 cargo test-sbf compute_units`.\n",
+        );
+        for (name, units) in measurements {
+            writeln!(baseline, "{} {}", units, name).unwrap();
+        }
+        fs::write(BASELINE_PATH, baseline).unwrap();
+    }
+
+    fn write_report(measurements: &[(String, u64)], baseline: &BTreeMap<String, u64>, sbf: bool) {
+        let mut report = String::from("# Compute units\n\n");
+        if !sbf {
+            report.push_str("Measured on the native build, whose compute units are not metered.\n\n");
+        }
+        report.push_str("| Benchmark | Units | Baseline | Change |\n|---|---:|---:|---:|\n");
+        for (name, units) in measurements {
+            let (base, change) = match baseline.get(name) {
+                Some(&base) => (
+                    base.to_string(),
+                    format!("{:+.1}%", (*units as f64 - base as f64) * 100.0 / base.max(1) as f64),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            writeln!(report, "| {} | {} | {} | {} |", name, units, base, change).unwrap();
+        }
+
+        if let Some(dir) = std::path::Path::new(REPORT_PATH).parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(REPORT_PATH, report).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compute_units() {
+        let sbf = find_file("casino_game.so").is_some();
+        let mut program_test = ProgramTest::new(
+            "casino_game",
+            id::id(),
+            processor!(process_instruction),
+        );
+        program_test.prefer_bpf(sbf);
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let new_authority = Keypair::new();
+        let operator = Keypair::new();
+        let player = Keypair::new();
+        let opponent = Keypair::new();
+        let token_authority = Keypair::new();
+        let mint = Keypair::new();
+        for account in [&authority, &new_authority, &operator, &player, &opponent, &token_authority] {
+            program_test.add_account(account.pubkey(), funded_account());
+        }
+        let (casino, _) = client::find_casino_state_address(&authority.pubkey());
+        let (token_casino, _) = client::find_casino_state_address(&token_authority.pubkey());
+
+        // Casinos written by the version 1 program, to measure the migration
+        let mut legacy_casinos = Vec::new();
+        for count in MIGRATED_OPERATORS {
+            let legacy_authority = Keypair::new();
+            let (legacy_casino, _) = client::find_casino_state_address(&legacy_authority.pubkey());
+            let mut operators = vec![legacy_authority.pubkey()];
+            operators.extend((1..count).map(|_| Pubkey::new_unique()));
+            let data = CasinoStateV1 {
+                authority: legacy_authority.pubkey(),
+                house_edge: 250,
+                min_bet: 100_000,
+                max_bet: 1_000_000_000,
+                operators: operators.clone(),
+            }
+            .try_to_vec()
+            .unwrap();
+
+            program_test.add_account(legacy_authority.pubkey(), funded_account());
+            program_test.add_account(
+                legacy_casino,
+                Account {
+                    lamports: Rent::default().minimum_balance(data.len()),
+                    data,
+                    owner: id::id(),
+                    ..Account::default()
+                },
+            );
+            legacy_casinos.push((legacy_authority, legacy_casino, operators));
+        }
+
+        // An unsettled game placed with the version 1 program
+        let legacy_game_id = game_id(255);
+        let data = GameStateV1 {
+            player: player.pubkey(),
+            bet_amount: 1_000_000,
+            is_settled: false,
+            is_win: false,
+            win_amount: 0,
+            result_hash: [0; 32],
+        }
+        .try_to_vec()
+        .unwrap();
+        program_test.add_account(
+            client::find_legacy_game_address(&legacy_game_id).0,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()) + 1_000_000,
+                data,
+                owner: id::id(),
+                ..Account::default()
+            },
+        );
+
+        let mut bench = Bench {
+            context: program_test.start_with_context().await,
+            measurements: Vec::new(),
+        };
+
+        // Casino administration
+        bench.measure(
+            "Initialize",
+            client::initialize(&authority.pubkey(), &casino, 250, 100_000, 1_000_000_000),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "AddOperator",
+            client::add_operator(&authority.pubkey(), &casino, &operator.pubkey(), ROLE_ALL, 100_000_000_000),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "FundVault",
+            client::fund_vault(&authority.pubkey(), &casino, 50_000_000_000),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "UpdateParams",
+            client::update_params(&operator.pubkey(), &casino, Some(300), Some(100_000), Some(1_000_000_000), Some(MIN_SETTLE_TIMEOUT)),
+            &[&operator],
+        ).await;
+
+        // Standard games, won and lost
+        for (index, is_win) in [(1, true), (2, false)] {
+            let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(index));
+            let server_seed = test_utils::seed_for(
+                &game,
+                &CLIENT_SEED,
+                |hash| randomness::is_win(hash) == is_win,
+            );
+            let post_commitment = client::post_commitment(&operator.pubkey(), &casino, randomness::commit(&server_seed));
+            let place_bet = client::place_bet(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(index),
+                10_000_000,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            );
+            let outcome = if is_win { "win" } else { "loss" };
+            if is_win {
+                bench.measure("PostCommitment", post_commitment, &[&operator]).await;
+                bench.measure("PlaceBet", place_bet, &[&player]).await;
+            } else {
+                bench.run(&[post_commitment, place_bet], &[&operator, &player]).await;
+            }
+            bench.settle(&format!("SettleGame ({})", outcome), &operator, &casino, &game, server_seed).await;
+        }
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(1));
+        bench.measure(
+            "CloseGame",
+            client::close_game(&operator.pubkey(), &casino, &game, &player.pubkey()),
+            &[&operator],
+        ).await;
+
+        // A full batch of standard games settled by one instruction, won and
+        // lost. More games are placed than one transaction holds, so the first
+        // batch is as large as `settle_batch` makes them.
+        let mut games = Vec::new();
+        for index in 0..16 {
+            let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(16 + index));
+            let server_seed = test_utils::seed_for(
+                &game,
+                &CLIENT_SEED,
+                |hash| randomness::is_win(hash) == (index % 2 == 0),
+            );
+            bench.commit(&operator, &casino, &server_seed).await;
+            bench.run(
+                &[client::place_bet(
+                    &player.pubkey(),
+                    &casino,
+                    &game,
+                    game_id(16 + index),
+                    10_000_000,
+                    CLIENT_SEED,
+                    randomness::commit(&server_seed),
+                )],
+                &[&player],
+            ).await;
+            games.push((game, server_seed));
+        }
+        let mut settlements = Vec::new();
+        for (game, server_seed) in games {
+            let game_state = client::fetch_game_state(&mut bench.context.banks_client, &game).await.unwrap();
+            let (is_win, win_amount) = client::settlement(&game_state, &game, &server_seed).unwrap();
+            settlements.push((game, game_state.payee(is_win), SettleResult { is_win, win_amount, server_seed }));
+        }
+        let mut batches = client::settle_batch(&operator.pubkey(), &casino, None, &settlements);
+        assert!(batches.len() > 1);
+        let batch = batches.remove(0);
+        // Five fixed accounts, then each game's account and payee
+        let batch_games = (batch.accounts.len() - 5) / 2;
+        bench.measure(&format!("SettleBatch ({})", count(batch_games, "game")), batch, &[&operator]).await;
+        for batch in batches {
+            bench.run(&[batch], &[&operator]).await;
+        }
+
+        // Roulette on the 15-slot wheel
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(3));
+        let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, |hash| {
+            roulette::slot_color(roulette::winning_slot(hash)) == RouletteColor::Green
+        });
+        bench.commit(&operator, &casino, &server_seed).await;
+        bench.measure(
+            "PlaceRouletteBet",
+            client::place_roulette_bet(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(3),
+                1_000_000,
+                RouletteColor::Green,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            ),
+            &[&player],
+        ).await;
+        bench.settle("SettleGame (roulette)", &operator, &casino, &game, server_seed).await;
+
+        // European roulette with the fewest and the most bets a game can hold
+        for (index, bet_count) in [(4, 1), (5, MAX_BETS)] {
+            let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(index));
+            let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, |hash| european_roulette::spin(hash) != 0);
+            bench.commit(&operator, &casino, &server_seed).await;
+            let bets = (0..bet_count)
+                .map(|number| RouletteWager {
+                    bet: RouletteBet::Number(number as u8 + 1),
+                    amount: 100_000,
+                })
+                .collect();
+            bench.measure(
+                &format!("PlaceRouletteBets ({})", count(bet_count, "bet")),
+                client::place_roulette_bets(
+                    &player.pubkey(),
+                    &casino,
+                    &game,
+                    game_id(index),
+                    bets,
+                    CLIENT_SEED,
+                    randomness::commit(&server_seed),
+                ),
+                &[&player],
+            ).await;
+            bench.settle(&format!("SettleGame (European roulette, {})", count(bet_count, "bet")), &operator, &casino, &game, server_seed).await;
+        }
+
+        // Dice, rolled under the target to pay the derived multiplier
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(8));
+        let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, |hash| dice::is_win(50, dice::roll(hash)));
+        bench.commit(&operator, &casino, &server_seed).await;
+        bench.measure(
+            "PlaceDiceBet",
+            client::place_dice_bet(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(8),
+                1_000_000,
+                50,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            ),
+            &[&player],
+        ).await;
+        bench.settle("SettleGame (dice)", &operator, &casino, &game, server_seed).await;
+
+        // Coinflip against the house
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(9));
+        let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, |hash| coinflip::flip(hash) == CoinSide::Heads);
+        bench.commit(&operator, &casino, &server_seed).await;
+        bench.measure(
+            "PlaceCoinflipBet",
+            client::place_coinflip_bet(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(9),
+                1_000_000,
+                CoinSide::Heads,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            ),
+            &[&player],
+        ).await;
+        bench.settle("SettleGame (coinflip)", &operator, &casino, &game, server_seed).await;
+
+        // A coinflip challenge the opponent wins, and one called off
+        let slot_hash = [9; 32];
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(10));
+        let client_seed = randomness::combine_seeds(&CLIENT_SEED, &slot_hash);
+        let server_seed = test_utils::seed_for(&game, &client_seed, |hash| coinflip::flip(hash) == CoinSide::Tails);
+        bench.commit(&operator, &casino, &server_seed).await;
+        bench.measure(
+            "OpenCoinflipChallenge",
+            client::open_coinflip_challenge(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(10),
+                1_000_000,
+                CoinSide::Heads,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            ),
+            &[&player],
+        ).await;
+        test_utils::set_slot_hash(&mut bench.context, slot_hash);
+        bench.measure(
+            "AcceptCoinflipChallenge",
+            client::accept_coinflip_challenge(&opponent.pubkey(), &casino, &game),
+            &[&opponent],
+        ).await;
+        bench.settle("SettleGame (coinflip challenge)", &operator, &casino, &game, server_seed).await;
+
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(11));
+        bench.commit(&operator, &casino, &[0; 32]).await;
+        bench.run(
+            &[client::open_coinflip_challenge(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(11),
+                1_000_000,
+                CoinSide::Heads,
+                CLIENT_SEED,
+                randomness::commit(&[0; 32]),
+            )],
+            &[&player],
+        ).await;
+        bench.measure(
+            "CancelCoinflipChallenge",
+            client::cancel_coinflip_challenge(&player.pubkey(), &casino, &game),
+            &[&player],
+        ).await;
+
+        // A crash round the player cashes out of by hand and the opponent
+        // automatically, and one left for its bets to be refunded
+        let clock: Clock = bench.context.banks_client.get_sysvar().await.unwrap();
+        let starts_at = clock.unix_timestamp + 10;
+        let (round, _) = client::find_crash_round_address(&casino, &game_id(12));
+        let (refunded_round, _) = client::find_crash_round_address(&casino, &game_id(13));
+        let server_seed = test_utils::seed_for(&round, &game_id(12), |hash| {
+            (20_000..100_000).contains(&crash::crash_point(hash, 250))
+        });
+        bench.measure(
+            "OpenCrashRound",
+            client::open_crash_round(&operator.pubkey(), &casino, game_id(12), randomness::commit(&server_seed), starts_at),
+            &[&operator],
+        ).await;
+        bench.run(
+            &[client::open_crash_round(&operator.pubkey(), &casino, game_id(13), [0; 32], starts_at)],
+            &[&operator],
+        ).await;
+        bench.measure(
+            "JoinCrashRound",
+            client::join_crash_round(&player.pubkey(), &casino, &round, 1_000_000, None),
+            &[&player],
+        ).await;
+        bench.run(
+            &[
+                client::join_crash_round(&opponent.pubkey(), &casino, &round, 1_000_000, Some(15_000)),
+                client::join_crash_round(&player.pubkey(), &casino, &refunded_round, 1_000_000, None),
+            ],
+            &[&opponent, &player],
+        ).await;
+        bench.advance_clock(11).await;
+        bench.measure(
+            "CashOut",
+            client::cash_out(&player.pubkey(), &round, crash::multiplier_at(1)),
+            &[&player],
+        ).await;
+        let crash_round = client::fetch_crash_round(&mut bench.context.banks_client, &round).await.unwrap();
+        bench.advance_clock(crash::seconds_to_reach(client::crash_point(&server_seed, &crash_round, &round))).await;
+        let players = [player.pubkey(), opponent.pubkey()];
+        let mut settle_instructions = client::settle_crash_round(&operator.pubkey(), &casino, &round, None, &players, server_seed);
+        bench.measure(
+            &format!("SettleCrashRound ({})", count(players.len(), "bet")),
+            settle_instructions.remove(0),
+            &[&operator],
+        ).await;
+
+        // A roulette round the player and opponent both bet on, and one left
+        // for its bets to be refunded
+        let clock: Clock = bench.context.banks_client.get_sysvar().await.unwrap();
+        let betting_ends_at = clock.unix_timestamp + 1;
+        let (round, _) = client::find_round_address(&casino, &game_id(14));
+        let (refunded_roulette_round, _) = client::find_round_address(&casino, &game_id(15));
+        let server_seed = test_utils::seed_for(&round, &game_id(14), |hash| {
+            roulette::slot_color(roulette::winning_slot(hash)) == RouletteColor::Red
+        });
+        bench.measure(
+            "OpenRound",
+            client::open_round(&operator.pubkey(), &casino, game_id(14), randomness::commit(&server_seed), betting_ends_at),
+            &[&operator],
+        ).await;
+        bench.run(
+            &[client::open_round(&operator.pubkey(), &casino, game_id(15), [0; 32], betting_ends_at)],
+            &[&operator],
+        ).await;
+        bench.measure(
+            "PlaceRoundBet",
+            client::place_round_bet(&player.pubkey(), &casino, &round, RouletteColor::Red, 1_000_000),
+            &[&player],
+        ).await;
+        bench.run(
+            &[
+                client::place_round_bet(&opponent.pubkey(), &casino, &round, RouletteColor::Black, 1_000_000),
+                client::place_round_bet(&player.pubkey(), &casino, &refunded_roulette_round, RouletteColor::Green, 1_000_000),
+            ],
+            &[&opponent, &player],
+        ).await;
+        bench.measure("LockRound", client::lock_round(&operator.pubkey(), &casino, &round), &[&operator]).await;
+        bench.measure(
+            "SpinRound",
+            client::spin_round(&operator.pubkey(), &casino, &round, server_seed),
+            &[&operator],
+        ).await;
+        let players = [player.pubkey(), opponent.pubkey()];
+        let mut settle_instructions = client::settle_round_bets(&operator.pubkey(), &operator.pubkey(), &casino, &round, None, &players);
+        bench.measure(
+            &format!("SettleRoundBets ({})", count(players.len(), "bet")),
+            settle_instructions.remove(0),
+            &[&operator],
+        ).await;
+
+        // A refund once the settlement timeout has passed
+        let (game, _) = client::find_game_address(&casino, &player.pubkey(), &game_id(6));
+        let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, randomness::is_win);
+        bench.commit(&operator, &casino, &server_seed).await;
+        bench.run(
+            &[client::place_bet(
+                &player.pubkey(),
+                &casino,
+                &game,
+                game_id(6),
+                10_000_000,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            )],
+            &[&player],
+        ).await;
+        bench.advance_clock(MIN_SETTLE_TIMEOUT + 1).await;
+        bench.measure("ClaimRefund", client::claim_refund(&player.pubkey(), &casino, &game), &[&player]).await;
+        bench.measure(
+            "ClaimCrashRefund",
+            client::claim_crash_refund(&player.pubkey(), &casino, &refunded_round),
+            &[&player],
+        ).await;
+        bench.measure(
+            "CloseCrashRound",
+            client::close_crash_round(&operator.pubkey(), &casino, &refunded_round, &operator.pubkey()),
+            &[&operator],
+        ).await;
+        bench.measure(
+            "ClaimRoundRefund",
+            client::claim_round_refund(&player.pubkey(), &casino, &refunded_roulette_round),
+            &[&player],
+        ).await;
+        bench.measure(
+            "CloseRound",
+            client::close_round(&operator.pubkey(), &casino, &refunded_roulette_round, &operator.pubkey()),
+            &[&operator],
+        ).await;
+
+        // Pausing, the bankroll and the casino's ownership
+        bench.measure("Pause", client::pause(&operator.pubkey(), &casino, true, true), &[&operator]).await;
+        bench.measure("Unpause", client::unpause(&operator.pubkey(), &casino, true, true), &[&operator]).await;
+        bench.measure(
+            "WithdrawFromVault",
+            client::withdraw_from_vault(&authority.pubkey(), &casino, &authority.pubkey(), 1_000_000_000),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "RemoveOperator",
+            client::remove_operator(&authority.pubkey(), &casino, &operator.pubkey()),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "ProposeAuthority",
+            client::propose_authority(&authority.pubkey(), &casino, &new_authority.pubkey()),
+            &[&authority],
+        ).await;
+        bench.measure(
+            "AcceptAuthority",
+            client::accept_authority(&new_authority.pubkey(), &casino, &authority.pubkey()),
+            &[&new_authority],
+        ).await;
+
+        // Migration cost grows with the legacy operator list, which becomes
+        // one operator account each
+        for (legacy_authority, legacy_casino, operators) in &legacy_casinos {
+            bench.measure(
+                &format!("MigrateCasinoState ({})", count(operators.len(), "operator")),
+                client::migrate_casino_state(&legacy_authority.pubkey(), legacy_casino, operators),
+                &[legacy_authority],
+            ).await;
+        }
+        bench.measure(
+            "ClaimLegacyRefund",
+            client::claim_legacy_refund(&player.pubkey(), legacy_game_id),
+            &[&player],
+        ).await;
+
+        // An SPL token casino, whose transfers are CPIs into the token program
+        let authority_token = get_associated_token_address(&token_authority.pubkey(), &mint.pubkey());
+        let player_token = get_associated_token_address(&player.pubkey(), &mint.pubkey());
+        let rent = bench.context.banks_client.get_rent().await.unwrap();
+        let payer = bench.context.payer.pubkey();
+        bench.run(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &token_authority.pubkey(), None, 6)
+                    .unwrap(),
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &payer,
+                    &token_authority.pubkey(),
+                    &mint.pubkey(),
+                    &spl_token::id(),
+                ),
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &payer,
+                    &player.pubkey(),
+                    &mint.pubkey(),
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &authority_token,
+                    &token_authority.pubkey(),
+                    &[],
+                    100_000_000,
+                )
+                .unwrap(),
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &player_token,
+                    &token_authority.pubkey(),
+                    &[],
+                    10_000_000,
+                )
+                .unwrap(),
+            ],
+            &[&mint, &token_authority],
+        ).await;
+
+        bench.measure(
+            "Initialize (token)",
+            client::initialize_with_mint(&token_authority.pubkey(), &token_casino, &mint.pubkey(), 250, 100_000, 5_000_000),
+            &[&token_authority],
+        ).await;
+        bench.measure(
+            "FundVault (token)",
+            client::fund_vault_tokens(&token_authority.pubkey(), &token_casino, &mint.pubkey(), 50_000_000),
+            &[&token_authority],
+        ).await;
+        let (game, _) = client::find_game_address(&token_casino, &player.pubkey(), &game_id(7));
+        let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, randomness::is_win);
+        bench.commit(&token_authority, &token_casino, &server_seed).await;
+        bench.measure(
+            "PlaceBet (token)",
+            client::place_token_bet(
+                &player.pubkey(),
+                &token_casino,
+                &game,
+                &mint.pubkey(),
+                game_id(7),
+                1_000_000,
+                CLIENT_SEED,
+                randomness::commit(&server_seed),
+            ),
+            &[&player],
+        ).await;
+        bench.settle("SettleGame (token, win)", &token_authority, &token_casino, &game, server_seed).await;
+
+        // A full batch of won token games, each paid by a CPI. More games are
+        // placed than one instruction holds, so the first batch is as large
+        // as `settle_batch` makes them.
+        let mut settlements = Vec::new();
+        for index in 0..=client::max_settled_per_instruction(Some(&mint.pubkey())) as u8 {
+            let (game, _) = client::find_game_address(&token_casino, &player.pubkey(), &game_id(48 + index));
+            let server_seed = test_utils::seed_for(&game, &CLIENT_SEED, randomness::is_win);
+            bench.commit(&token_authority, &token_casino, &server_seed).await;
+            bench.run(
+                &[client::place_token_bet(
+                    &player.pubkey(),
+                    &token_casino,
+                    &game,
+                    &mint.pubkey(),
+                    game_id(48 + index),
+                    1_000_000,
+                    CLIENT_SEED,
+                    randomness::commit(&server_seed),
+                )],
+                &[&player],
+            ).await;
+            let game_state = client::fetch_game_state(&mut bench.context.banks_client, &game).await.unwrap();
+            let (is_win, win_amount) = client::settlement(&game_state, &game, &server_seed).unwrap();
+            settlements.push((game, game_state.payee(is_win), SettleResult { is_win, win_amount, server_seed }));
+        }
+        let mut batches = client::settle_batch(&token_authority.pubkey(), &token_casino, Some(&mint.pubkey()), &settlements);
+        assert!(batches.len() > 1);
+        let batch = batches.remove(0);
+        // Seven fixed accounts, then each game's account, payee and token account
+        let batch_games = (batch.accounts.len() - 7) / 3;
+        bench.measure(&format!("SettleBatch (token, {})", count(batch_games, "game")), batch, &[&token_authority]).await;
+
+        // Report, and compare the SBF build against the baseline
+        let baseline = read_baseline();
+        write_report(&bench.measurements, baseline.as_ref().unwrap_or(&BTreeMap::new()), sbf);
+        if !sbf {
+            // Written past the test harness's output capture so the skip shows
+            // up in a passing run
+            writeln!(
+                std::io::stderr(),
+                "\n*** SKIPPED compute unit regression check: casino_game.so not found, so the native \
+                 build ran unmetered. Run `cargo test-sbf compute_units` to compare against {}. ***\n",
+                BASELINE_PATH,
+            )
+            .unwrap();
+            return;
+        }
+        if env::var("UPDATE_COMPUTE_UNITS").is_ok() {
+            write_baseline(&bench.measurements);
+            return;
+        }
+        let baseline = baseline.unwrap_or_else(|| {
+            panic!(
+                "no compute unit baseline at {}, record one with `UPDATE_COMPUTE_UNITS=1 cargo test-sbf compute_units`",
+                BASELINE_PATH,
+            )
+        });
+
+        // A benchmark without a baseline would otherwise never be checked
+        let unrecorded: Vec<&str> = bench
+            .measurements
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| !baseline.contains_key(*name))
+            .collect();
+        assert!(
+            unrecorded.is_empty(),
+            "benchmarks missing from {}, record them with `UPDATE_COMPUTE_UNITS=1 cargo test-sbf compute_units`:\n{}",
+            BASELINE_PATH,
+            unrecorded.join("\n"),
+        );
+
+        let tolerance = env::var("COMPUTE_UNITS_TOLERANCE")
+            .ok()
+            .and_then(|tolerance| tolerance.parse().ok())
+            .unwrap_or(DEFAULT_TOLERANCE_PERCENT);
+        let regressions: Vec<String> = bench
+            .measurements
+            .iter()
+            .filter_map(|(name, units)| {
+                let base = baseline[name];
+                (units * 100 > base * (100 + tolerance))
+                    .then(|| format!("{}: {} units, baseline {}", name, units, base))
+            })
+            .collect();
+        assert!(
+            regressions.is_empty(),
+            "compute units grew more than {}% over {}:\n{}",
+            tolerance,
+            BASELINE_PATH,
+            regressions.join("\n"),
+        );
+    }
+}
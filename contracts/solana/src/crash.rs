@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: ISC
+//! Crash. When a round's betting window closes its multiplier starts at
+//! 1.00x and climbs 6% a second until the round crashes; bets that cashed out
+//! below the crash point are paid their stake times the multiplier they
+//! cashed out at, the others are lost.
+//!
+//! The crash point comes from the round's committed server seed with the
+//! house edge applied, so cashing out at any multiplier returns
+//! `100% - house_edge` of the stake on average. Multipliers are in basis
+//! points, 10_000 being 1.00x.
+use crate::randomness;
+
+/// Multiplier of 1.00x, in basis points
+pub const ONE_X: u64 = 10_000;
+
+/// Highest multiplier a round reaches, in basis points (100.00x)
+pub const MAX_MULTIPLIER: u64 = 1_000_000;
+
+/// What the multiplier grows to every second, in basis points of its value
+/// the second before
+pub const GROWTH_PER_SECOND: u64 = 10_600;
+
+// Number of distinct rolls a crash point is drawn from
+const ROLLS: u32 = 1_000_000;
+
+/// Whether a bet may cash out at `multiplier`, automatically or by hand
+pub fn is_valid_cashout(multiplier: u64) -> bool {
+    multiplier > ONE_X && multiplier <= MAX_MULTIPLIER
+}
+
+/// Multiplier a round has reached `elapsed` seconds after it started
+pub fn multiplier_at(elapsed: i64) -> u64 {
+    let mut multiplier = ONE_X;
+    for _ in 0..elapsed.max(0) {
+        if multiplier == MAX_MULTIPLIER {
+            break;
+        }
+        multiplier = grow(multiplier);
+    }
+    multiplier
+}
+
+/// Seconds after its start a round takes to reach `multiplier`
+pub const fn seconds_to_reach(multiplier: u64) -> i64 {
+    let target = if multiplier < MAX_MULTIPLIER { multiplier } else { MAX_MULTIPLIER };
+    let mut current = ONE_X;
+    let mut elapsed = 0;
+    while current < target {
+        current = grow(current);
+        elapsed += 1;
+    }
+    elapsed
+}
+
+/// Longest a round runs before it crashes, reaching `MAX_MULTIPLIER`
+pub const MAX_DURATION: i64 = seconds_to_reach(MAX_MULTIPLIER);
+
+// The multiplier a second later
+const fn grow(multiplier: u64) -> u64 {
+    let grown = multiplier * GROWTH_PER_SECOND / ONE_X;
+    if grown < MAX_MULTIPLIER { grown } else { MAX_MULTIPLIER }
+}
+
+/// Multiplier a round crashes at for a result hash: fair odds less the house
+/// edge, between 1.00x (nobody wins) and `MAX_MULTIPLIER`
+pub fn crash_point(result_hash: &[u8; 32], house_edge: u16) -> u64 {
+    let roll = randomness::roll(result_hash, ROLLS) as u64;
+    let point = ONE_X.saturating_sub(house_edge as u64) * ROLLS as u64 / (ROLLS as u64 - roll);
+    point.clamp(ONE_X, MAX_MULTIPLIER)
+}
+
+/// Multiplier a bet cashed out at: the lower of its auto cash-out and the one
+/// it cashed out at by hand, if it has either
+pub fn cashout(auto_cashout: Option<u64>, cashed_out_at: Option<u64>) -> Option<u64> {
+    match (auto_cashout, cashed_out_at) {
+        (Some(auto), Some(manual)) => Some(auto.min(manual)),
+        (auto, manual) => auto.or(manual),
+    }
+}
+
+/// Amount paid for a bet that cashed out at `cashout` in a round that crashed
+/// at `crash_point` (0 if it didn't cash out below it), or `None` if the
+/// payout overflows
+pub fn payout(bet_amount: u64, cashout: Option<u64>, crash_point: u64) -> Option<u64> {
+    match cashout {
+        Some(multiplier) if multiplier < crash_point => bet_amount.checked_mul(multiplier)?.checked_div(ONE_X),
+        _ => Some(0),
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: ISC
+//! Roll-under dice. The player picks a target between 1 and 98 and wins if
+//! the roll, 0.00-99.99, lands below it.
+//!
+//! The payout is fair odds for the target less the casino's house edge, so a
+//! bet under 50 with a 2.5% edge pays 1.95x the stake.
+use crate::randomness;
+
+/// Lowest target a bet may roll under
+pub const MIN_TARGET: u8 = 1;
+
+/// Highest target a bet may roll under
+pub const MAX_TARGET: u8 = 98;
+
+/// Number of distinct rolls, in hundredths (0.00-99.99)
+pub const ROLLS: u32 = 10_000;
+
+/// Whether a bet may roll under `target`
+pub fn is_valid_target(target: u8) -> bool {
+    (MIN_TARGET..=MAX_TARGET).contains(&target)
+}
+
+/// Roll for a result hash, in hundredths (0-9999 for 0.00-99.99)
+pub fn roll(result_hash: &[u8; 32]) -> u16 {
+    randomness::roll(result_hash, ROLLS) as u16
+}
+
+/// Whether a roll wins a bet under `target`
+pub fn is_win(target: u8, roll: u16) -> bool {
+    roll < target as u16 * 100
+}
+
+/// Total payout multiplier (stake included) for a winning bet under
+/// `target`, in basis points: `(100% - house_edge) / target%`
+pub fn multiplier_bps(target: u8, house_edge: u16) -> u64 {
+    10_000u64.saturating_sub(house_edge as u64) * 100 / target as u64
+}
+
+/// Amount paid for a bet under `target` given a result hash (0 if it lost),
+/// or `None` if the payout overflows
+pub fn payout(bet_amount: u64, target: u8, house_edge: u16, result_hash: &[u8; 32]) -> Option<u64> {
+    if !is_win(target, roll(result_hash)) {
+        return Some(0);
+    }
+    win_payout(bet_amount, target, house_edge)
+}
+
+/// Amount paid for a winning bet under `target`, or `None` if it overflows
+pub fn win_payout(bet_amount: u64, target: u8, house_edge: u16) -> Option<u64> {
+    bet_amount
+        .checked_mul(multiplier_bps(target, house_edge))?
+        .checked_div(10_000)
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod edge_cases_tests {
     use {
-        crate::{id, CasinoState, GameState, process_instruction},
+        crate::{id, test_utils::*, CasinoState, GameState, process_instruction},
         borsh::BorshDeserialize,
         solana_program::{
             instruction::{AccountMeta, Instruction},
@@ -44,7 +44,7 @@ mod edge_cases_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -92,19 +92,27 @@ mod edge_cases_tests {
         // Try to place a bet with zero amount
         let zero_bet = 0;
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let zero_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             zero_bet,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut zero_bet_transaction = Transaction::new_with_payer(
-            &[zero_bet_instruction],
+            &[commitment_instruction, zero_bet_instruction],
             Some(&payer.pubkey()),
         );
-        zero_bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        zero_bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         // This should fail because bet amount is zero
         let result = banks_client.process_transaction(zero_bet_transaction).await;
@@ -127,7 +135,7 @@ mod edge_cases_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -175,26 +183,47 @@ mod edge_cases_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
-        // With 0% house edge, the max win should be equal to the bet amount
-        // Try to settle with win amount equal to bet amount
-        let win_amount = bet_amount;
-        let result_hash = [2u8; 32];
+        // Fund the house vault so it can cover payouts above the stake
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut fund_vault_transaction = Transaction::new_with_payer(
+            &[fund_vault_instruction],
+            Some(&payer.pubkey()),
+        );
+        fund_vault_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(fund_vault_transaction).await.unwrap();
+        
+        // With 0% house edge a win pays exactly even odds, double the stake
+        let win_amount = 2 * bet_amount;
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -203,7 +232,7 @@ mod edge_cases_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
@@ -280,7 +309,7 @@ mod edge_cases_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -327,7 +356,7 @@ mod edge_cases_tests {
         
         // Try to settle a game that doesn't exist
         let win_amount = 500_000; // 0.0005 SOL
-        let result_hash = [2u8; 32];
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -336,7 +365,7 @@ mod edge_cases_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
@@ -433,7 +462,7 @@ mod edge_cases_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -489,25 +518,32 @@ mod edge_cases_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
         // Try to settle the game with the wrong player
         let win_amount = 900_000; // 0.0009 SOL
-        let result_hash = [2u8; 32];
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -516,7 +552,7 @@ mod edge_cases_tests {
             &wrong_player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
@@ -547,7 +583,7 @@ mod edge_cases_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player1.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -603,38 +639,53 @@ mod edge_cases_tests {
         // Player1 places a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player1.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player1], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player1], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
         // Player2 tries to place a bet with the same game ID
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let second_bet_instruction = crate::client::place_bet(
             &player2.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut second_bet_transaction = Transaction::new_with_payer(
-            &[second_bet_instruction],
+            &[commitment_instruction, second_bet_instruction],
             Some(&payer.pubkey()),
         );
-        second_bet_transaction.sign(&[&payer, &player2], recent_blockhash);
+        second_bet_transaction.sign(&[&payer, &authority, &player2], recent_blockhash);
         
-        // This should fail because the game ID is already in use
+        // This should fail because the game account is derived for player1
         let result = banks_client.process_transaction(second_bet_transaction).await;
         assert!(result.is_err());
     }
@@ -692,6 +743,7 @@ mod edge_cases_tests {
             None,
             None,
             None,
+            None,
         );
         
         let mut no_params_transaction = Transaction::new_with_payer(
@@ -710,6 +762,7 @@ mod edge_cases_tests {
             None,
             Some(0),
             None,
+            None,
         );
         
         let mut zero_min_bet_transaction = Transaction::new_with_payer(
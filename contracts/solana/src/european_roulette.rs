@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: ISC
+//! Single-zero European roulette with the bet types and payouts of
+//! `contracts/ethereum/RouletteGame.sol`.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::randomness;
+
+/// Number of pockets on the wheel (0-36)
+pub const POCKETS: u32 = 37;
+
+/// Highest number on the wheel
+pub const MAX_NUMBER: u8 = 36;
+
+/// Most bets a single game may carry
+pub const MAX_BETS: usize = 16;
+
+/// Payout odds for a single number (35:1)
+pub const STRAIGHT_UP_PAYOUT: u64 = 35;
+
+/// Payout odds for a column (2:1)
+pub const COLUMN_PAYOUT: u64 = 2;
+
+/// Payout odds for a dozen (2:1)
+pub const DOZEN_PAYOUT: u64 = 2;
+
+/// Payout odds for red/black, even/odd and low/high (1:1)
+pub const EVEN_MONEY_PAYOUT: u64 = 1;
+
+/// Red numbers on a standard roulette wheel
+pub const RED_NUMBERS: [u8; 18] = [
+    1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+];
+
+/// What a roulette bet covers
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouletteBet {
+    /// Single number (0-36)
+    Number(u8),
+    /// Red numbers
+    Red,
+    /// Black numbers
+    Black,
+    /// Even numbers
+    Even,
+    /// Odd numbers
+    Odd,
+    /// Numbers 1-18
+    Low,
+    /// Numbers 19-36
+    High,
+    /// Numbers 1-12
+    Dozen1,
+    /// Numbers 13-24
+    Dozen2,
+    /// Numbers 25-36
+    Dozen3,
+    /// First column (1, 4, 7, ..., 34)
+    Column1,
+    /// Second column (2, 5, 8, ..., 35)
+    Column2,
+    /// Third column (3, 6, 9, ..., 36)
+    Column3,
+}
+
+impl RouletteBet {
+    /// Payout odds of the bet (the stake is returned on top)
+    pub fn payout_odds(self) -> u64 {
+        match self {
+            RouletteBet::Number(_) => STRAIGHT_UP_PAYOUT,
+            RouletteBet::Dozen1 | RouletteBet::Dozen2 | RouletteBet::Dozen3 => DOZEN_PAYOUT,
+            RouletteBet::Column1 | RouletteBet::Column2 | RouletteBet::Column3 => COLUMN_PAYOUT,
+            _ => EVEN_MONEY_PAYOUT,
+        }
+    }
+
+    /// Whether the bet wins when the ball lands on `spin`
+    pub fn covers(self, spin: u8) -> bool {
+        match self {
+            RouletteBet::Number(number) => number == spin,
+            RouletteBet::Red => is_red(spin),
+            RouletteBet::Black => spin != 0 && !is_red(spin),
+            RouletteBet::Even => spin != 0 && spin % 2 != 1,
+            RouletteBet::Odd => spin % 2 == 1,
+            RouletteBet::Low => (1..=18).contains(&spin),
+            RouletteBet::High => (19..=36).contains(&spin),
+            RouletteBet::Dozen1 => (1..=12).contains(&spin),
+            RouletteBet::Dozen2 => (13..=24).contains(&spin),
+            RouletteBet::Dozen3 => (25..=36).contains(&spin),
+            RouletteBet::Column1 => column(spin) == Some(1),
+            RouletteBet::Column2 => column(spin) == Some(2),
+            RouletteBet::Column3 => column(spin) == Some(3),
+        }
+    }
+}
+
+/// A roulette bet together with its stake
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouletteWager {
+    /// What the bet covers
+    pub bet: RouletteBet,
+    /// Stake in lamports (or token base units)
+    pub amount: u64,
+}
+
+/// Whether a number is red
+pub fn is_red(number: u8) -> bool {
+    RED_NUMBERS.contains(&number)
+}
+
+/// Table column (1-3) of a number, or `None` for zero
+pub fn column(number: u8) -> Option<u8> {
+    (number != 0).then(|| (number - 1) % 3 + 1)
+}
+
+/// Pocket the ball lands on for a result hash
+pub fn spin(result_hash: &[u8; 32]) -> u8 {
+    randomness::roll(result_hash, POCKETS) as u8
+}
+
+/// Total stake of a set of bets, or `None` if the set is empty, too large,
+/// contains a zero stake or an out-of-range number, or overflows
+pub fn total_stake(wagers: &[RouletteWager]) -> Option<u64> {
+    if wagers.is_empty() || wagers.len() > MAX_BETS {
+        return None;
+    }
+
+    wagers.iter().try_fold(0u64, |total, wager| {
+        let valid_number = match wager.bet {
+            RouletteBet::Number(number) => number <= MAX_NUMBER,
+            _ => true,
+        };
+        if wager.amount == 0 || !valid_number {
+            return None;
+        }
+        total.checked_add(wager.amount)
+    })
+}
+
+/// Total paid for a set of bets when the ball lands on `spin`, or `None` if
+/// the payout overflows
+pub fn payout(wagers: &[RouletteWager], spin: u8) -> Option<u64> {
+    wagers
+        .iter()
+        .filter(|wager| wager.bet.covers(spin))
+        .try_fold(0u64, |total, wager| {
+            let win = wager.amount.checked_mul(wager.bet.payout_odds() + 1)?;
+            total.checked_add(win)
+        })
+}
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: ISC
+//! Structured events the program logs with `sol_log_data`, mirroring the
+//! events of `contracts/ethereum/CasinoGame.sol` and `RouletteGame.sol`.
+//!
+//! Each event is logged as the Borsh encoding of a `CasinoEvent`, which shows
+//! up in the transaction logs as a `Program data: <base64>` line.
+//! `client::decode_events` turns those lines back into events.
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+use crate::{roulette::RouletteColor, GameType};
+
+/// A casino was initialized
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CasinoInitialized {
+    pub casino: Pubkey,
+    pub authority: Pubkey,
+    pub house_edge: u16,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    /// SPL token mint of the casino, or `None` for a SOL casino
+    pub mint: Option<Pubkey>,
+}
+
+/// A player placed a bet (`BetPlaced` in `RouletteGame.sol`)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BetPlaced {
+    pub casino: Pubkey,
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub game_id: [u8; 32],
+    pub bet_amount: u64,
+    pub game_type: GameType,
+}
+
+/// A game was settled (`GamePlayed` and `GameResultVerified` in
+/// `CasinoGame.sol`)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct GameSettled {
+    pub casino: Pubkey,
+    pub game: Pubkey,
+    pub player: Pubkey,
+    /// The operator who settled the game
+    pub operator: Pubkey,
+    pub game_id: [u8; 32],
+    pub bet_amount: u64,
+    pub is_win: bool,
+    /// Amount paid out; for a coinflip challenge the player lost, to the
+    /// opponent
+    pub win_amount: u64,
+    pub result_hash: [u8; 32],
+}
+
+/// A settled game account was closed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct GameClosed {
+    pub casino: Pubkey,
+    pub game: Pubkey,
+    pub player: Pubkey,
+}
+
+/// A player reclaimed the stake of a game left unsettled, or of a coinflip
+/// challenge that was cancelled. For a crash or roulette round bet, `game` is
+/// the bet account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RefundClaimed {
+    pub casino: Pubkey,
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+/// The casino parameters changed (`HouseEdgeUpdated`, `MinBetUpdated` and
+/// `MaxBetUpdated` in `CasinoGame.sol`); carries the values after the update
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ParamsUpdated {
+    pub casino: Pubkey,
+    pub house_edge: u16,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub settle_timeout: i64,
+}
+
+/// An operator was added, or its roles and cap changed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OperatorAdded {
+    pub casino: Pubkey,
+    pub operator: Pubkey,
+    pub roles: u8,
+    pub daily_payout_cap: u64,
+}
+
+/// An operator was removed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OperatorRemoved {
+    pub casino: Pubkey,
+    pub operator: Pubkey,
+}
+
+/// The house vault was funded (`Deposit` in `CasinoGame.sol`)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VaultFunded {
+    pub casino: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+}
+
+/// The authority withdrew from the house vault (`Withdrawal` in
+/// `CasinoGame.sol`)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VaultWithdrawn {
+    pub casino: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// A new authority was proposed (`Pubkey::default()` cancels the proposal)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AuthorityProposed {
+    pub casino: Pubkey,
+    pub authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// The proposed authority took over the casino
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AuthorityTransferred {
+    pub casino: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Betting or settlement was paused or resumed; carries the flags after the
+/// change
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct PauseUpdated {
+    pub casino: Pubkey,
+    /// The operator who changed the flags
+    pub operator: Pubkey,
+    pub betting_paused: bool,
+    pub settlement_paused: bool,
+}
+
+/// A casino state account was upgraded to a newer layout
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CasinoMigrated {
+    pub casino: Pubkey,
+    pub version: u8,
+}
+
+/// An opponent matched the stake of a coinflip challenge
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ChallengeAccepted {
+    pub casino: Pubkey,
+    pub game: Pubkey,
+    /// The player who opened the challenge
+    pub player: Pubkey,
+    pub opponent: Pubkey,
+    pub stake: u64,
+    /// The slot hash mixed into the flip
+    pub slot_hash: [u8; 32],
+}
+
+/// An operator opened a crash round, committing to its crash point
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CrashRoundOpened {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub operator: Pubkey,
+    pub round_id: [u8; 32],
+    pub server_seed_hash: [u8; 32],
+    /// House edge the crash point is computed with
+    pub house_edge: u16,
+    /// Unix timestamp betting closes and the multiplier starts climbing at
+    pub starts_at: i64,
+}
+
+/// A player joined a crash round
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CrashBetPlaced {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    /// The player's bet account
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    pub round_id: [u8; 32],
+    pub bet_amount: u64,
+    /// Multiplier the bet cashes out at automatically, in basis points
+    pub auto_cashout: Option<u64>,
+}
+
+/// A player cashed out of a running crash round
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CashedOut {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    /// In basis points
+    pub multiplier: u64,
+    /// Unix timestamp of the cash-out; it isn't paid unless this is before the crash
+    pub time: i64,
+}
+
+/// The server seed of a crash round was revealed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CrashRoundSettled {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    /// The operator who revealed the seed
+    pub operator: Pubkey,
+    /// Multiplier the round crashed at, in basis points
+    pub crash_point: u64,
+    pub result_hash: [u8; 32],
+}
+
+/// A crash bet was settled and its account closed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CrashBetSettled {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    /// Who settled the bet: an operator, or anyone once the seed was revealed
+    pub operator: Pubkey,
+    pub bet_amount: u64,
+    /// Multiplier the bet cashed out at, if it did, in basis points
+    pub cashout: Option<u64>,
+    /// Amount paid out (0 if the round crashed first)
+    pub win_amount: u64,
+}
+
+/// An operator opened a shared roulette round, committing to its spin
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundOpened {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub operator: Pubkey,
+    pub round_id: [u8; 32],
+    pub server_seed_hash: [u8; 32],
+    /// Unix timestamp betting closes at unless the round is locked earlier
+    pub betting_ends_at: i64,
+}
+
+/// A player bet on a colour in a roulette round
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundBetPlaced {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    /// The player's bet account, shared by all their bets in the round
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    pub round_id: [u8; 32],
+    pub color: RouletteColor,
+    /// Amount added to the stake on the colour
+    pub amount: u64,
+}
+
+/// Betting on a roulette round closed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundLocked {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub operator: Pubkey,
+    /// Number of players with bets in the round
+    pub open_bets: u32,
+}
+
+/// The server seed of a roulette round was revealed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundSpun {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    /// The operator who revealed the seed
+    pub operator: Pubkey,
+    pub winning_slot: u8,
+    pub result_hash: [u8; 32],
+}
+
+/// A player's bets in a roulette round were settled and their account closed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundBetSettled {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    /// Who settled the bets, not necessarily an operator
+    pub operator: Pubkey,
+    /// Stakes on all colours together
+    pub bet_amount: u64,
+    /// Amount paid out (0 if no colour bet on won)
+    pub win_amount: u64,
+}
+
+/// An operator committed to a server seed for a bet to be placed against
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CommitmentPosted {
+    pub casino: Pubkey,
+    pub commitment: Pubkey,
+    pub operator: Pubkey,
+    pub server_seed_hash: [u8; 32],
+}
+
+/// A crash or roulette round with no open bets was closed, its rent returned
+/// to the operator who opened it
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundClosed {
+    pub casino: Pubkey,
+    pub round: Pubkey,
+    pub operator: Pubkey,
+}
+
+/// Every event the program logs. New events are only ever appended so older
+/// logs keep decoding.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum CasinoEvent {
+    CasinoInitialized(CasinoInitialized),
+    BetPlaced(BetPlaced),
+    GameSettled(GameSettled),
+    GameClosed(GameClosed),
+    RefundClaimed(RefundClaimed),
+    ParamsUpdated(ParamsUpdated),
+    OperatorAdded(OperatorAdded),
+    OperatorRemoved(OperatorRemoved),
+    VaultFunded(VaultFunded),
+    VaultWithdrawn(VaultWithdrawn),
+    AuthorityProposed(AuthorityProposed),
+    AuthorityTransferred(AuthorityTransferred),
+    PauseUpdated(PauseUpdated),
+    CasinoMigrated(CasinoMigrated),
+    ChallengeAccepted(ChallengeAccepted),
+    CrashRoundOpened(CrashRoundOpened),
+    CrashBetPlaced(CrashBetPlaced),
+    CashedOut(CashedOut),
+    CrashRoundSettled(CrashRoundSettled),
+    CrashBetSettled(CrashBetSettled),
+    RoundOpened(RoundOpened),
+    RoundBetPlaced(RoundBetPlaced),
+    RoundLocked(RoundLocked),
+    RoundSpun(RoundSpun),
+    RoundBetSettled(RoundBetSettled),
+    CommitmentPosted(CommitmentPosted),
+    RoundClosed(RoundClosed),
+}
+
+impl CasinoEvent {
+    /// Logs the event with `sol_log_data`
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+}
@@ -2,18 +2,26 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint,
     entrypoint::ProgramResult,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
-    sysvar::Sysvar,
-    program::invoke_signed,
+    sysvar::{slot_hashes, Sysvar},
+    program::{invoke, invoke_signed},
+    program_pack::Pack,
     system_instruction,
+    system_program,
 };
 use thiserror::Error;
 
+use coinflip::CoinSide;
+use european_roulette::RouletteWager;
+use events::CasinoEvent;
+use roulette::RouletteColor;
+
 // Program entry point
 entrypoint!(process_instruction);
 
@@ -26,6 +34,27 @@ pub mod id {
 // Client module for interacting with the program
 pub mod client;
 
+// Commit-reveal randomness helpers
+pub mod randomness;
+
+// Red/black/green roulette wheel
+pub mod roulette;
+
+// Single-zero European roulette
+pub mod european_roulette;
+
+// Roll-under dice
+pub mod dice;
+
+// Coinflip against the house or another player
+pub mod coinflip;
+
+// Crash rounds with on-chain cash-outs
+pub mod crash;
+
+// Structured program events
+pub mod events;
+
 // Test modules
 #[cfg(test)]
 mod test;
@@ -35,6 +64,12 @@ mod security_test;
 mod edge_cases_test;
 #[cfg(test)]
 mod tokio_tests;
+#[cfg(test)]
+mod token_test;
+#[cfg(test)]
+mod compute_units_test;
+#[cfg(test)]
+mod test_utils;
 
 // Error types
 #[derive(Error, Debug, Copy, Clone)]
@@ -68,6 +103,75 @@ pub enum CasinoError {
     
     #[error("Invalid house edge")]
     InvalidHouseEdge,
+    
+    #[error("Server seed does not match the committed hash")]
+    InvalidServerSeed,
+    
+    #[error("Claimed outcome does not match the derived result")]
+    OutcomeMismatch,
+    
+    #[error("Invalid roulette bet")]
+    InvalidRouletteBet,
+    
+    #[error("Game not settled")]
+    GameNotSettled,
+    
+    #[error("Settlement timeout has not elapsed")]
+    SettleTimeoutNotReached,
+    
+    #[error("Operator daily payout cap exceeded")]
+    PayoutCapExceeded,
+    
+    #[error("Casino is paused")]
+    Paused,
+    
+    #[error("Unsupported account version")]
+    UnsupportedAccountVersion,
+    
+    #[error("Dice target must be between 1 and 98")]
+    InvalidDiceTarget,
+    
+    #[error("Coinflip challenge is not open")]
+    ChallengeNotOpen,
+    
+    #[error("Coinflip challenge has not been accepted")]
+    ChallengeNotAccepted,
+    
+    #[error("Cash-out multiplier must be above 1.00x and at most 100.00x")]
+    InvalidCashout,
+    
+    #[error("Betting on the round has closed")]
+    BettingClosed,
+    
+    #[error("Round has not started yet")]
+    RoundNotStarted,
+    
+    #[error("Round has not crashed yet")]
+    RoundNotOver,
+    
+    #[error("Round has not reached the cash-out multiplier")]
+    CashoutNotReached,
+    
+    #[error("Bet has already cashed out")]
+    AlreadyCashedOut,
+    
+    #[error("Round is still taking bets")]
+    RoundNotLocked,
+    
+    #[error("Round has not been spun yet")]
+    RoundNotSpun,
+    
+    #[error("Batch results do not match the game accounts passed")]
+    InvalidBatch,
+    
+    #[error("No operator has committed to the server seed hash")]
+    CommitmentNotPosted,
+    
+    #[error("Round still has bets to settle or refund")]
+    RoundHasOpenBets,
+    
+    #[error("Bet would not pay back more than its stake if it won")]
+    InvalidBetParameters,
 }
 
 impl From<CasinoError> for ProgramError {
@@ -84,52 +188,111 @@ pub enum CasinoInstruction {
     /// Accounts expected:
     /// 0. `[signer, writable]` The authority account (casino owner)
     /// 1. `[writable]` The casino state account
-    /// 2. `[]` System program
+    /// 2. `[writable]` The vault account
+    /// 3. `[writable]` The authority's operator account
+    /// 4. `[]` System program
+    /// 
+    /// Optional, to run the casino on an SPL token instead of lamports:
+    /// 5. `[]` The token mint
+    /// 6. `[writable]` The vault's associated token account
+    /// 7. `[]` SPL token program
+    /// 8. `[]` Associated token account program
     Initialize {
         /// House edge in basis points (e.g., 250 = 2.5%)
         house_edge: u16,
-        /// Minimum bet amount in lamports
+        /// Minimum bet amount in lamports (or token base units)
         min_bet: u64,
-        /// Maximum bet amount in lamports
+        /// Maximum bet amount in lamports (or token base units)
         max_bet: u64,
     },
     
-    /// Place a bet
+    /// Place a bet against a commitment posted with `PostCommitment`, which
+    /// the bet consumes
     /// 
     /// Accounts expected:
     /// 0. `[signer, writable]` The player account
-    /// 1. `[]` The casino state account
-    /// 2. `[writable]` The game account
-    /// 3. `[]` System program
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The game account, derived from `["game", casino, player, game_id]`
+    /// 3. `[writable]` The vault account
+    /// 4. `[]` System program
+    /// 5. `[writable]` The commitment account for `server_seed_hash`, closed into the vault
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The player's token account
+    /// 7. `[writable]` The vault's associated token account
+    /// 8. `[]` SPL token program
     PlaceBet {
         /// Unique game ID
         game_id: [u8; 32],
-        /// Bet amount in lamports
+        /// Bet amount in lamports (or token base units)
         bet_amount: u64,
+        /// Seed chosen by the player, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
     },
     
     /// Settle a game
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority account (casino owner/operator)
-    /// 1. `[]` The casino state account
-    /// 2. `[writable]` The game account
-    /// 3. `[writable]` The player account
-    /// 4. `[]` System program
+    /// 0. `[signer]` The operator (needs the settle role)
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The signer's operator account
+    /// 3. `[writable]` The game account
+    /// 4. `[writable]` The player account, or the opponent's if it won a coinflip challenge
+    /// 5. `[writable]` The vault account
+    /// 6. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 7. `[writable]` The paid account's token account
+    /// 8. `[writable]` The vault's associated token account
+    /// 9. `[]` SPL token program
     SettleGame {
-        /// Whether the player won
+        /// Whether the player won (for a coinflip challenge, the player who opened it)
         is_win: bool,
-        /// Win amount in lamports (0 if loss)
+        /// Win amount in lamports or token base units (0 if loss); for a
+        /// coinflip challenge, the pot paid to whichever side won
         win_amount: u64,
-        /// Result hash for verification
-        result_hash: [u8; 32],
+        /// The server seed committed to when the bet was placed
+        server_seed: [u8; 32],
+    },
+    
+    /// Place a bet on a colour of the 15-slot roulette wheel
+    /// 
+    /// Accounts expected: same as `PlaceBet`
+    PlaceRouletteBet {
+        /// Unique game ID
+        game_id: [u8; 32],
+        /// Bet amount in lamports (or token base units)
+        bet_amount: u64,
+        /// Colour the bet is placed on
+        color: RouletteColor,
+        /// Seed chosen by the player, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+    },
+    
+    /// Place one or more European roulette bets settled by a single spin
+    /// 
+    /// Accounts expected: same as `PlaceBet`
+    PlaceRouletteBets {
+        /// Unique game ID
+        game_id: [u8; 32],
+        /// The bets and their stakes; the total stake is escrowed
+        bets: Vec<RouletteWager>,
+        /// Seed chosen by the player, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
     },
     
     /// Update casino parameters
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority account (casino owner)
+    /// 0. `[signer]` The operator (needs the params role)
     /// 1. `[writable]` The casino state account
+    /// 2. `[]` The signer's operator account
     UpdateParams {
         /// New house edge in basis points
         house_edge: Option<u16>,
@@ -137,28 +300,601 @@ pub enum CasinoInstruction {
         min_bet: Option<u64>,
         /// New maximum bet amount in lamports
         max_bet: Option<u64>,
+        /// New settlement timeout in seconds, from `MIN_SETTLE_TIMEOUT` to
+        /// `MAX_SETTLE_TIMEOUT`; games and rounds already open keep theirs
+        settle_timeout: Option<i64>,
+    },
+    
+    /// Add an operator, or update the roles and payout cap of an existing one
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The authority account (casino owner)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The operator key
+    /// 3. `[writable]` The operator account
+    /// 4. `[]` System program
+    AddOperator {
+        /// Bitmask of `ROLE_*` flags granted to the operator
+        roles: u8,
+        /// Most the operator may pay out in wins per UTC day
+        daily_payout_cap: u64,
+    },
+    
+    /// Remove an operator, closing its operator account and returning the
+    /// rent to the authority
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The authority account (casino owner)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The operator key
+    /// 3. `[writable]` The operator account
+    RemoveOperator,
+    
+    /// Deposit lamports into the house vault
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The funding account
+    /// 1. `[]` The casino state account
+    /// 2. `[writable]` The vault account
+    /// 3. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 4. `[writable]` The funder's token account
+    /// 5. `[writable]` The vault's associated token account
+    /// 6. `[]` SPL token program
+    FundVault {
+        /// Amount to deposit in lamports (or token base units)
+        amount: u64,
     },
     
-    /// Add an operator
+    /// Withdraw lamports from the house vault. The stakes of bets not yet
+    /// settled or refunded stay in it.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The authority account (casino owner)
+    /// 1. `[]` The casino state account
+    /// 2. `[writable]` The vault account
+    /// 3. `[writable]` The recipient account (a token account for SPL token casinos)
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 4. `[writable]` The vault's associated token account
+    /// 5. `[]` SPL token program
+    WithdrawFromVault {
+        /// Amount to withdraw in lamports (or token base units)
+        amount: u64,
+    },
+    
+    /// Close a settled game account, refunding its rent to the player and
+    /// sweeping any other lamports into the house vault
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the settle role)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The game account
+    /// 4. `[writable]` The player account
+    /// 5. `[writable]` The vault account
+    CloseGame,
+    
+    /// Refund a game the operators failed to settle within the casino's
+    /// settlement timeout and close the game account. A bet against the house
+    /// is paid the most it could have won, as far as the vault holds more than
+    /// the stakes of open bets; a coinflip challenge returns both stakes.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The player account
     /// 1. `[writable]` The casino state account
-    /// 2. `[]` The operator account to add
-    AddOperator,
+    /// 2. `[writable]` The game account
+    /// 3. `[writable]` The vault account
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 4. `[writable]` The player's token account
+    /// 5. `[writable]` The vault's associated token account
+    /// 6. `[]` SPL token program
+    /// 
+    /// Additionally, for accepted coinflip challenges, whose opponent is
+    /// refunded too (following the token accounts in SPL token casinos):
+    /// 7. `[writable]` The opponent account
+    /// 8. `[writable]` The opponent's token account, in SPL token casinos
+    ClaimRefund,
     
-    /// Remove an operator
+    /// Propose a new casino authority, which takes over once it accepts
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The authority account (casino owner)
     /// 1. `[writable]` The casino state account
-    /// 2. `[]` The operator account to remove
-    RemoveOperator,
+    ProposeAuthority {
+        /// The proposed authority, or `Pubkey::default()` to cancel a pending
+        /// transfer; never the current authority
+        new_authority: Pubkey,
+    },
+    
+    /// Accept a pending authority transfer. The new authority gets an operator
+    /// account with every role and the previous authority's is closed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The pending authority
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The pending authority's operator account
+    /// 3. `[writable]` The previous authority
+    /// 4. `[writable]` The previous authority's operator account
+    /// 5. `[]` System program
+    AcceptAuthority,
+    
+    /// Pause betting and/or settlement
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the pause role)
+    /// 1. `[writable]` The casino state account
+    /// 2. `[]` The signer's operator account
+    Pause {
+        /// Stop new bets from being placed
+        betting: bool,
+        /// Stop games from being settled
+        settlement: bool,
+    },
+    
+    /// Resume betting and/or settlement
+    /// 
+    /// Accounts expected: same as `Pause`
+    Unpause {
+        /// Allow new bets again
+        betting: bool,
+        /// Allow games to be settled again
+        settlement: bool,
+    },
+    
+    /// Upgrade a casino state account created by an older program version to
+    /// the current layout, resizing it and creating the accounts the newer
+    /// layout relies on. Does nothing if the account is already current.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The authority account (casino owner)
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The vault account
+    /// 3. `[writable]` The authority's operator account
+    /// 4. `[]` System program
+    /// 5. `[writable]` Onwards: an operator account for every other operator
+    ///    in the version 1 `operators` list, in order
+    MigrateCasinoState,
+    
+    /// Place a roll-under dice bet, paid at the odds of its target less the
+    /// house edge. The target must be one whose win pays more than the stake
+    /// at the casino's house edge.
+    /// 
+    /// Accounts expected: same as `PlaceBet`
+    PlaceDiceBet {
+        /// Unique game ID
+        game_id: [u8; 32],
+        /// Bet amount in lamports (or token base units)
+        bet_amount: u64,
+        /// The bet wins if the roll (0.00-99.99) is below this target (1-98)
+        target: u8,
+        /// Seed chosen by the player, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+    },
+    
+    /// Bet on a coinflip against the house vault, paid twice the stake less
+    /// the house edge
+    /// 
+    /// Accounts expected: same as `PlaceBet`
+    PlaceCoinflipBet {
+        /// Unique game ID
+        game_id: [u8; 32],
+        /// Bet amount in lamports (or token base units)
+        bet_amount: u64,
+        /// Side the bet is placed on
+        side: CoinSide,
+        /// Seed chosen by the player, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+    },
+    
+    /// Open a coinflip challenge another player can accept by matching the
+    /// stake. The winner takes both stakes less the house edge.
+    /// 
+    /// Accounts expected: same as `PlaceBet`
+    OpenCoinflipChallenge {
+        /// Unique game ID
+        game_id: [u8; 32],
+        /// Stake in lamports (or token base units) the opponent must match
+        stake: u64,
+        /// Side the challenger calls; the opponent takes the other
+        side: CoinSide,
+        /// Seed chosen by the challenger, mixed into the result
+        client_seed: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+    },
+    
+    /// Accept an open coinflip challenge, escrowing a matching stake. The
+    /// settlement timeout runs from the moment it is accepted.
+    /// 
+    /// The opponent adds no seed of their own: they act last and could pick
+    /// one that wins against a server seed leaked to them. The flip mixes the
+    /// hash of the latest slot into the challenger's seed instead.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The opponent account
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The game account of the challenge
+    /// 3. `[writable]` The vault account
+    /// 4. `[]` System program
+    /// 5. `[]` SlotHashes sysvar
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The opponent's token account
+    /// 7. `[writable]` The vault's associated token account
+    /// 8. `[]` SPL token program
+    AcceptCoinflipChallenge,
+    
+    /// Cancel a coinflip challenge nobody has accepted yet, refunding the
+    /// stake and closing the game account
+    /// 
+    /// Accounts expected: same as `ClaimRefund`
+    CancelCoinflipChallenge,
+    
+    /// Open a crash round, committing to its crash point before any bet is
+    /// placed. Betting closes and the multiplier starts climbing at `starts_at`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The operator (needs the settle role)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The round account, derived from `["crash_round", casino, round_id]`
+    /// 4. `[]` System program
+    OpenCrashRound {
+        /// Unique round ID
+        round_id: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+        /// Unix timestamp betting closes at, in the future
+        starts_at: i64,
+    },
+    
+    /// Join a crash round before betting closes, escrowing the bet
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The player account
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The round account
+    /// 3. `[writable]` The bet account, derived from `["crash_bet", round, player]`
+    /// 4. `[writable]` The vault account
+    /// 5. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The player's token account
+    /// 7. `[writable]` The vault's associated token account
+    /// 8. `[]` SPL token program
+    JoinCrashRound {
+        /// Bet amount in lamports (or token base units)
+        bet_amount: u64,
+        /// Multiplier to cash out at automatically, in basis points
+        auto_cashout: Option<u64>,
+    },
+    
+    /// Cash out of a running crash round. The bet wins if the round crashes
+    /// above the multiplier it cashed out at, after the cash-out; cashing out
+    /// once the round has crashed counts for nothing.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The player account
+    /// 1. `[]` The round account
+    /// 2. `[writable]` The bet account
+    CashOut {
+        /// Multiplier to cash out at, in basis points; the round must have
+        /// reached it
+        multiplier: u64,
+    },
+    
+    /// Reveal the server seed of a crash round once it has crashed and settle
+    /// bets: those that cashed out below the crash point are paid, and each
+    /// bet account is closed with its rent going back to the player. Bets
+    /// that don't fit are settled by sending it again with the same seed,
+    /// which anyone may do once the seed is revealed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the settle role), or anyone once the
+    ///    round is settled
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The signer's operator account, or once the round is
+    ///    settled that of the operator who opened it, whose payout cap the
+    ///    payouts count against
+    /// 3. `[writable]` The round account
+    /// 4. `[writable]` The vault account
+    /// 5. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The vault's associated token account
+    /// 7. `[]` SPL token program
+    /// 
+    /// Followed by, for every bet settled:
+    /// - `[writable]` The bet account
+    /// - `[writable]` The player account
+    /// - `[writable]` The player's token account, in SPL token casinos
+    SettleCrashRound {
+        /// The server seed committed to when the round was opened
+        server_seed: [u8; 32],
+    },
+    
+    /// Reclaim a crash bet whose round was not settled within the casino's
+    /// settlement timeout of starting, closing the bet account. The bet is paid
+    /// at least its stake, and its cash-out multiplier as if the round never
+    /// crashed, as far as the vault holds more than the stakes of open bets.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The player account
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The round account
+    /// 3. `[writable]` The bet account
+    /// 4. `[writable]` The vault account
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 5. `[writable]` The player's token account
+    /// 6. `[writable]` The vault's associated token account
+    /// 7. `[]` SPL token program
+    ClaimCrashRefund,
+    
+    /// Open a shared 15-slot roulette round, committing to its spin before any
+    /// bet is placed. Betting closes at `betting_ends_at`, or earlier if the
+    /// round is locked.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The operator (needs the settle role)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The round account, derived from `["round", casino, round_id]`
+    /// 4. `[]` System program
+    OpenRound {
+        /// Unique round ID
+        round_id: [u8; 32],
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+        /// Unix timestamp betting closes at, in the future
+        betting_ends_at: i64,
+    },
+    
+    /// Bet on a colour in a roulette round while it takes bets, escrowing the
+    /// stake. A player's bets in a round share one bet account; betting again
+    /// adds to the stake on the colour.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The player account
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The round account
+    /// 3. `[writable]` The bet account, derived from `["round_bet", round, player]`
+    /// 4. `[writable]` The vault account
+    /// 5. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The player's token account
+    /// 7. `[writable]` The vault's associated token account
+    /// 8. `[]` SPL token program
+    PlaceRoundBet {
+        /// Colour bet on
+        color: RouletteColor,
+        /// Amount added to the stake, in lamports (or token base units)
+        amount: u64,
+    },
+    
+    /// Close betting on a roulette round so it can be spun
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the settle role)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The round account
+    LockRound,
+    
+    /// Reveal the server seed of a roulette round once betting has closed,
+    /// whether it was locked or `betting_ends_at` has passed, which decides the
+    /// winning slot for every bet in it
+    /// 
+    /// Accounts expected: same as `LockRound`
+    SpinRound {
+        /// The server seed committed to when the round was opened
+        server_seed: [u8; 32],
+    },
+    
+    /// Settle bets of a spun roulette round: winning colours are paid, and
+    /// each bet account is closed with its rent going back to the player.
+    /// Bets that don't fit are settled by sending it again. Anyone may settle
+    /// a spun round, since its payouts are fixed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Any account
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The operator account of the operator who opened the
+    ///    round, whose payout cap the payouts count against
+    /// 3. `[writable]` The round account
+    /// 4. `[writable]` The vault account
+    /// 5. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 6. `[writable]` The vault's associated token account
+    /// 7. `[]` SPL token program
+    /// 
+    /// Followed by, for every bet settled:
+    /// - `[writable]` The bet account
+    /// - `[writable]` The player account
+    /// - `[writable]` The player's token account, in SPL token casinos
+    SettleRoundBets,
+    
+    /// Reclaim a roulette round bet whose round was not spun within the
+    /// casino's settlement timeout of betting closing, closing the bet
+    /// account. The bet is paid as if the colour paying it most had won, as
+    /// far as the vault holds more than the stakes of open bets.
+    /// 
+    /// Accounts expected: same as `ClaimCrashRefund`
+    ClaimRoundRefund,
+    
+    /// Settle many games at once, each checked as `SettleGame` checks it.
+    /// `results[i]` settles the i-th pair of game accounts; if any game fails
+    /// nothing is settled, and the failing game is logged with its index.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the settle role)
+    /// 1. `[writable]` The casino state account
+    /// 2. `[writable]` The signer's operator account
+    /// 3. `[writable]` The vault account
+    /// 4. `[]` System program
+    /// 
+    /// Additionally, for SPL token casinos:
+    /// 5. `[writable]` The vault's associated token account
+    /// 6. `[]` SPL token program
+    /// 
+    /// Followed by, for every result:
+    /// - `[writable]` The game account
+    /// - `[writable]` The player account, or the opponent's if it won a coinflip challenge
+    /// - `[writable]` The paid account's token account, in SPL token casinos
+    SettleBatch {
+        /// Outcome claimed for each game, in the order of the game accounts
+        results: Vec<SettleResult>,
+    },
+    
+    /// Commit to a server seed for a player to bet against. A bet names the
+    /// commitment by its hash and closes it, so every bet is placed against a
+    /// seed an operator chose and has not revealed yet.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The operator (needs the settle role), paying the commitment's rent
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The commitment account, derived from `["commitment", casino, server_seed_hash]`
+    /// 4. `[]` System program
+    PostCommitment {
+        /// The operator's commitment, `sha256(server_seed)`
+        server_seed_hash: [u8; 32],
+    },
+    
+    /// Close a crash round with no open bets, returning its rent to the
+    /// operator who opened it. The round must be settled, or its bets all
+    /// refunded after the settlement timeout.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The operator (needs the settle role)
+    /// 1. `[]` The casino state account
+    /// 2. `[]` The signer's operator account
+    /// 3. `[writable]` The round account
+    /// 4. `[writable]` The operator who opened the round
+    CloseCrashRound,
+    
+    /// Close a roulette round with no open bets, returning its rent to the
+    /// operator who opened it. The round must be spun, or its bets all
+    /// refunded after the settlement timeout.
+    /// 
+    /// Accounts expected: same as `CloseCrashRound`
+    CloseRound,
+    
+    /// Reclaim the stake of a game placed with a version 1 program and never
+    /// settled, closing its game account. Version 1 games committed to no
+    /// server seed, so they can no longer be settled; the player gets back
+    /// the stake escrowed in the account along with its rent.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The player account
+    /// 1. `[writable]` The game account, derived from `[game_id]`
+    ClaimLegacyRefund {
+        /// Game ID the version 1 game account was derived from
+        game_id: [u8; 32],
+    },
 }
 
-// Casino state
+/// Outcome claimed for one game of a `SettleBatch`, as `SettleGame` takes it
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct SettleResult {
+    /// Whether the player won (for a coinflip challenge, the player who opened it)
+    pub is_win: bool,
+    /// Win amount in lamports or token base units (0 if loss); for a
+    /// coinflip challenge, the pot paid to whichever side won
+    pub win_amount: u64,
+    /// The server seed committed to when the bet was placed
+    pub server_seed: [u8; 32],
+}
+
+/// Settlement timeout of a new casino: a day
+pub const DEFAULT_SETTLE_TIMEOUT: i64 = 24 * 60 * 60;
+
+/// Shortest settlement timeout a casino may set: the longest a crash round
+/// runs, so no refund opens before it can be settled, plus ten minutes to
+/// settle it
+pub const MIN_SETTLE_TIMEOUT: i64 = crash::MAX_DURATION + 10 * 60;
+
+/// Longest settlement timeout a casino may set: thirty days
+pub const MAX_SETTLE_TIMEOUT: i64 = 30 * 24 * 60 * 60;
+
+// Casino state, version 2
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct CasinoState {
+    /// The owner of the casino
+    pub authority: Pubkey,
+    /// House edge in basis points (e.g., 250 = 2.5%)
+    pub house_edge: u16,
+    /// Minimum bet amount in lamports (or token base units)
+    pub min_bet: u64,
+    /// Maximum bet amount in lamports (or token base units)
+    pub max_bet: u64,
+    /// SPL token mint bets are placed in, or `None` for a SOL casino
+    pub mint: Option<Pubkey>,
+    /// Seconds after which a player may reclaim the stake of an unsettled
+    /// game, fixed into each game and round when it is created
+    pub settle_timeout: i64,
+    /// The key that initialized the casino; the casino address is derived from
+    /// it, so it stays the same when the authority changes
+    pub creator: Pubkey,
+    /// Authority proposed by `ProposeAuthority` (`Pubkey::default()` when no
+    /// transfer is pending)
+    pub pending_authority: Pubkey,
+    /// Whether placing bets is paused
+    pub betting_paused: bool,
+    /// Whether settling games is paused
+    pub settlement_paused: bool,
+    /// Stakes escrowed in the vault for bets not yet settled or refunded,
+    /// which withdrawals must leave in place
+    pub liabilities: u64,
+    /// Bump seed of the casino state PDA
+    pub bump: u8,
+}
+
+impl CasinoState {
+    /// Decodes the data of a casino state account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+    
+    /// Records stakes escrowed in the vault for a new bet
+    pub fn escrow(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.liabilities = self.liabilities
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    
+    /// Releases escrowed stakes once their bet is settled or refunded
+    pub fn release(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.liabilities = self.liabilities
+            .checked_sub(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }    
+    /// What a refund after the settlement timeout pays for a bet of `stake`:
+    /// the most it could have won, `max_payout`, so that leaving a bet the
+    /// house loses unsettled gains the operators nothing. It is limited to the
+    /// stake plus what `vault_balance` holds beyond the stakes of open bets.
+    pub fn timeout_refund(&self, stake: u64, max_payout: u64, vault_balance: u64) -> u64 {
+        let bankroll = vault_balance.saturating_sub(self.liabilities);
+        max_payout.max(stake).min(stake.saturating_add(bankroll))
+    }
+}
+
+/// Casino state layout of version 1 programs, stored without a discriminator
+/// or version byte. `MigrateCasinoState` upgrades it to `CasinoState`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CasinoStateV1 {
     /// The owner of the casino
     pub authority: Pubkey,
     /// House edge in basis points (e.g., 250 = 2.5%)
@@ -171,12 +907,127 @@ pub struct CasinoState {
     pub operators: Vec<Pubkey>,
 }
 
-// Game state
+/// Operator role: settle and close games
+pub const ROLE_SETTLE: u8 = 1 << 0;
+/// Operator role: pause and unpause the casino (the guardian role)
+pub const ROLE_PAUSE: u8 = 1 << 1;
+/// Operator role: update casino parameters
+pub const ROLE_PARAMS: u8 = 1 << 2;
+/// Every operator role, held by the casino authority
+pub const ROLE_ALL: u8 = ROLE_SETTLE | ROLE_PAUSE | ROLE_PARAMS;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Operator state, one PDA per casino and operator key
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct GameState {
-    /// The player who placed the bet
-    pub player: Pubkey,
-    /// Bet amount in lamports
+pub struct OperatorState {
+    /// The casino the operator works for
+    pub casino: Pubkey,
+    /// The operator's signing key
+    pub operator: Pubkey,
+    /// Bitmask of `ROLE_*` flags
+    pub roles: u8,
+    /// Most the operator may pay out in wins per UTC day
+    pub daily_payout_cap: u64,
+    /// Wins paid out by the operator on `payout_day`
+    pub paid_today: u64,
+    /// Day (unix timestamp / 86400) `paid_today` counts towards
+    pub payout_day: i64,
+}
+
+impl OperatorState {
+    /// Decodes the data of an operator account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+    
+    /// Records a payout against the daily cap, restarting the count on a new day
+    pub fn record_payout(&mut self, amount: u64, unix_timestamp: i64) -> Result<(), CasinoError> {
+        let day = unix_timestamp.div_euclid(SECONDS_PER_DAY);
+        if day != self.payout_day {
+            self.payout_day = day;
+            self.paid_today = 0;
+        }
+        
+        let paid_today = self.paid_today
+            .checked_add(amount)
+            .filter(|paid| *paid <= self.daily_payout_cap)
+            .ok_or(CasinoError::PayoutCapExceeded)?;
+        self.paid_today = paid_today;
+        
+        Ok(())
+    }
+}
+
+// Kind of game a bet was placed on
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum GameType {
+    /// Even-odds win/loss bet paying double the stake less the house edge
+    Standard,
+    /// 15-slot roulette bet on a colour, paid at the wheel's fixed multiplier
+    Roulette { color: RouletteColor },
+    /// European roulette bets paid at the odds of `RouletteGame.sol`
+    EuropeanRoulette { bets: Vec<RouletteWager> },
+    /// Roll-under dice bet paid at the target's odds less the house edge
+    Dice { target: u8 },
+    /// Coinflip against the house vault
+    Coinflip { side: CoinSide },
+    /// Coinflip between the player, who called `side`, and an opponent, who
+    /// is `Pubkey::default()` until the challenge is accepted
+    CoinflipChallenge { side: CoinSide, opponent: Pubkey },
+}
+
+// Game state, version 2
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct GameState {
+    /// The player who placed the bet
+    pub player: Pubkey,
+    /// Bet amount in lamports (or token base units)
+    pub bet_amount: u64,
+    /// Whether the game has been settled
+    pub is_settled: bool,
+    /// Whether the player won (only valid if is_settled is true)
+    pub is_win: bool,
+    /// Amount paid out in lamports or token base units: the player's win, or
+    /// the pot of a coinflip challenge whichever side won it
+    pub win_amount: u64,
+    /// Result hash derived from the seeds (only valid if is_settled is true)
+    pub result_hash: [u8; 32],
+    /// The operator's commitment, `sha256(server_seed)`
+    pub server_seed_hash: [u8; 32],
+    /// Seed chosen by the player, combined with the latest slot hash once a
+    /// coinflip challenge is accepted
+    pub client_seed: [u8; 32],
+    /// The revealed server seed (only valid if is_settled is true)
+    pub server_seed: [u8; 32],
+    /// The game the bet was placed on
+    pub game_type: GameType,
+    /// House edge in basis points the payout is computed with, fixed when
+    /// the bet is placed
+    pub house_edge: u16,
+    /// Unix timestamp of when the bet was placed, or a coinflip challenge
+    /// accepted
+    pub created_at: i64,
+    /// Unix timestamp from which the stake may be refunded if the game is
+    /// still unsettled: `created_at` plus the casino's settlement timeout
+    pub refundable_at: i64,
+    /// The casino the bet was placed in
+    pub casino: Pubkey,
+    /// Game ID the account address is derived from
+    pub game_id: [u8; 32],
+    /// Bump seed of the game PDA
+    pub bump: u8,
+}
+
+/// Game state layout of version 1 programs, stored without a discriminator
+/// or version byte at the address derived from `[game_id]`, with the stake
+/// escrowed in the game account. `ClaimLegacyRefund` returns unsettled ones.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct GameStateV1 {
+    /// The player who placed the bet
+    pub player: Pubkey,
+    /// Bet amount in lamports
     pub bet_amount: u64,
     /// Whether the game has been settled
     pub is_settled: bool,
@@ -188,6 +1039,361 @@ pub struct GameState {
     pub result_hash: [u8; 32],
 }
 
+impl GameState {
+    /// Decodes the data of a game account, checking its discriminator and
+    /// layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+    
+    /// The opponent who accepted a coinflip challenge, if any
+    pub fn opponent(&self) -> Option<Pubkey> {
+        match self.game_type {
+            GameType::CoinflipChallenge { opponent, .. } if opponent != Pubkey::default() => Some(opponent),
+            _ => None,
+        }
+    }
+    
+    /// The account a settlement claiming `is_win` pays: the player, or the
+    /// opponent who won a coinflip challenge
+    pub fn payee(&self, is_win: bool) -> Pubkey {
+        match self.opponent() {
+            Some(opponent) if !is_win => opponent,
+            _ => self.player,
+        }
+    }
+    
+    /// Stakes escrowed in the vault for the game: the player's, and the
+    /// opponent's once a coinflip challenge is accepted
+    pub fn escrowed(&self) -> Option<u64> {
+        self.bet_amount.checked_mul(if self.opponent().is_some() { 2 } else { 1 })
+    }    
+    /// The most a settlement could pay, whatever the result: a win, or the
+    /// best spin for the bets of a European roulette game. `None` if it
+    /// overflows.
+    pub fn max_payout(&self) -> Option<u64> {
+        match &self.game_type {
+            GameType::Standard => randomness::win_payout(self.bet_amount, self.house_edge),
+            GameType::Roulette { color } => self.bet_amount.checked_mul(color.multiplier()),
+            GameType::EuropeanRoulette { bets } => (0..european_roulette::POCKETS as u8)
+                .try_fold(0u64, |max, spin| Some(max.max(european_roulette::payout(bets, spin)?))),
+            GameType::Dice { target } => dice::win_payout(self.bet_amount, *target, self.house_edge),
+            GameType::Coinflip { .. } | GameType::CoinflipChallenge { .. } => {
+                coinflip::pot_payout(self.bet_amount, self.house_edge)
+            },
+        }
+    }
+}
+
+// Crash round, one PDA per casino and round ID
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CrashRound {
+    /// The casino the round is played in
+    pub casino: Pubkey,
+    /// Round ID the account address is derived from
+    pub round_id: [u8; 32],
+    /// The operator who opened the round
+    pub operator: Pubkey,
+    /// The operator's commitment, `sha256(server_seed)`
+    pub server_seed_hash: [u8; 32],
+    /// House edge in basis points the crash point is computed with, fixed
+    /// when the round is opened
+    pub house_edge: u16,
+    /// Unix timestamp betting closes and the multiplier starts climbing at
+    pub starts_at: i64,
+    /// Unix timestamp from which the bets may be refunded if the round is
+    /// still unsettled: `starts_at` plus the casino's settlement timeout
+    pub refundable_at: i64,
+    /// Bets placed that have not been settled or refunded yet
+    pub open_bets: u32,
+    /// Whether the server seed has been revealed
+    pub is_settled: bool,
+    /// Multiplier the round crashed at, in basis points (only valid if
+    /// is_settled is true)
+    pub crash_point: u64,
+    /// Result hash derived from the server seed (only valid if is_settled is true)
+    pub result_hash: [u8; 32],
+    /// The revealed server seed (only valid if is_settled is true)
+    pub server_seed: [u8; 32],
+    /// Bump seed of the round PDA
+    pub bump: u8,
+}
+
+impl CrashRound {
+    /// Decodes the data of a crash round account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+    
+    /// Unix timestamp the multiplier reaches `crash_point` at, which is when
+    /// the round crashes if that is its crash point
+    pub fn crashes_at(&self, crash_point: u64) -> Option<i64> {
+        self.starts_at.checked_add(crash::seconds_to_reach(crash_point))
+    }
+}
+
+// A player's bet in a crash round, one PDA per round and player. The account
+// is closed when the bet is settled or refunded.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CrashBet {
+    /// The round the bet was placed in
+    pub round: Pubkey,
+    /// The player who placed the bet
+    pub player: Pubkey,
+    /// Bet amount in lamports (or token base units)
+    pub bet_amount: u64,
+    /// Multiplier the bet cashes out at automatically, in basis points
+    pub auto_cashout: Option<u64>,
+    /// Multiplier the player cashed out at with `CashOut`, in basis points, or
+    /// 0 if they haven't; kept fixed-size so the account never has to grow
+    pub cashed_out_at: u64,
+    /// Unix timestamp of the `CashOut` (only valid if cashed_out_at is set);
+    /// a cash-out at or after the crash isn't paid
+    pub cashed_out_time: i64,
+    /// Bump seed of the bet PDA
+    pub bump: u8,
+}
+
+impl CrashBet {
+    /// Decodes the data of a crash bet account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }    
+    /// The most a settlement could pay: the multiplier the bet cashed out
+    /// at, as if the round crashed above it. `None` if it overflows.
+    pub fn max_payout(&self) -> Option<u64> {
+        let cashed_out = (self.cashed_out_at != 0).then_some(self.cashed_out_at);
+        crash::payout(self.bet_amount, crash::cashout(self.auto_cashout, cashed_out), u64::MAX)
+    }
+}
+
+// An operator's commitment to a server seed, one PDA per casino and seed hash.
+// The account is closed when a bet is placed against it.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Commitment {
+    /// The casino the commitment was posted in
+    pub casino: Pubkey,
+    /// The operator who posted it
+    pub operator: Pubkey,
+    /// The committed hash, `sha256(server_seed)`
+    pub server_seed_hash: [u8; 32],
+    /// Bump seed of the commitment PDA
+    pub bump: u8,
+}
+
+impl Commitment {
+    /// Decodes the data of a commitment account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+}
+
+// Shared 15-slot roulette round, one PDA per casino and round ID
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Round {
+    /// The casino the round is played in
+    pub casino: Pubkey,
+    /// Round ID the account address is derived from
+    pub round_id: [u8; 32],
+    /// The operator who opened the round
+    pub operator: Pubkey,
+    /// The operator's commitment, `sha256(server_seed)`
+    pub server_seed_hash: [u8; 32],
+    /// Unix timestamp betting closes at unless the round is locked earlier
+    pub betting_ends_at: i64,
+    /// Unix timestamp from which the bets may be refunded if the round is
+    /// still unspun: `betting_ends_at` plus the casino's settlement timeout
+    pub refundable_at: i64,
+    /// Bet accounts that have not been settled or refunded yet
+    pub open_bets: u32,
+    /// Whether betting has been closed with `LockRound`
+    pub is_locked: bool,
+    /// Whether the server seed has been revealed
+    pub is_spun: bool,
+    /// Slot the wheel landed on (only valid if is_spun is true)
+    pub winning_slot: u8,
+    /// Result hash derived from the server seed (only valid if is_spun is true)
+    pub result_hash: [u8; 32],
+    /// The revealed server seed (only valid if is_spun is true)
+    pub server_seed: [u8; 32],
+    /// Bump seed of the round PDA
+    pub bump: u8,
+}
+
+impl Round {
+    /// Decodes the data of a round account, checking its discriminator and
+    /// layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+}
+
+// A player's bets in a roulette round, one PDA per round and player. The
+// account is closed when the bets are settled or refunded.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RoundBet {
+    /// The round the bets were placed in
+    pub round: Pubkey,
+    /// The player who placed the bets
+    pub player: Pubkey,
+    /// Stake on red, in lamports (or token base units)
+    pub red: u64,
+    /// Stake on black
+    pub black: u64,
+    /// Stake on green
+    pub green: u64,
+    /// Bump seed of the bet PDA
+    pub bump: u8,
+}
+
+impl RoundBet {
+    /// Decodes the data of a round bet account, checking its discriminator
+    /// and layout version
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_account_data(data)
+    }
+    
+    /// Stake on a colour
+    pub fn stake(&self, color: RouletteColor) -> u64 {
+        match color {
+            RouletteColor::Red => self.red,
+            RouletteColor::Black => self.black,
+            RouletteColor::Green => self.green,
+        }
+    }
+    
+    fn stake_mut(&mut self, color: RouletteColor) -> &mut u64 {
+        match color {
+            RouletteColor::Red => &mut self.red,
+            RouletteColor::Black => &mut self.black,
+            RouletteColor::Green => &mut self.green,
+        }
+    }
+    
+    /// Stakes on all colours together, or `None` if they overflow
+    pub fn total_stake(&self) -> Option<u64> {
+        self.red.checked_add(self.black)?.checked_add(self.green)
+    }
+    
+    /// The most the spin could pay: the stake of whichever colour pays most
+    /// if it wins. `None` if it overflows.
+    pub fn max_payout(&self) -> Option<u64> {
+        [RouletteColor::Red, RouletteColor::Black, RouletteColor::Green]
+            .into_iter()
+            .try_fold(0u64, |max, color| Some(max.max(self.stake(color).checked_mul(color.multiplier())?)))
+    }
+    
+    /// Amount paid for the bets given the round's result hash (0 if they all
+    /// lost), or `None` if the payout overflows
+    pub fn payout(&self, result_hash: &[u8; 32]) -> Option<u64> {
+        let color = roulette::slot_color(roulette::winning_slot(result_hash));
+        roulette::payout(self.stake(color), color, result_hash)
+    }
+}
+
+/// Length of the type tag at the start of every program account
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Length of the discriminator and layout version byte preceding the state
+pub const ACCOUNT_HEADER_LEN: usize = DISCRIMINATOR_LEN + 1;
+
+/// State stored in a program-owned account: an 8-byte discriminator
+/// identifying the account type and a byte giving the layout version,
+/// followed by the Borsh-encoded state
+pub trait ProgramAccount: BorshSerialize + BorshDeserialize {
+    /// Type tag, the first 8 bytes of `sha256("account:<TypeName>")`
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN];
+    
+    /// Layout version written by this program; bump it whenever the fields
+    /// change and teach the migration to upgrade the previous layout
+    const VERSION: u8;
+    
+    /// Encodes the state as account data
+    fn to_account_data(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        data.push(Self::VERSION);
+        self.serialize(&mut data)?;
+        Ok(data)
+    }
+    
+    /// Decodes account data, rejecting accounts of any other type or layout
+    /// version
+    fn from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < ACCOUNT_HEADER_LEN || data[..DISCRIMINATOR_LEN] != Self::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        
+        if data[DISCRIMINATOR_LEN] != Self::VERSION {
+            return Err(CasinoError::UnsupportedAccountVersion.into());
+        }
+        
+        Ok(Self::try_from_slice(&data[ACCOUNT_HEADER_LEN..])?)
+    }
+    
+    /// Loads the state of an account, checking that the program owns it
+    fn load(program_id: &Pubkey, account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        
+        Self::from_account_data(&account_info.data.borrow())
+    }
+    
+    /// Writes the state back to its account
+    fn store(&self, account_info: &AccountInfo) -> ProgramResult {
+        let data = self.to_account_data()?;
+        account_info.data
+            .borrow_mut()
+            .get_mut(..data.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+impl ProgramAccount for CasinoState {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [78, 213, 52, 249, 79, 38, 105, 234];
+    const VERSION: u8 = 2;
+}
+
+impl ProgramAccount for OperatorState {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [253, 164, 195, 158, 226, 13, 170, 145];
+    const VERSION: u8 = 1;
+}
+
+impl ProgramAccount for GameState {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [144, 94, 208, 172, 248, 99, 134, 120];
+    const VERSION: u8 = 2;
+}
+
+impl ProgramAccount for CrashRound {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [228, 108, 241, 18, 148, 87, 175, 12];
+    const VERSION: u8 = 1;
+}
+
+impl ProgramAccount for CrashBet {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [132, 170, 104, 195, 62, 156, 79, 27];
+    const VERSION: u8 = 1;
+}
+
+impl ProgramAccount for Round {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [87, 127, 165, 51, 73, 78, 116, 174];
+    const VERSION: u8 = 1;
+}
+
+impl ProgramAccount for RoundBet {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [94, 161, 115, 147, 160, 65, 219, 179];
+    const VERSION: u8 = 1;
+}
+
+impl ProgramAccount for Commitment {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [61, 112, 129, 128, 24, 147, 77, 87];
+    const VERSION: u8 = 1;
+}
+
 // Program entry point implementation
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -202,21 +1408,127 @@ pub fn process_instruction(
         CasinoInstruction::Initialize { house_edge, min_bet, max_bet } => {
             process_initialize(program_id, accounts, house_edge, min_bet, max_bet)
         },
-        CasinoInstruction::PlaceBet { game_id, bet_amount } => {
-            process_place_bet(program_id, accounts, game_id, bet_amount)
+        CasinoInstruction::PlaceBet { game_id, bet_amount, client_seed, server_seed_hash } => {
+            process_place_bet(program_id, accounts, game_id, bet_amount, client_seed, server_seed_hash, GameType::Standard)
+        },
+        CasinoInstruction::PlaceRouletteBet { game_id, bet_amount, color, client_seed, server_seed_hash } => {
+            let game_type = GameType::Roulette { color };
+            process_place_bet(program_id, accounts, game_id, bet_amount, client_seed, server_seed_hash, game_type)
+        },
+        CasinoInstruction::PlaceRouletteBets { game_id, bets, client_seed, server_seed_hash } => {
+            let bet_amount = european_roulette::total_stake(&bets)
+                .ok_or(CasinoError::InvalidRouletteBet)?;
+            let game_type = GameType::EuropeanRoulette { bets };
+            process_place_bet(program_id, accounts, game_id, bet_amount, client_seed, server_seed_hash, game_type)
         },
-        CasinoInstruction::SettleGame { is_win, win_amount, result_hash } => {
-            process_settle_game(program_id, accounts, is_win, win_amount, result_hash)
+        CasinoInstruction::SettleGame { is_win, win_amount, server_seed } => {
+            process_settle_game(program_id, accounts, is_win, win_amount, server_seed)
         },
-        CasinoInstruction::UpdateParams { house_edge, min_bet, max_bet } => {
-            process_update_params(program_id, accounts, house_edge, min_bet, max_bet)
+        CasinoInstruction::UpdateParams { house_edge, min_bet, max_bet, settle_timeout } => {
+            process_update_params(program_id, accounts, house_edge, min_bet, max_bet, settle_timeout)
         },
-        CasinoInstruction::AddOperator => {
-            process_add_operator(program_id, accounts)
+        CasinoInstruction::AddOperator { roles, daily_payout_cap } => {
+            process_add_operator(program_id, accounts, roles, daily_payout_cap)
         },
         CasinoInstruction::RemoveOperator => {
             process_remove_operator(program_id, accounts)
         },
+        CasinoInstruction::FundVault { amount } => {
+            process_fund_vault(program_id, accounts, amount)
+        },
+        CasinoInstruction::WithdrawFromVault { amount } => {
+            process_withdraw_from_vault(program_id, accounts, amount)
+        },
+        CasinoInstruction::CloseGame => {
+            process_close_game(program_id, accounts)
+        },
+        CasinoInstruction::ClaimRefund => {
+            process_claim_refund(program_id, accounts, false)
+        },
+        CasinoInstruction::ProposeAuthority { new_authority } => {
+            process_propose_authority(program_id, accounts, new_authority)
+        },
+        CasinoInstruction::AcceptAuthority => {
+            process_accept_authority(program_id, accounts)
+        },
+        CasinoInstruction::Pause { betting, settlement } => {
+            process_set_paused(program_id, accounts, betting, settlement, true)
+        },
+        CasinoInstruction::Unpause { betting, settlement } => {
+            process_set_paused(program_id, accounts, betting, settlement, false)
+        },
+        CasinoInstruction::MigrateCasinoState => {
+            process_migrate_casino_state(program_id, accounts)
+        },
+        CasinoInstruction::PlaceDiceBet { game_id, bet_amount, target, client_seed, server_seed_hash } => {
+            if !dice::is_valid_target(target) {
+                return Err(CasinoError::InvalidDiceTarget.into());
+            }
+            let game_type = GameType::Dice { target };
+            process_place_bet(program_id, accounts, game_id, bet_amount, client_seed, server_seed_hash, game_type)
+        },
+        CasinoInstruction::PlaceCoinflipBet { game_id, bet_amount, side, client_seed, server_seed_hash } => {
+            let game_type = GameType::Coinflip { side };
+            process_place_bet(program_id, accounts, game_id, bet_amount, client_seed, server_seed_hash, game_type)
+        },
+        CasinoInstruction::OpenCoinflipChallenge { game_id, stake, side, client_seed, server_seed_hash } => {
+            let game_type = GameType::CoinflipChallenge { side, opponent: Pubkey::default() };
+            process_place_bet(program_id, accounts, game_id, stake, client_seed, server_seed_hash, game_type)
+        },
+        CasinoInstruction::AcceptCoinflipChallenge => {
+            process_accept_coinflip_challenge(program_id, accounts)
+        },
+        CasinoInstruction::CancelCoinflipChallenge => {
+            process_claim_refund(program_id, accounts, true)
+        },
+        CasinoInstruction::OpenCrashRound { round_id, server_seed_hash, starts_at } => {
+            process_open_crash_round(program_id, accounts, round_id, server_seed_hash, starts_at)
+        },
+        CasinoInstruction::JoinCrashRound { bet_amount, auto_cashout } => {
+            process_join_crash_round(program_id, accounts, bet_amount, auto_cashout)
+        },
+        CasinoInstruction::CashOut { multiplier } => {
+            process_cash_out(program_id, accounts, multiplier)
+        },
+        CasinoInstruction::SettleCrashRound { server_seed } => {
+            process_settle_crash_round(program_id, accounts, server_seed)
+        },
+        CasinoInstruction::ClaimCrashRefund => {
+            process_claim_crash_refund(program_id, accounts)
+        },
+        CasinoInstruction::OpenRound { round_id, server_seed_hash, betting_ends_at } => {
+            process_open_round(program_id, accounts, round_id, server_seed_hash, betting_ends_at)
+        },
+        CasinoInstruction::PlaceRoundBet { color, amount } => {
+            process_place_round_bet(program_id, accounts, color, amount)
+        },
+        CasinoInstruction::LockRound => {
+            process_lock_round(program_id, accounts)
+        },
+        CasinoInstruction::SpinRound { server_seed } => {
+            process_spin_round(program_id, accounts, server_seed)
+        },
+        CasinoInstruction::SettleRoundBets => {
+            process_settle_round_bets(program_id, accounts)
+        },
+        CasinoInstruction::ClaimRoundRefund => {
+            process_claim_round_refund(program_id, accounts)
+        },
+        CasinoInstruction::SettleBatch { results } => {
+            process_settle_batch(program_id, accounts, results)
+        },
+        CasinoInstruction::PostCommitment { server_seed_hash } => {
+            process_post_commitment(program_id, accounts, server_seed_hash)
+        },
+        CasinoInstruction::CloseCrashRound => {
+            process_close_round(program_id, accounts, true)
+        },
+        CasinoInstruction::CloseRound => {
+            process_close_round(program_id, accounts, false)
+        },
+        CasinoInstruction::ClaimLegacyRefund { game_id } => {
+            process_claim_legacy_refund(program_id, accounts, game_id)
+        },
     }
 }
 
@@ -233,8 +1545,13 @@ fn process_initialize(
     // Get accounts
     let authority_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     
+    // A mint account after the system program switches the casino to SPL tokens
+    let mint_info = next_account_info(account_info_iter).ok();
+    
     // Check that the authority signed the transaction
     if !authority_info.is_signer {
         return Err(CasinoError::Unauthorized.into());
@@ -249,19 +1566,6 @@ fn process_initialize(
         return Err(CasinoError::InvalidBetAmount.into());
     }
     
-    // Create casino state account
-    let rent = Rent::get()?;
-    let casino_state = CasinoState {
-        authority: *authority_info.key,
-        house_edge,
-        min_bet,
-        max_bet,
-        operators: vec![*authority_info.key],  // Authority is an operator by default
-    };
-    
-    let space = casino_state.try_to_vec()?.len();
-    let lamports = rent.minimum_balance(space);
-    
     // Derive the expected PDA
     let (expected_casino_state_pubkey, bump_seed) = Pubkey::find_program_address(
         &[b"casino", authority_info.key.as_ref()],
@@ -273,25 +1577,106 @@ fn process_initialize(
         return Err(ProgramError::InvalidArgument);
     }
     
+    // Create casino state account
+    let casino_state = CasinoState {
+        authority: *authority_info.key,
+        house_edge,
+        min_bet,
+        max_bet,
+        mint: mint_info.map(|info| *info.key),
+        settle_timeout: DEFAULT_SETTLE_TIMEOUT,
+        creator: *authority_info.key,
+        pending_authority: Pubkey::default(),
+        betting_paused: false,
+        settlement_paused: false,
+        liabilities: 0,
+        bump: bump_seed,
+    };
+    
+    let space = casino_state.to_account_data()?.len();
+    
     // Create the account using PDA
-    invoke_signed(
-        &system_instruction::create_account(
-            authority_info.key,
-            casino_state_info.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
-        &[
-            authority_info.clone(),
-            casino_state_info.clone(),
-            system_program_info.clone(),
-        ],
-        &[&[b"casino", authority_info.key.as_ref(), &[bump_seed]]],
+    create_pda_account(
+        program_id,
+        authority_info,
+        casino_state_info,
+        system_program_info,
+        space,
+        &[b"casino", authority_info.key.as_ref(), &[bump_seed]],
     )?;
     
     // Serialize the state into the newly created account
-    casino_state.serialize(&mut *casino_state_info.data.borrow_mut())?;
+    casino_state.store(casino_state_info)?;
+    
+    // Create the house vault
+    create_vault_account(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        vault_info,
+        system_program_info,
+    )?;
+    
+    // The authority is an operator with every role and no payout cap
+    create_operator_account(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        system_program_info,
+        &OperatorState {
+            casino: *casino_state_info.key,
+            operator: *authority_info.key,
+            roles: ROLE_ALL,
+            daily_payout_cap: u64::MAX,
+            paid_today: 0,
+            payout_day: 0,
+        },
+    )?;
+    
+    // Token casinos keep their bankroll in the vault's associated token account
+    if let Some(mint_info) = mint_info {
+        let vault_token_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        
+        if *token_program_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        spl_token::state::Mint::unpack(&mint_info.data.borrow())?;
+        validate_vault_token_account(vault_info.key, mint_info.key, vault_token_info)?;
+        
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                authority_info.key,
+                vault_info.key,
+                mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                authority_info.clone(),
+                vault_token_info.clone(),
+                vault_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        
+        msg!("Casino bets are denominated in mint: {}", mint_info.key);
+    }
+    
+    CasinoEvent::CasinoInitialized(events::CasinoInitialized {
+        casino: *casino_state_info.key,
+        authority: *authority_info.key,
+        house_edge,
+        min_bet,
+        max_bet,
+        mint: casino_state.mint,
+    })
+    .emit()?;
     
     msg!("Casino initialized with house edge: {}, min bet: {}, max bet: {}", 
         house_edge, min_bet, max_bet);
@@ -305,6 +1690,9 @@ fn process_place_bet(
     accounts: &[AccountInfo],
     game_id: [u8; 32],
     bet_amount: u64,
+    client_seed: [u8; 32],
+    server_seed_hash: [u8; 32],
+    game_type: GameType,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
@@ -312,7 +1700,9 @@ fn process_place_bet(
     let player_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
     let game_account_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
+    let commitment_info = next_account_info(account_info_iter)?;
     
     // Check that the player signed the transaction
     if !player_info.is_signer {
@@ -320,18 +1710,56 @@ fn process_place_bet(
     }
     
     // Deserialize casino state
-    let casino_state = CasinoState::try_from_slice(&casino_state_info.data.borrow())?;
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.betting_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Only a hash an operator committed to can be bet against, and only once,
+    // so a player can't replay a revealed seed's hash
+    load_commitment(program_id, casino_state_info.key, &server_seed_hash, commitment_info)?;
     
     // Validate bet amount
     if bet_amount < casino_state.min_bet || bet_amount > casino_state.max_bet {
         return Err(CasinoError::InvalidBetAmount.into());
     }
     
+    // At a high house edge a target near the top would pay less than the
+    // stake even when it wins
+    if let GameType::Dice { target } = game_type {
+        if dice::multiplier_bps(target, casino_state.house_edge) <= 10_000 {
+            return Err(CasinoError::InvalidBetParameters.into());
+        }
+    }
+    
     // Check if player has enough funds
-    if player_info.lamports() < bet_amount {
+    if casino_state.mint.is_none() && player_info.lamports() < bet_amount {
         return Err(CasinoError::InsufficientFunds.into());
     }
     
+    // The game account is derived from the casino, the player and the game ID
+    let (game_pubkey, game_bump_seed) = find_game_address(
+        program_id,
+        casino_state_info.key,
+        player_info.key,
+        &game_id,
+    );
+    
+    // Verify the derived address matches the provided game account
+    if game_pubkey != *game_account_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // The settlement timeout in force now is the one the bet is placed under
+    let created_at = Clock::get()?.unix_timestamp;
+    let refundable_at = created_at
+        .checked_add(casino_state.settle_timeout)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
     // Create game state
     let game_state = GameState {
         player: *player_info.key,
@@ -340,47 +1768,86 @@ fn process_place_bet(
         is_win: false,
         win_amount: 0,
         result_hash: [0; 32],
+        server_seed_hash,
+        client_seed,
+        server_seed: [0; 32],
+        game_type,
+        house_edge: casino_state.house_edge,
+        created_at,
+        refundable_at,
+        casino: *casino_state_info.key,
+        game_id,
+        bump: game_bump_seed,
     };
     
     // Create game account
-    let rent = Rent::get()?;
-    let space = game_state.try_to_vec()?.len();
-    let lamports = rent.minimum_balance(space);
-    
-    // Create the account with the game ID as seed
-    let (game_pubkey, _) = Pubkey::find_program_address(
-        &[&game_id],
-        program_id,
-    );
-    
-    // Verify the derived address matches the provided game account
-    if game_pubkey != *game_account_info.key {
-        return Err(ProgramError::InvalidArgument);
-    }
+    let space = game_state.to_account_data()?.len();
     
     // Create the game account
-    invoke_signed(
-        &system_instruction::create_account(
-            player_info.key,
-            game_account_info.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
+    create_pda_account(
+        program_id,
+        player_info,
+        game_account_info,
+        system_program_info,
+        space,
         &[
-            player_info.clone(),
-            game_account_info.clone(),
-            system_program_info.clone(),
+            b"game",
+            casino_state_info.key.as_ref(),
+            player_info.key.as_ref(),
+            &game_id,
+            &[game_bump_seed],
         ],
-        &[&[&game_id]],
     )?;
     
     // Serialize the game state into the newly created account
-    game_state.serialize(&mut *game_account_info.data.borrow_mut())?;
+    game_state.store(game_account_info)?;
+    
+    // Escrow the bet in the house vault
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            transfer_tokens(
+                token_program_info,
+                player_token_info,
+                vault_token_info,
+                player_info,
+                bet_amount,
+                &[],
+            )?;
+        },
+        None => {
+            invoke(
+                &system_instruction::transfer(player_info.key, vault_info.key, bet_amount),
+                &[
+                    player_info.clone(),
+                    vault_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        },
+    }
+    
+    // Withdrawals must leave the stake in the vault until it is paid out
+    casino_state.escrow(bet_amount)?;
+    casino_state.store(casino_state_info)?;
+    
+    // Consume the commitment, returning its rent to the vault
+    close_account(commitment_info, vault_info)?;
     
-    // Transfer bet amount from player to the program
-    **player_info.try_borrow_mut_lamports()? -= bet_amount;
-    **game_account_info.try_borrow_mut_lamports()? += bet_amount;
+    CasinoEvent::BetPlaced(events::BetPlaced {
+        casino: *casino_state_info.key,
+        game: *game_account_info.key,
+        player: *player_info.key,
+        game_id,
+        bet_amount,
+        game_type: game_state.game_type,
+    })
+    .emit()?;
     
     msg!("Bet placed: {} lamports", bet_amount);
     
@@ -389,18 +1856,20 @@ fn process_place_bet(
 
 // Process SettleGame instruction
 fn process_settle_game(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     is_win: bool,
     win_amount: u64,
-    result_hash: [u8; 32],
+    server_seed: [u8; 32],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
     let authority_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
     let game_info = next_account_info(account_info_iter)?;
     let player_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
     let _system_program_info = next_account_info(account_info_iter)?;
     
     // Check that the authority signed the transaction
@@ -409,74 +1878,261 @@ fn process_settle_game(
     }
     
     // Deserialize casino state
-    let casino_state = CasinoState::try_from_slice(&casino_state_info.data.borrow())?;
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
     
-    // Check that the signer is an authorized operator
-    if !casino_state.operators.contains(authority_info.key) {
-        return Err(CasinoError::Unauthorized.into());
+    if casino_state.settlement_paused {
+        return Err(CasinoError::Paused.into());
     }
     
-    // Deserialize game state
-    let mut game_state = GameState::try_from_slice(&game_info.data.borrow())?;
+    // Check that the signer is an operator allowed to settle
+    let mut operator_state = authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
     
-    // Check that the game has not been settled yet
-    if game_state.is_settled {
-        return Err(CasinoError::GameAlreadySettled.into());
-    }
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
     
-    // Check that the player account matches the one in the game state
-    if *player_info.key != game_state.player {
-        return Err(ProgramError::InvalidArgument);
-    }
+    // Deserialize game state
+    let mut game_state = load_game_state(program_id, casino_state_info.key, game_info)?;
     
-    // If the player won, validate the win amount against house edge
-    if is_win {
-        // Calculate maximum possible win with house edge
-        let basis_points = 10000;
-        let max_possible_win = game_state.bet_amount
-            .checked_mul(basis_points)
-            .ok_or(ProgramError::ArithmeticOverflow)?
-            .checked_div(basis_points.checked_sub(casino_state.house_edge as u64).unwrap_or(1))
-            .ok_or(ProgramError::ArithmeticOverflow)?;
+    // Check the claimed outcome against the one the seeds decide
+    let (result_hash, payout) = check_settlement(
+        &game_state,
+        game_info.key,
+        player_info.key,
+        is_win,
+        win_amount,
+        &server_seed,
+    )?;
+    
+    if payout > 0 {
+        // Count the win against the operator's daily payout cap
+        operator_state.record_payout(payout, Clock::get()?.unix_timestamp)?;
+        operator_state.store(operator_account_info)?;
         
-        if win_amount > max_possible_win {
-            return Err(CasinoError::ExpectedAmountMismatch.into());
+        // Pay the win out of the house vault
+        match casino_state.mint {
+            Some(mint) => {
+                let player_token_info = next_account_info(account_info_iter)?;
+                let vault_token_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+                
+                // Winnings may only go to a token account the payee owns
+                let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+                if player_token.owner != *player_info.key {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                
+                validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+                
+                pay_tokens_from_vault(
+                    program_id,
+                    casino_state_info.key,
+                    vault_info,
+                    vault_token_info,
+                    player_token_info,
+                    token_program_info,
+                    payout,
+                )?;
+            },
+            None => {
+                if vault_available_balance(vault_info)? < payout {
+                    return Err(CasinoError::InsufficientFunds.into());
+                }
+                
+                **vault_info.try_borrow_mut_lamports()? -= payout;
+                **player_info.try_borrow_mut_lamports()? += payout;
+            },
         }
-        
-        // Transfer win amount to player
-        **game_info.try_borrow_mut_lamports()? -= win_amount;
-        **player_info.try_borrow_mut_lamports()? += win_amount;
     }
     
     // Update game state
     game_state.is_settled = true;
     game_state.is_win = is_win;
-    game_state.win_amount = win_amount;
+    game_state.win_amount = payout;
     game_state.result_hash = result_hash;
+    game_state.server_seed = server_seed;
     
     // Serialize updated game state
-    game_state.serialize(&mut *game_info.data.borrow_mut())?;
+    game_state.store(game_info)?;
+    
+    casino_state.release(game_state.escrowed().ok_or(ProgramError::ArithmeticOverflow)?)?;
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::GameSettled(events::GameSettled {
+        casino: *casino_state_info.key,
+        game: *game_info.key,
+        player: game_state.player,
+        operator: *authority_info.key,
+        game_id: game_state.game_id,
+        bet_amount: game_state.bet_amount,
+        is_win,
+        win_amount: game_state.win_amount,
+        result_hash,
+    })
+    .emit()?;
     
     msg!("Game settled: player {}, win amount: {}", 
         if is_win { "won" } else { "lost" }, 
-        win_amount);
+        game_state.win_amount);
     
     Ok(())
 }
 
+// Checks a claimed outcome of a game against the one its seeds decide, for
+// `SettleGame` and `SettleBatch`. Returns the result hash and the amount the
+// outcome pays `payee`.
+fn check_settlement(
+    game_state: &GameState,
+    game: &Pubkey,
+    payee: &Pubkey,
+    is_win: bool,
+    win_amount: u64,
+    server_seed: &[u8; 32],
+) -> Result<([u8; 32], u64), ProgramError> {
+    // Check that the game has not been settled yet
+    if game_state.is_settled {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    // Check that the player account is the one the claimed outcome pays
+    if *payee != game_state.payee(is_win) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // The revealed seed must open the commitment made at bet time
+    if randomness::commit(server_seed) != game_state.server_seed_hash {
+        return Err(CasinoError::InvalidServerSeed.into());
+    }
+    
+    // Derive the outcome from both seeds and reject a different claim
+    let result_hash = randomness::result_hash(server_seed, &game_state.client_seed, game);
+    match &game_state.game_type {
+        GameType::Standard => {
+            // A win pays even odds less the house edge, nothing else
+            let payout = randomness::payout(game_state.bet_amount, game_state.house_edge, &result_hash)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            
+            if is_win != (payout > 0) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            if is_win && win_amount != payout {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+        },
+        GameType::Roulette { color } => {
+            // The wheel decides the payout; the claim must match it exactly
+            let payout = roulette::payout(game_state.bet_amount, *color, &result_hash)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            
+            if is_win != (payout > 0) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            if is_win && win_amount != payout {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+            
+            msg!("Roulette landed on slot {}", roulette::winning_slot(&result_hash));
+        },
+        GameType::EuropeanRoulette { bets } => {
+            // One spin pays every covering bet at its fixed odds
+            let spin = european_roulette::spin(&result_hash);
+            let payout = european_roulette::payout(bets, spin)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            
+            if is_win != (payout > 0) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            if is_win && win_amount != payout {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+            
+            msg!("Roulette spin result: {}", spin);
+        },
+        GameType::Dice { target } => {
+            // The roll and the house edge fix the payout; the claim must match it
+            let payout = dice::payout(game_state.bet_amount, *target, game_state.house_edge, &result_hash)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            
+            if is_win != (payout > 0) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            if is_win && win_amount != payout {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+            
+            let roll = dice::roll(&result_hash);
+            msg!("Dice rolled {}.{:02} against a target of {}", roll / 100, roll % 100, target);
+        },
+        GameType::Coinflip { side } => {
+            let payout = coinflip::payout(game_state.bet_amount, *side, game_state.house_edge, &result_hash)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            
+            if is_win != (payout > 0) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            if is_win && win_amount != payout {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+            
+            msg!("Coin landed on {:?}", coinflip::flip(&result_hash));
+        },
+        GameType::CoinflipChallenge { side, opponent } => {
+            // Nobody has matched the stake yet
+            if *opponent == Pubkey::default() {
+                return Err(CasinoError::ChallengeNotAccepted.into());
+            }
+            
+            // Whichever side won takes both stakes less the rake
+            let landed = coinflip::flip(&result_hash);
+            if is_win != (landed == *side) {
+                return Err(CasinoError::OutcomeMismatch.into());
+            }
+            
+            let pot = coinflip::pot_payout(game_state.bet_amount, game_state.house_edge)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if win_amount != pot {
+                return Err(CasinoError::ExpectedAmountMismatch.into());
+            }
+            
+            msg!("Coin landed on {:?}", landed);
+        },
+    }
+    
+    // A challenge pays its pot to whichever side won, other games only a win
+    let payout = match game_state.game_type {
+        GameType::CoinflipChallenge { .. } => win_amount,
+        _ if is_win => win_amount,
+        _ => 0,
+    };
+    
+    Ok((result_hash, payout))
+}
+
 // Process UpdateParams instruction
 fn process_update_params(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     house_edge: Option<u16>,
     min_bet: Option<u64>,
     max_bet: Option<u64>,
+    settle_timeout: Option<i64>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
     // Get accounts
     let authority_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
     
     // Check that the authority signed the transaction
     if !authority_info.is_signer {
@@ -484,12 +2140,16 @@ fn process_update_params(
     }
     
     // Deserialize casino state
-    let mut casino_state = CasinoState::try_from_slice(&casino_state_info.data.borrow())?;
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
     
-    // Check that the signer is the casino authority
-    if *authority_info.key != casino_state.authority {
-        return Err(CasinoError::Unauthorized.into());
-    }
+    // Check that the signer is an operator allowed to change parameters
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_PARAMS,
+    )?;
     
     // Update parameters if provided
     if let Some(edge) = house_edge {
@@ -507,24 +2167,42 @@ fn process_update_params(
         casino_state.max_bet = max;
     }
     
+    if let Some(timeout) = settle_timeout {
+        if !(MIN_SETTLE_TIMEOUT..=MAX_SETTLE_TIMEOUT).contains(&timeout) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        casino_state.settle_timeout = timeout;
+    }
+    
     // Validate min/max bet relationship
     if casino_state.min_bet > casino_state.max_bet {
         return Err(CasinoError::InvalidBetAmount.into());
     }
     
     // Serialize updated casino state
-    casino_state.serialize(&mut *casino_state_info.data.borrow_mut())?;
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::ParamsUpdated(events::ParamsUpdated {
+        casino: *casino_state_info.key,
+        house_edge: casino_state.house_edge,
+        min_bet: casino_state.min_bet,
+        max_bet: casino_state.max_bet,
+        settle_timeout: casino_state.settle_timeout,
+    })
+    .emit()?;
     
-    msg!("Casino parameters updated: house edge: {}, min bet: {}, max bet: {}", 
-        casino_state.house_edge, casino_state.min_bet, casino_state.max_bet);
+    msg!("Casino parameters updated: house edge: {}, min bet: {}, max bet: {}, settle timeout: {}s", 
+        casino_state.house_edge, casino_state.min_bet, casino_state.max_bet, casino_state.settle_timeout);
     
     Ok(())
 }
 
 // Process AddOperator instruction
 fn process_add_operator(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
+    roles: u8,
+    daily_payout_cap: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
@@ -532,6 +2210,8 @@ fn process_add_operator(
     let authority_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
     let operator_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
     
     // Check that the authority signed the transaction
     if !authority_info.is_signer {
@@ -539,31 +2219,64 @@ fn process_add_operator(
     }
     
     // Deserialize casino state
-    let mut casino_state = CasinoState::try_from_slice(&casino_state_info.data.borrow())?;
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
     
     // Check that the signer is the casino authority
     if *authority_info.key != casino_state.authority {
         return Err(CasinoError::Unauthorized.into());
     }
     
-    // Add operator if not already in the list
-    if !casino_state.operators.contains(operator_info.key) {
-        casino_state.operators.push(*operator_info.key);
-        
-        // Serialize updated casino state
-        casino_state.serialize(&mut *casino_state_info.data.borrow_mut())?;
+    // Only known roles can be granted
+    if roles & !ROLE_ALL != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if operator_account_info.data_is_empty() {
+        create_operator_account(
+            program_id,
+            casino_state_info.key,
+            authority_info,
+            operator_account_info,
+            system_program_info,
+            &OperatorState {
+                casino: *casino_state_info.key,
+                operator: *operator_info.key,
+                roles,
+                daily_payout_cap,
+                paid_today: 0,
+                payout_day: 0,
+            },
+        )?;
         
         msg!("Operator added: {}", operator_info.key);
     } else {
-        msg!("Operator already exists: {}", operator_info.key);
+        let mut operator_state = load_operator_account(
+            program_id,
+            casino_state_info.key,
+            operator_info.key,
+            operator_account_info,
+        )?;
+        operator_state.roles = roles;
+        operator_state.daily_payout_cap = daily_payout_cap;
+        operator_state.store(operator_account_info)?;
+        
+        msg!("Operator updated: {}", operator_info.key);
     }
     
+    CasinoEvent::OperatorAdded(events::OperatorAdded {
+        casino: *casino_state_info.key,
+        operator: *operator_info.key,
+        roles,
+        daily_payout_cap,
+    })
+    .emit()?;
+    
     Ok(())
 }
 
 // Process RemoveOperator instruction
 fn process_remove_operator(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -572,6 +2285,7 @@ fn process_remove_operator(
     let authority_info = next_account_info(account_info_iter)?;
     let casino_state_info = next_account_info(account_info_iter)?;
     let operator_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
     
     // Check that the authority signed the transaction
     if !authority_info.is_signer {
@@ -579,7 +2293,7 @@ fn process_remove_operator(
     }
     
     // Deserialize casino state
-    let mut casino_state = CasinoState::try_from_slice(&casino_state_info.data.borrow())?;
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
     
     // Check that the signer is the casino authority
     if *authority_info.key != casino_state.authority {
@@ -591,21 +2305,2968 @@ fn process_remove_operator(
         return Err(CasinoError::Unauthorized.into());
     }
     
-    // Remove operator if in the list
-    if let Some(index) = casino_state.operators.iter().position(|&x| x == *operator_info.key) {
-        casino_state.operators.remove(index);
-        
-        // Serialize updated casino state
-        casino_state.serialize(&mut *casino_state_info.data.borrow_mut())?;
-        
-        msg!("Operator removed: {}", operator_info.key);
+    load_operator_account(
+        program_id,
+        casino_state_info.key,
+        operator_info.key,
+        operator_account_info,
+    )?;
+    
+    // Close the operator account and return its rent to the authority
+    close_account(operator_account_info, authority_info)?;
+    
+    CasinoEvent::OperatorRemoved(events::OperatorRemoved {
+        casino: *casino_state_info.key,
+        operator: *operator_info.key,
+    })
+    .emit()?;
+    
+    msg!("Operator removed: {}", operator_info.key);
+    
+    Ok(())
+}
+
+// Process ProposeAuthority instruction
+fn process_propose_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is the casino authority
+    if *authority_info.key != casino_state.authority {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Accepting would close the authority's own operator account
+    if new_authority == casino_state.authority {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    casino_state.pending_authority = new_authority;
+    
+    // Serialize updated casino state
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::AuthorityProposed(events::AuthorityProposed {
+        casino: *casino_state_info.key,
+        authority: *authority_info.key,
+        new_authority,
+    })
+    .emit()?;
+    
+    msg!("Authority transfer proposed to: {}", new_authority);
+    
+    Ok(())
+}
+
+// Process AcceptAuthority instruction
+fn process_accept_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let new_authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let new_operator_account_info = next_account_info(account_info_iter)?;
+    let previous_authority_info = next_account_info(account_info_iter)?;
+    let previous_operator_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the new authority signed the transaction
+    if !new_authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Only the proposed key can accept, and only while the proposal stands
+    if casino_state.pending_authority == Pubkey::default()
+        || *new_authority_info.key != casino_state.pending_authority
+    {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    if *previous_authority_info.key != casino_state.authority {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Give the new authority every role with no payout cap
+    if new_operator_account_info.data_is_empty() {
+        create_operator_account(
+            program_id,
+            casino_state_info.key,
+            new_authority_info,
+            new_operator_account_info,
+            system_program_info,
+            &OperatorState {
+                casino: *casino_state_info.key,
+                operator: *new_authority_info.key,
+                roles: ROLE_ALL,
+                daily_payout_cap: u64::MAX,
+                paid_today: 0,
+                payout_day: 0,
+            },
+        )?;
     } else {
-        msg!("Operator not found: {}", operator_info.key);
+        let mut operator_state = load_operator_account(
+            program_id,
+            casino_state_info.key,
+            new_authority_info.key,
+            new_operator_account_info,
+        )?;
+        operator_state.roles = ROLE_ALL;
+        operator_state.daily_payout_cap = u64::MAX;
+        operator_state.store(new_operator_account_info)?;
+    }
+    
+    // Revoke the previous authority's operator account
+    load_operator_account(
+        program_id,
+        casino_state_info.key,
+        previous_authority_info.key,
+        previous_operator_account_info,
+    )?;
+    close_account(previous_operator_account_info, previous_authority_info)?;
+    
+    casino_state.authority = *new_authority_info.key;
+    casino_state.pending_authority = Pubkey::default();
+    
+    // Serialize updated casino state
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::AuthorityTransferred(events::AuthorityTransferred {
+        casino: *casino_state_info.key,
+        previous_authority: *previous_authority_info.key,
+        new_authority: *new_authority_info.key,
+    })
+    .emit()?;
+    
+    msg!("Casino authority transferred from {} to {}", previous_authority_info.key, new_authority_info.key);
+    
+    Ok(())
+}
+
+// Process Pause and Unpause instructions
+fn process_set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    betting: bool,
+    settlement: bool,
+    paused: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to pause
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_PAUSE,
+    )?;
+    
+    if betting {
+        casino_state.betting_paused = paused;
+    }
+    
+    if settlement {
+        casino_state.settlement_paused = paused;
+    }
+    
+    // Serialize updated casino state
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::PauseUpdated(events::PauseUpdated {
+        casino: *casino_state_info.key,
+        operator: *authority_info.key,
+        betting_paused: casino_state.betting_paused,
+        settlement_paused: casino_state.settlement_paused,
+    })
+    .emit()?;
+    
+    msg!("Casino paused: betting: {}, settlement: {}", 
+        casino_state.betting_paused, casino_state.settlement_paused);
+    
+    Ok(())
+}
+
+// Process MigrateCasinoState instruction
+fn process_migrate_casino_state(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    if casino_state_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    
+    // Current accounts start with the casino state discriminator
+    let data = casino_state_info.data.borrow();
+    if data.starts_with(&CasinoState::DISCRIMINATOR) {
+        return match data.get(DISCRIMINATOR_LEN) {
+            Some(&CasinoState::VERSION) => {
+                msg!("Casino state is already at version {}", CasinoState::VERSION);
+                Ok(())
+            },
+            _ => Err(CasinoError::UnsupportedAccountVersion.into()),
+        };
+    }
+    
+    // Anything else must be a version 1 casino
+    let legacy_state = CasinoStateV1::try_from_slice(&data)?;
+    drop(data);
+    
+    // Only the casino authority may migrate it
+    if *authority_info.key != legacy_state.authority {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Version 1 casinos were derived from the authority, which never changed
+    let (expected_casino_state_pubkey, bump_seed) = Pubkey::find_program_address(
+        &[b"casino", legacy_state.authority.as_ref()],
+        program_id,
+    );
+    if expected_casino_state_pubkey != *casino_state_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let casino_state = CasinoState {
+        authority: legacy_state.authority,
+        house_edge: legacy_state.house_edge,
+        min_bet: legacy_state.min_bet,
+        max_bet: legacy_state.max_bet,
+        mint: None,
+        settle_timeout: DEFAULT_SETTLE_TIMEOUT,
+        creator: legacy_state.authority,
+        pending_authority: Pubkey::default(),
+        betting_paused: false,
+        settlement_paused: false,
+        liabilities: 0,
+        bump: bump_seed,
+    };
+    
+    // Resize the account, topping up its rent from the authority
+    let new_data = casino_state.to_account_data()?;
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(new_data.len());
+    if casino_state_info.lamports() < required_lamports {
+        invoke(
+            &system_instruction::transfer(
+                authority_info.key,
+                casino_state_info.key,
+                required_lamports - casino_state_info.lamports(),
+            ),
+            &[
+                authority_info.clone(),
+                casino_state_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    
+    casino_state_info.realloc(new_data.len(), false)?;
+    casino_state.store(casino_state_info)?;
+    
+    // Version 1 casinos escrowed nothing, so they start with an empty vault
+    create_vault_account(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        vault_info,
+        system_program_info,
+    )?;
+    
+    // The authority is an operator with every role and no payout cap
+    create_operator_account(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        system_program_info,
+        &OperatorState {
+            casino: *casino_state_info.key,
+            operator: legacy_state.authority,
+            roles: ROLE_ALL,
+            daily_payout_cap: u64::MAX,
+            paid_today: 0,
+            payout_day: 0,
+        },
+    )?;
+    
+    // Other operators keep the settlement rights they had, uncapped
+    let legacy_operators = legacy_state.operators
+        .iter()
+        .filter(|operator| **operator != legacy_state.authority);
+    for operator in legacy_operators {
+        let legacy_operator_account_info = next_account_info(account_info_iter)?;
+        create_operator_account(
+            program_id,
+            casino_state_info.key,
+            authority_info,
+            legacy_operator_account_info,
+            system_program_info,
+            &OperatorState {
+                casino: *casino_state_info.key,
+                operator: *operator,
+                roles: ROLE_SETTLE,
+                daily_payout_cap: u64::MAX,
+                paid_today: 0,
+                payout_day: 0,
+            },
+        )?;
+    }
+    
+    CasinoEvent::CasinoMigrated(events::CasinoMigrated {
+        casino: *casino_state_info.key,
+        version: CasinoState::VERSION,
+    })
+    .emit()?;
+    
+    msg!("Casino state migrated to version {}", CasinoState::VERSION);
+    
+    Ok(())
+}
+
+// Process FundVault instruction
+fn process_fund_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let funder_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the funder signed the transaction
+    if !funder_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    if amount == 0 {
+        return Err(CasinoError::InvalidBetAmount.into());
+    }
+    
+    // Make sure the casino exists and the vault belongs to it
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    match casino_state.mint {
+        Some(mint) => {
+            let funder_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            transfer_tokens(
+                token_program_info,
+                funder_token_info,
+                vault_token_info,
+                funder_info,
+                amount,
+                &[],
+            )?;
+        },
+        None => {
+            invoke(
+                &system_instruction::transfer(funder_info.key, vault_info.key, amount),
+                &[
+                    funder_info.clone(),
+                    vault_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        },
     }
     
+    CasinoEvent::VaultFunded(events::VaultFunded {
+        casino: *casino_state_info.key,
+        funder: *funder_info.key,
+        amount,
+    })
+    .emit()?;
+    
+    msg!("Vault funded: {} lamports", amount);
+    
     Ok(())
 }
 
+// Process WithdrawFromVault instruction
+fn process_withdraw_from_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Only the casino authority may take money out of the house bankroll
+    if *authority_info.key != casino_state.authority {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    match casino_state.mint {
+        Some(mint) => {
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            // The stakes of open bets stay in the vault
+            let vault_token = spl_token::state::Account::unpack(&vault_token_info.data.borrow())?;
+            if vault_token.amount.saturating_sub(casino_state.liabilities) < amount {
+                return Err(CasinoError::InsufficientFunds.into());
+            }
+            
+            pay_tokens_from_vault(
+                program_id,
+                casino_state_info.key,
+                vault_info,
+                vault_token_info,
+                recipient_info,
+                token_program_info,
+                amount,
+            )?;
+        },
+        None => {
+            // The vault must stay rent exempt and keep the stakes of open bets
+            if vault_available_balance(vault_info)?.saturating_sub(casino_state.liabilities) < amount {
+                return Err(CasinoError::InsufficientFunds.into());
+            }
+            
+            **vault_info.try_borrow_mut_lamports()? -= amount;
+            **recipient_info.try_borrow_mut_lamports()? += amount;
+        },
+    }
+    
+    CasinoEvent::VaultWithdrawn(events::VaultWithdrawn {
+        casino: *casino_state_info.key,
+        recipient: *recipient_info.key,
+        amount,
+    })
+    .emit()?;
+    
+    msg!("Vault withdrawal: {} lamports to {}", amount, recipient_info.key);
+    
+    Ok(())
+}
+
+// Process CloseGame instruction
+fn process_close_game(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let game_info = next_account_info(account_info_iter)?;
+    let player_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize game state
+    let game_state = load_game_state(program_id, casino_state_info.key, game_info)?;
+    
+    // Open games still hold a pending outcome
+    if !game_state.is_settled {
+        return Err(CasinoError::GameNotSettled.into());
+    }
+    
+    // The rent goes back to the player who paid for the account
+    if *player_info.key != game_state.player {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let (refund, excess) = close_game_account(game_info, player_info, vault_info)?;
+    
+    CasinoEvent::GameClosed(events::GameClosed {
+        casino: *casino_state_info.key,
+        game: *game_info.key,
+        player: game_state.player,
+    })
+    .emit()?;
+    
+    msg!("Game closed: {} lamports refunded, {} lamports swept to the vault", refund, excess);
+    
+    Ok(())
+}
+
+// Process ClaimRefund and CancelCoinflipChallenge instructions
+fn process_claim_refund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cancel: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let game_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize game state
+    let game_state = load_game_state(program_id, casino_state_info.key, game_info)?;
+    
+    // Only the player who placed the bet can reclaim it
+    if *player_info.key != game_state.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Settled games have already been paid out
+    if game_state.is_settled {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    if cancel {
+        // Only a challenge nobody has matched can be called off
+        let is_open = matches!(game_state.game_type, GameType::CoinflipChallenge { .. })
+            && game_state.opponent().is_none();
+        if !is_open {
+            return Err(CasinoError::ChallengeNotOpen.into());
+        }
+    } else {
+        // The operators get the full timeout to settle the game
+        if Clock::get()?.unix_timestamp < game_state.refundable_at {
+            return Err(CasinoError::SettleTimeoutNotReached.into());
+        }
+    }
+    
+    // A bet against the house is refunded the most it could have won, so the
+    // operators can't profit by leaving the games the house loses unsettled.
+    // The players of a challenge only get their stakes back: the house has
+    // nothing riding on it.
+    let max_payout = match game_state.game_type {
+        GameType::CoinflipChallenge { .. } => game_state.bet_amount,
+        _ => game_state.max_payout().ok_or(ProgramError::ArithmeticOverflow)?,
+    };
+    let amount;
+    
+    // Pay the refunds from the house vault: the player's, and the opponent's
+    // stake of an accepted challenge
+    let mut refunds = vec![(player_info, game_state.player)];
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            let vault_token = spl_token::state::Account::unpack(&vault_token_info.data.borrow())?;
+            amount = casino_state.timeout_refund(game_state.bet_amount, max_payout, vault_token.amount);
+            
+            refunds[0].0 = player_token_info;
+            if let Some(opponent) = game_state.opponent() {
+                let opponent_info = next_account_info(account_info_iter)?;
+                if *opponent_info.key != opponent {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                refunds.push((next_account_info(account_info_iter)?, opponent));
+            }
+            
+            for (token_info, owner) in &refunds {
+                // A stake may only go back to a token account its owner owns
+                let token_account = spl_token::state::Account::unpack(&token_info.data.borrow())?;
+                if token_account.owner != *owner {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                
+                pay_tokens_from_vault(
+                    program_id,
+                    casino_state_info.key,
+                    vault_info,
+                    vault_token_info,
+                    token_info,
+                    token_program_info,
+                    amount,
+                )?;
+            }
+        },
+        None => {
+            if let Some(opponent) = game_state.opponent() {
+                let opponent_info = next_account_info(account_info_iter)?;
+                if *opponent_info.key != opponent {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                refunds.push((opponent_info, opponent));
+            }
+            
+            let available = vault_available_balance(vault_info)?;
+            amount = casino_state.timeout_refund(game_state.bet_amount, max_payout, available);
+            
+            let total = amount
+                .checked_mul(refunds.len() as u64)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if available < total {
+                return Err(CasinoError::InsufficientFunds.into());
+            }
+            
+            for (recipient_info, _) in &refunds {
+                **vault_info.try_borrow_mut_lamports()? -= amount;
+                **recipient_info.try_borrow_mut_lamports()? += amount;
+            }
+        },
+    }
+    
+    close_game_account(game_info, player_info, vault_info)?;
+    
+    casino_state.release(game_state.escrowed().ok_or(ProgramError::ArithmeticOverflow)?)?;
+    casino_state.store(casino_state_info)?;
+    
+    for (_, owner) in &refunds {
+        CasinoEvent::RefundClaimed(events::RefundClaimed {
+            casino: *casino_state_info.key,
+            game: *game_info.key,
+            player: *owner,
+            amount,
+        })
+        .emit()?;
+    }
+    
+    if cancel {
+        msg!("Challenge cancelled: {} returned to {}", amount, player_info.key);
+    } else {
+        msg!("Refund claimed: {} returned to {}", amount, player_info.key);
+    }
+    
+    Ok(())
+}
+
+// Process AcceptCoinflipChallenge instruction
+fn process_accept_coinflip_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let opponent_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let game_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let slot_hashes_info = next_account_info(account_info_iter)?;
+    
+    // Check that the opponent signed the transaction
+    if !opponent_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.betting_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize game state
+    let mut game_state = load_game_state(program_id, casino_state_info.key, game_info)?;
+    
+    // The game must be a challenge still waiting for an opponent
+    let side = match game_state.game_type {
+        GameType::CoinflipChallenge { side, .. } if game_state.opponent().is_none() && !game_state.is_settled => side,
+        _ => return Err(CasinoError::ChallengeNotOpen.into()),
+    };
+    
+    // Players can't take the other side of their own challenge
+    if *opponent_info.key == game_state.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    let stake = game_state.bet_amount;
+    if casino_state.mint.is_none() && opponent_info.lamports() < stake {
+        return Err(CasinoError::InsufficientFunds.into());
+    }
+    
+    // Escrow the matching stake in the house vault
+    match casino_state.mint {
+        Some(mint) => {
+            let opponent_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            transfer_tokens(
+                token_program_info,
+                opponent_token_info,
+                vault_token_info,
+                opponent_info,
+                stake,
+                &[],
+            )?;
+        },
+        None => {
+            invoke(
+                &system_instruction::transfer(opponent_info.key, vault_info.key, stake),
+                &[
+                    opponent_info.clone(),
+                    vault_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        },
+    }
+    
+    // Withdrawals must leave the stake in the vault until it is paid out
+    casino_state.escrow(stake)?;
+    casino_state.store(casino_state_info)?;
+    
+    // The latest slot hash goes into the flip with the challenger's seed, and
+    // the operators' settlement timeout starts over now that the game is on
+    let slot_hash = latest_slot_hash(slot_hashes_info)?;
+    game_state.game_type = GameType::CoinflipChallenge { side, opponent: *opponent_info.key };
+    game_state.client_seed = randomness::combine_seeds(&game_state.client_seed, &slot_hash);
+    game_state.created_at = Clock::get()?.unix_timestamp;
+    game_state.refundable_at = game_state.created_at
+        .checked_add(casino_state.settle_timeout)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    game_state.store(game_info)?;
+    
+    CasinoEvent::ChallengeAccepted(events::ChallengeAccepted {
+        casino: *casino_state_info.key,
+        game: *game_info.key,
+        player: game_state.player,
+        opponent: *opponent_info.key,
+        stake,
+        slot_hash,
+    })
+    .emit()?;
+    
+    msg!("Challenge accepted: {} staked by {}", stake, opponent_info.key);
+    
+    Ok(())
+}
+
+// Process OpenCrashRound instruction
+fn process_open_crash_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    round_id: [u8; 32],
+    server_seed_hash: [u8; 32],
+    starts_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Players need a window to join before the multiplier starts climbing
+    if starts_at <= Clock::get()?.unix_timestamp {
+        return Err(CasinoError::BettingClosed.into());
+    }
+    
+    // The round account is derived from the casino and the round ID
+    let (round_pubkey, round_bump_seed) = find_crash_round_address(program_id, casino_state_info.key, &round_id);
+    if round_pubkey != *round_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let round = CrashRound {
+        casino: *casino_state_info.key,
+        round_id,
+        operator: *authority_info.key,
+        server_seed_hash,
+        house_edge: casino_state.house_edge,
+        starts_at,
+        refundable_at: starts_at
+            .checked_add(casino_state.settle_timeout)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        open_bets: 0,
+        is_settled: false,
+        crash_point: 0,
+        result_hash: [0; 32],
+        server_seed: [0; 32],
+        bump: round_bump_seed,
+    };
+    
+    // Create the round account
+    let space = round.to_account_data()?.len();
+    
+    create_pda_account(
+        program_id,
+        authority_info,
+        round_info,
+        system_program_info,
+        space,
+        &[
+            b"crash_round",
+            casino_state_info.key.as_ref(),
+            &round_id,
+            &[round_bump_seed],
+        ],
+    )?;
+    
+    round.store(round_info)?;
+    
+    CasinoEvent::CrashRoundOpened(events::CrashRoundOpened {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        operator: *authority_info.key,
+        round_id,
+        server_seed_hash,
+        house_edge: round.house_edge,
+        starts_at,
+    })
+    .emit()?;
+    
+    msg!("Crash round opened, starting at {}", starts_at);
+    
+    Ok(())
+}
+
+// Process JoinCrashRound instruction
+fn process_join_crash_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bet_amount: u64,
+    auto_cashout: Option<u64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let bet_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.betting_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_crash_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Bets are only taken until the multiplier starts climbing
+    if Clock::get()?.unix_timestamp >= round.starts_at {
+        return Err(CasinoError::BettingClosed.into());
+    }
+    
+    if auto_cashout.is_some_and(|multiplier| !crash::is_valid_cashout(multiplier)) {
+        return Err(CasinoError::InvalidCashout.into());
+    }
+    
+    // Validate bet amount
+    if bet_amount < casino_state.min_bet || bet_amount > casino_state.max_bet {
+        return Err(CasinoError::InvalidBetAmount.into());
+    }
+    
+    // Check if player has enough funds
+    if casino_state.mint.is_none() && player_info.lamports() < bet_amount {
+        return Err(CasinoError::InsufficientFunds.into());
+    }
+    
+    // The bet account is derived from the round and the player
+    let (bet_pubkey, bet_bump_seed) = find_crash_bet_address(program_id, round_info.key, player_info.key);
+    if bet_pubkey != *bet_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let bet = CrashBet {
+        round: *round_info.key,
+        player: *player_info.key,
+        bet_amount,
+        auto_cashout,
+        cashed_out_at: 0,
+        cashed_out_time: 0,
+        bump: bet_bump_seed,
+    };
+    
+    // Create the bet account
+    let space = bet.to_account_data()?.len();
+    
+    create_pda_account(
+        program_id,
+        player_info,
+        bet_info,
+        system_program_info,
+        space,
+        &[
+            b"crash_bet",
+            round_info.key.as_ref(),
+            player_info.key.as_ref(),
+            &[bet_bump_seed],
+        ],
+    )?;
+    
+    bet.store(bet_info)?;
+    
+    // Escrow the bet in the house vault
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            transfer_tokens(
+                token_program_info,
+                player_token_info,
+                vault_token_info,
+                player_info,
+                bet_amount,
+                &[],
+            )?;
+        },
+        None => {
+            invoke(
+                &system_instruction::transfer(player_info.key, vault_info.key, bet_amount),
+                &[
+                    player_info.clone(),
+                    vault_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        },
+    }
+    
+    // Withdrawals must leave the stake in the vault until it is paid out
+    casino_state.escrow(bet_amount)?;
+    casino_state.store(casino_state_info)?;
+    
+    round.open_bets = round.open_bets
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    round.store(round_info)?;
+    
+    CasinoEvent::CrashBetPlaced(events::CrashBetPlaced {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        bet: *bet_info.key,
+        player: *player_info.key,
+        round_id: round.round_id,
+        bet_amount,
+        auto_cashout,
+    })
+    .emit()?;
+    
+    msg!("Crash bet placed: {} lamports", bet_amount);
+    
+    Ok(())
+}
+
+// Process CashOut instruction
+fn process_cash_out(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    multiplier: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let bet_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    let round = load_crash_round(program_id, round_info)?;
+    let mut bet = load_crash_bet(program_id, round_info.key, bet_info)?;
+    
+    // Only the player who placed the bet can cash it out
+    if *player_info.key != bet.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    if round.is_settled {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    if now < round.starts_at {
+        return Err(CasinoError::RoundNotStarted.into());
+    }
+    
+    if !crash::is_valid_cashout(multiplier) {
+        return Err(CasinoError::InvalidCashout.into());
+    }
+    
+    if bet.cashed_out_at != 0 {
+        return Err(CasinoError::AlreadyCashedOut.into());
+    }
+    
+    // Whether the round crashed below the multiplier only comes out when the
+    // seed is revealed; until then it only has to have been reached
+    if crash::multiplier_at(now - round.starts_at) < multiplier {
+        return Err(CasinoError::CashoutNotReached.into());
+    }
+    
+    bet.cashed_out_at = multiplier;
+    bet.cashed_out_time = now;
+    bet.store(bet_info)?;
+    
+    CasinoEvent::CashedOut(events::CashedOut {
+        casino: round.casino,
+        round: *round_info.key,
+        bet: *bet_info.key,
+        player: bet.player,
+        multiplier,
+        time: now,
+    })
+    .emit()?;
+    
+    msg!("Cashed out at {}.{:02}x", multiplier / crash::ONE_X, multiplier % crash::ONE_X / 100);
+    
+    Ok(())
+}
+
+// Process SettleCrashRound instruction
+fn process_settle_crash_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    server_seed: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.settlement_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_crash_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Only an operator allowed to settle may reveal the seed. Once it is
+    // revealed every payout is fixed, so anyone may settle the bets left, and
+    // they count against the cap of the operator who opened the round. If
+    // that operator has been removed the bets wait until it is added back.
+    let mut operator_state = match round.is_settled {
+        true => load_operator_account(program_id, casino_state_info.key, &round.operator, operator_account_info)?,
+        false => authorize_operator(
+            program_id,
+            casino_state_info.key,
+            authority_info,
+            operator_account_info,
+            ROLE_SETTLE,
+        )?,
+    };
+    
+    // The revealed seed must open the commitment made when the round opened
+    if randomness::commit(&server_seed) != round.server_seed_hash {
+        return Err(CasinoError::InvalidServerSeed.into());
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    
+    // The first settlement reveals the crash point. No player seed goes into
+    // a shared round; the commitment made before betting opened keeps it fair.
+    if !round.is_settled {
+        let result_hash = randomness::result_hash(&server_seed, &round.round_id, round_info.key);
+        let crash_point = crash::crash_point(&result_hash, round.house_edge);
+        
+        // Players may cash out until the multiplier reaches the crash point
+        let crashed_at = round.crashes_at(crash_point).ok_or(ProgramError::ArithmeticOverflow)?;
+        if now < crashed_at {
+            return Err(CasinoError::RoundNotOver.into());
+        }
+        
+        round.is_settled = true;
+        round.crash_point = crash_point;
+        round.result_hash = result_hash;
+        round.server_seed = server_seed;
+        
+        CasinoEvent::CrashRoundSettled(events::CrashRoundSettled {
+            casino: *casino_state_info.key,
+            round: *round_info.key,
+            operator: *authority_info.key,
+            crash_point,
+            result_hash,
+        })
+        .emit()?;
+        
+        msg!("Round crashed at {}.{:02}x", crash_point / crash::ONE_X, crash_point % crash::ONE_X / 100);
+    }
+    
+    // A cash-out by hand only counts if it came before the crash
+    let crashed_at = round.crashes_at(round.crash_point).ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // Token casinos pay out of the vault's token account
+    let token_accounts = match casino_state.mint {
+        Some(mint) => {
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            Some((vault_token_info, token_program_info))
+        },
+        None => None,
+    };
+    
+    // Settle every bet passed after the fixed accounts
+    let mut paid_out = false;
+    while let Some(bet_info) = account_info_iter.next() {
+        let player_info = next_account_info(account_info_iter)?;
+        let bet = load_crash_bet(program_id, round_info.key, bet_info)?;
+        
+        // The player's account gets the bet account's rent back
+        if *player_info.key != bet.player {
+            return Err(ProgramError::InvalidArgument);
+        }
+        
+        let cashed_out = (bet.cashed_out_at != 0 && bet.cashed_out_time < crashed_at).then_some(bet.cashed_out_at);
+        let cashout = crash::cashout(bet.auto_cashout, cashed_out);
+        let win_amount = crash::payout(bet.bet_amount, cashout, round.crash_point)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        
+        if win_amount > 0 {
+            // Count the win against the operator's daily payout cap
+            operator_state.record_payout(win_amount, now)?;
+            paid_out = true;
+            
+            match token_accounts {
+                Some((vault_token_info, token_program_info)) => {
+                    let player_token_info = next_account_info(account_info_iter)?;
+                    
+                    // Winnings may only go to a token account the player owns
+                    let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+                    if player_token.owner != bet.player {
+                        return Err(ProgramError::InvalidArgument);
+                    }
+                    
+                    pay_tokens_from_vault(
+                        program_id,
+                        casino_state_info.key,
+                        vault_info,
+                        vault_token_info,
+                        player_token_info,
+                        token_program_info,
+                        win_amount,
+                    )?;
+                },
+                None => {
+                    if vault_available_balance(vault_info)? < win_amount {
+                        return Err(CasinoError::InsufficientFunds.into());
+                    }
+                    
+                    **vault_info.try_borrow_mut_lamports()? -= win_amount;
+                    **player_info.try_borrow_mut_lamports()? += win_amount;
+                },
+            }
+        } else if token_accounts.is_some() {
+            // Losing bets still pass their token account to keep the layout
+            next_account_info(account_info_iter)?;
+        }
+        
+        close_game_account(bet_info, player_info, vault_info)?;
+        round.open_bets = round.open_bets.saturating_sub(1);
+        casino_state.release(bet.bet_amount)?;
+        
+        CasinoEvent::CrashBetSettled(events::CrashBetSettled {
+            casino: *casino_state_info.key,
+            round: *round_info.key,
+            bet: *bet_info.key,
+            player: bet.player,
+            operator: *authority_info.key,
+            bet_amount: bet.bet_amount,
+            cashout,
+            win_amount,
+        })
+        .emit()?;
+    }
+    
+    if paid_out {
+        operator_state.store(operator_account_info)?;
+    }
+    round.store(round_info)?;
+    casino_state.store(casino_state_info)?;
+    
+    msg!("Crash round settled: {} bets left open", round.open_bets);
+    
+    Ok(())
+}
+
+// Process ClaimCrashRefund instruction
+fn process_claim_crash_refund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let bet_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_crash_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let bet = load_crash_bet(program_id, round_info.key, bet_info)?;
+    
+    // Only the player who placed the bet can reclaim it
+    if *player_info.key != bet.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // A settled round's bets are paid by the operators
+    if round.is_settled {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    // The operators get the full timeout from the start of the round to settle it
+    if Clock::get()?.unix_timestamp < round.refundable_at {
+        return Err(CasinoError::SettleTimeoutNotReached.into());
+    }
+    
+    // Pay what the bet could have won at most from the house vault, so the
+    // operators can't profit by leaving the rounds the house loses unsettled
+    let max_payout = bet.max_payout().ok_or(ProgramError::ArithmeticOverflow)?;
+    let amount;
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            let vault_token = spl_token::state::Account::unpack(&vault_token_info.data.borrow())?;
+            amount = casino_state.timeout_refund(bet.bet_amount, max_payout, vault_token.amount);
+            
+            // The stake may only go back to a token account the player owns
+            let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+            if player_token.owner != bet.player {
+                return Err(ProgramError::InvalidArgument);
+            }
+            
+            pay_tokens_from_vault(
+                program_id,
+                casino_state_info.key,
+                vault_info,
+                vault_token_info,
+                player_token_info,
+                token_program_info,
+                amount,
+            )?;
+        },
+        None => {
+            let available = vault_available_balance(vault_info)?;
+            amount = casino_state.timeout_refund(bet.bet_amount, max_payout, available);
+            if available < amount {
+                return Err(CasinoError::InsufficientFunds.into());
+            }
+            
+            **vault_info.try_borrow_mut_lamports()? -= amount;
+            **player_info.try_borrow_mut_lamports()? += amount;
+        },
+    }
+    
+    close_game_account(bet_info, player_info, vault_info)?;
+    
+    round.open_bets = round.open_bets.saturating_sub(1);
+    round.store(round_info)?;
+    
+    casino_state.release(bet.bet_amount)?;
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::RefundClaimed(events::RefundClaimed {
+        casino: *casino_state_info.key,
+        game: *bet_info.key,
+        player: bet.player,
+        amount,
+    })
+    .emit()?;
+    
+    msg!("Refund claimed: {} returned to {}", amount, player_info.key);
+    
+    Ok(())
+}
+
+// Process OpenRound instruction
+fn process_open_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    round_id: [u8; 32],
+    server_seed_hash: [u8; 32],
+    betting_ends_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Players need a window to bet in
+    if betting_ends_at <= Clock::get()?.unix_timestamp {
+        return Err(CasinoError::BettingClosed.into());
+    }
+    
+    // The round account is derived from the casino and the round ID
+    let (round_pubkey, round_bump_seed) = find_round_address(program_id, casino_state_info.key, &round_id);
+    if round_pubkey != *round_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let round = Round {
+        casino: *casino_state_info.key,
+        round_id,
+        operator: *authority_info.key,
+        server_seed_hash,
+        betting_ends_at,
+        refundable_at: betting_ends_at
+            .checked_add(casino_state.settle_timeout)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        open_bets: 0,
+        is_locked: false,
+        is_spun: false,
+        winning_slot: 0,
+        result_hash: [0; 32],
+        server_seed: [0; 32],
+        bump: round_bump_seed,
+    };
+    
+    // Create the round account
+    let space = round.to_account_data()?.len();
+    
+    create_pda_account(
+        program_id,
+        authority_info,
+        round_info,
+        system_program_info,
+        space,
+        &[
+            b"round",
+            casino_state_info.key.as_ref(),
+            &round_id,
+            &[round_bump_seed],
+        ],
+    )?;
+    
+    round.store(round_info)?;
+    
+    CasinoEvent::RoundOpened(events::RoundOpened {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        operator: *authority_info.key,
+        round_id,
+        server_seed_hash,
+        betting_ends_at,
+    })
+    .emit()?;
+    
+    msg!("Roulette round opened, betting until {}", betting_ends_at);
+    
+    Ok(())
+}
+
+// Process PlaceRoundBet instruction
+fn process_place_round_bet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    color: RouletteColor,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let bet_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.betting_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if round.is_locked || Clock::get()?.unix_timestamp >= round.betting_ends_at {
+        return Err(CasinoError::BettingClosed.into());
+    }
+    
+    // Validate bet amount
+    if amount < casino_state.min_bet || amount > casino_state.max_bet {
+        return Err(CasinoError::InvalidBetAmount.into());
+    }
+    
+    // Check if player has enough funds
+    if casino_state.mint.is_none() && player_info.lamports() < amount {
+        return Err(CasinoError::InsufficientFunds.into());
+    }
+    
+    // The bet account is derived from the round and the player
+    let (bet_pubkey, bet_bump_seed) = find_round_bet_address(program_id, round_info.key, player_info.key);
+    if bet_pubkey != *bet_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // The player's first bet in the round creates their bet account
+    let mut bet = if bet_info.data_is_empty() {
+        let bet = RoundBet {
+            round: *round_info.key,
+            player: *player_info.key,
+            red: 0,
+            black: 0,
+            green: 0,
+            bump: bet_bump_seed,
+        };
+        
+        let space = bet.to_account_data()?.len();
+        
+        create_pda_account(
+            program_id,
+            player_info,
+            bet_info,
+            system_program_info,
+            space,
+            &[
+                b"round_bet",
+                round_info.key.as_ref(),
+                player_info.key.as_ref(),
+                &[bet_bump_seed],
+            ],
+        )?;
+        
+        round.open_bets = round.open_bets
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        bet
+    } else {
+        load_round_bet(program_id, round_info.key, bet_info)?
+    };
+    
+    let stake = bet.stake_mut(color);
+    *stake = stake.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // The maximum bet caps a player's stakes in the round together
+    let total_stake = bet.total_stake().ok_or(ProgramError::ArithmeticOverflow)?;
+    if total_stake > casino_state.max_bet {
+        return Err(CasinoError::InvalidBetAmount.into());
+    }
+    
+    bet.store(bet_info)?;
+    
+    // Escrow the bet in the house vault
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            transfer_tokens(
+                token_program_info,
+                player_token_info,
+                vault_token_info,
+                player_info,
+                amount,
+                &[],
+            )?;
+        },
+        None => {
+            invoke(
+                &system_instruction::transfer(player_info.key, vault_info.key, amount),
+                &[
+                    player_info.clone(),
+                    vault_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        },
+    }
+    
+    // Withdrawals must leave the stake in the vault until it is paid out
+    casino_state.escrow(amount)?;
+    casino_state.store(casino_state_info)?;
+    
+    round.store(round_info)?;
+    
+    CasinoEvent::RoundBetPlaced(events::RoundBetPlaced {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        bet: *bet_info.key,
+        player: *player_info.key,
+        round_id: round.round_id,
+        color,
+        amount,
+    })
+    .emit()?;
+    
+    msg!("Round bet placed: {} lamports on {:?}", amount, color);
+    
+    Ok(())
+}
+
+// Process LockRound instruction
+fn process_lock_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if round.is_locked {
+        return Err(CasinoError::BettingClosed.into());
+    }
+    
+    round.is_locked = true;
+    round.store(round_info)?;
+    
+    CasinoEvent::RoundLocked(events::RoundLocked {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        operator: *authority_info.key,
+        open_bets: round.open_bets,
+    })
+    .emit()?;
+    
+    msg!("Roulette round locked with {} bets", round.open_bets);
+    
+    Ok(())
+}
+
+// Process SpinRound instruction
+fn process_spin_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    server_seed: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.settlement_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if round.is_spun {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    // Every bet has to be in before the outcome is known: the round has been
+    // locked, or betting has run out on its own
+    if !round.is_locked && Clock::get()?.unix_timestamp < round.betting_ends_at {
+        return Err(CasinoError::RoundNotLocked.into());
+    }
+    
+    // The revealed seed must open the commitment made when the round opened
+    if randomness::commit(&server_seed) != round.server_seed_hash {
+        return Err(CasinoError::InvalidServerSeed.into());
+    }
+    
+    // As in crash, no player seed goes into a shared round
+    let result_hash = randomness::result_hash(&server_seed, &round.round_id, round_info.key);
+    let winning_slot = roulette::winning_slot(&result_hash);
+    
+    round.is_locked = true;
+    round.is_spun = true;
+    round.winning_slot = winning_slot;
+    round.result_hash = result_hash;
+    round.server_seed = server_seed;
+    round.store(round_info)?;
+    
+    CasinoEvent::RoundSpun(events::RoundSpun {
+        casino: *casino_state_info.key,
+        round: *round_info.key,
+        operator: *authority_info.key,
+        winning_slot,
+        result_hash,
+    })
+    .emit()?;
+    
+    msg!("Roulette landed on slot {}", winning_slot);
+    
+    Ok(())
+}
+
+// Process SettleRoundBets instruction
+fn process_settle_round_bets(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.settlement_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if !round.is_spun {
+        return Err(CasinoError::RoundNotSpun.into());
+    }
+    
+    // The spin fixed every payout, so anyone may settle the bets; they count
+    // against the cap of the operator who opened the round, and wait for it
+    // to be added back if it has been removed
+    let mut operator_state =
+        load_operator_account(program_id, casino_state_info.key, &round.operator, operator_account_info)?;
+    
+    // Token casinos pay out of the vault's token account
+    let token_accounts = match casino_state.mint {
+        Some(mint) => {
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            Some((vault_token_info, token_program_info))
+        },
+        None => None,
+    };
+    
+    // Settle every bet passed after the fixed accounts
+    let now = Clock::get()?.unix_timestamp;
+    let mut paid_out = false;
+    while let Some(bet_info) = account_info_iter.next() {
+        let player_info = next_account_info(account_info_iter)?;
+        let bet = load_round_bet(program_id, round_info.key, bet_info)?;
+        
+        // The player's account gets the bet account's rent back
+        if *player_info.key != bet.player {
+            return Err(ProgramError::InvalidArgument);
+        }
+        
+        let bet_amount = bet.total_stake().ok_or(ProgramError::ArithmeticOverflow)?;
+        let win_amount = bet.payout(&round.result_hash).ok_or(ProgramError::ArithmeticOverflow)?;
+        
+        if win_amount > 0 {
+            // Count the win against the operator's daily payout cap
+            operator_state.record_payout(win_amount, now)?;
+            paid_out = true;
+            
+            match token_accounts {
+                Some((vault_token_info, token_program_info)) => {
+                    let player_token_info = next_account_info(account_info_iter)?;
+                    
+                    // Winnings may only go to a token account the player owns
+                    let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+                    if player_token.owner != bet.player {
+                        return Err(ProgramError::InvalidArgument);
+                    }
+                    
+                    pay_tokens_from_vault(
+                        program_id,
+                        casino_state_info.key,
+                        vault_info,
+                        vault_token_info,
+                        player_token_info,
+                        token_program_info,
+                        win_amount,
+                    )?;
+                },
+                None => {
+                    if vault_available_balance(vault_info)? < win_amount {
+                        return Err(CasinoError::InsufficientFunds.into());
+                    }
+                    
+                    **vault_info.try_borrow_mut_lamports()? -= win_amount;
+                    **player_info.try_borrow_mut_lamports()? += win_amount;
+                },
+            }
+        } else if token_accounts.is_some() {
+            // Losing bets still pass their token account to keep the layout
+            next_account_info(account_info_iter)?;
+        }
+        
+        close_game_account(bet_info, player_info, vault_info)?;
+        round.open_bets = round.open_bets.saturating_sub(1);
+        casino_state.release(bet_amount)?;
+        
+        CasinoEvent::RoundBetSettled(events::RoundBetSettled {
+            casino: *casino_state_info.key,
+            round: *round_info.key,
+            bet: *bet_info.key,
+            player: bet.player,
+            operator: *authority_info.key,
+            bet_amount,
+            win_amount,
+        })
+        .emit()?;
+    }
+    
+    if paid_out {
+        operator_state.store(operator_account_info)?;
+    }
+    round.store(round_info)?;
+    casino_state.store(casino_state_info)?;
+    
+    msg!("Roulette round bets settled: {} left open", round.open_bets);
+    
+    Ok(())
+}
+
+// Process ClaimRoundRefund instruction
+fn process_claim_round_refund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let bet_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Deserialize the round, which must belong to this casino
+    let mut round = load_round(program_id, round_info)?;
+    if round.casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let bet = load_round_bet(program_id, round_info.key, bet_info)?;
+    
+    // Only the player who placed the bets can reclaim them
+    if *player_info.key != bet.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // A spun round's bets are paid by the operators
+    if round.is_spun {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    // The operators get the full timeout from the end of betting to spin it
+    if Clock::get()?.unix_timestamp < round.refundable_at {
+        return Err(CasinoError::SettleTimeoutNotReached.into());
+    }
+    
+    let stake = bet.total_stake().ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // Pay what the bets could have won at most from the house vault, so the
+    // operators can't profit by leaving the rounds the house loses unspun
+    let max_payout = bet.max_payout().ok_or(ProgramError::ArithmeticOverflow)?;
+    let amount;
+    match casino_state.mint {
+        Some(mint) => {
+            let player_token_info = next_account_info(account_info_iter)?;
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            
+            let vault_token = spl_token::state::Account::unpack(&vault_token_info.data.borrow())?;
+            amount = casino_state.timeout_refund(stake, max_payout, vault_token.amount);
+            
+            // The stakes may only go back to a token account the player owns
+            let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+            if player_token.owner != bet.player {
+                return Err(ProgramError::InvalidArgument);
+            }
+            
+            pay_tokens_from_vault(
+                program_id,
+                casino_state_info.key,
+                vault_info,
+                vault_token_info,
+                player_token_info,
+                token_program_info,
+                amount,
+            )?;
+        },
+        None => {
+            let available = vault_available_balance(vault_info)?;
+            amount = casino_state.timeout_refund(stake, max_payout, available);
+            if available < amount {
+                return Err(CasinoError::InsufficientFunds.into());
+            }
+            
+            **vault_info.try_borrow_mut_lamports()? -= amount;
+            **player_info.try_borrow_mut_lamports()? += amount;
+        },
+    }
+    
+    close_game_account(bet_info, player_info, vault_info)?;
+    
+    round.open_bets = round.open_bets.saturating_sub(1);
+    round.store(round_info)?;
+    
+    casino_state.release(stake)?;
+    casino_state.store(casino_state_info)?;
+    
+    CasinoEvent::RefundClaimed(events::RefundClaimed {
+        casino: *casino_state_info.key,
+        game: *bet_info.key,
+        player: bet.player,
+        amount,
+    })
+    .emit()?;
+    
+    msg!("Refund claimed: {} returned to {}", amount, player_info.key);
+    
+    Ok(())
+}
+
+// Process SettleBatch instruction
+fn process_settle_batch<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    results: Vec<SettleResult>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Deserialize casino state
+    let mut casino_state = load_casino_state(program_id, casino_state_info)?;
+    
+    if casino_state.settlement_paused {
+        return Err(CasinoError::Paused.into());
+    }
+    
+    // Check that the signer is an operator allowed to settle
+    let mut operator_state = authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // Verify the vault belongs to this casino
+    validate_vault(program_id, casino_state_info.key, vault_info)?;
+    
+    // Token casinos pay out of the vault's token account
+    let token_accounts = match casino_state.mint {
+        Some(mint) => {
+            let vault_token_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            validate_vault_token_account(vault_info.key, &mint, vault_token_info)?;
+            Some((vault_token_info, token_program_info))
+        },
+        None => None,
+    };
+    
+    // Every result needs its game and payee, and the payee's token account in
+    // token casinos
+    let stride = if token_accounts.is_some() { 3 } else { 2 };
+    let game_accounts = account_info_iter.as_slice();
+    if results.is_empty() || game_accounts.len() != results.len() * stride {
+        return Err(CasinoError::InvalidBatch.into());
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    
+    // Settles one game the way `SettleGame` does, returning the amount paid
+    // and the stakes it releases
+    let mut settle = |game: &[AccountInfo<'a>], result: &SettleResult| -> Result<(u64, u64), ProgramError> {
+        let game_info = &game[0];
+        let player_info = &game[1];
+        
+        let mut game_state = load_game_state(program_id, casino_state_info.key, game_info)?;
+        let (result_hash, payout) = check_settlement(
+            &game_state,
+            game_info.key,
+            player_info.key,
+            result.is_win,
+            result.win_amount,
+            &result.server_seed,
+        )?;
+        
+        if payout > 0 {
+            // Count the win against the operator's daily payout cap
+            operator_state.record_payout(payout, now)?;
+            
+            match token_accounts {
+                Some((vault_token_info, token_program_info)) => {
+                    let player_token_info = &game[2];
+                    
+                    // Winnings may only go to a token account the payee owns
+                    let player_token = spl_token::state::Account::unpack(&player_token_info.data.borrow())?;
+                    if player_token.owner != *player_info.key {
+                        return Err(ProgramError::InvalidArgument);
+                    }
+                    
+                    pay_tokens_from_vault(
+                        program_id,
+                        casino_state_info.key,
+                        vault_info,
+                        vault_token_info,
+                        player_token_info,
+                        token_program_info,
+                        payout,
+                    )?;
+                },
+                None => {
+                    if vault_available_balance(vault_info)? < payout {
+                        return Err(CasinoError::InsufficientFunds.into());
+                    }
+                    
+                    **vault_info.try_borrow_mut_lamports()? -= payout;
+                    **player_info.try_borrow_mut_lamports()? += payout;
+                },
+            }
+        }
+        
+        // Storing each game before the next makes a repeated game fail
+        game_state.is_settled = true;
+        game_state.is_win = result.is_win;
+        game_state.win_amount = payout;
+        game_state.result_hash = result_hash;
+        game_state.server_seed = result.server_seed;
+        game_state.store(game_info)?;
+        
+        CasinoEvent::GameSettled(events::GameSettled {
+            casino: *casino_state_info.key,
+            game: *game_info.key,
+            player: game_state.player,
+            operator: *authority_info.key,
+            game_id: game_state.game_id,
+            bet_amount: game_state.bet_amount,
+            is_win: result.is_win,
+            win_amount: payout,
+            result_hash,
+        })
+        .emit()?;
+        
+        Ok((payout, game_state.escrowed().ok_or(ProgramError::ArithmeticOverflow)?))
+    };
+    
+    // Any failure rolls back the whole batch; name the game that caused it
+    let mut total_paid: u64 = 0;
+    for (index, (game, result)) in game_accounts.chunks(stride).zip(&results).enumerate() {
+        let (paid, escrowed) = settle(game, result).map_err(|err| {
+            msg!("Batch game {} ({}) failed: {}", index, game[0].key, err);
+            err
+        })?;
+        total_paid = total_paid.checked_add(paid).ok_or(ProgramError::ArithmeticOverflow)?;
+        casino_state.release(escrowed)?;
+    }
+    casino_state.store(casino_state_info)?;
+    
+    if total_paid > 0 {
+        operator_state.store(operator_account_info)?;
+    }
+    
+    msg!("Batch settled: {} games, {} paid out", results.len(), total_paid);
+    
+    Ok(())
+}
+
+// Process PostCommitment instruction
+fn process_post_commitment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    server_seed_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let commitment_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // The commitment account is derived from the casino and the hash
+    let (commitment_pubkey, commitment_bump_seed) =
+        find_commitment_address(program_id, casino_state_info.key, &server_seed_hash);
+    if commitment_pubkey != *commitment_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let commitment = Commitment {
+        casino: *casino_state_info.key,
+        operator: *authority_info.key,
+        server_seed_hash,
+        bump: commitment_bump_seed,
+    };
+    
+    // Create the commitment account
+    let space = commitment.to_account_data()?.len();
+    
+    create_pda_account(
+        program_id,
+        authority_info,
+        commitment_info,
+        system_program_info,
+        space,
+        &[
+            b"commitment",
+            casino_state_info.key.as_ref(),
+            &server_seed_hash,
+            &[commitment_bump_seed],
+        ],
+    )?;
+    
+    commitment.store(commitment_info)?;
+    
+    CasinoEvent::CommitmentPosted(events::CommitmentPosted {
+        casino: *casino_state_info.key,
+        commitment: *commitment_info.key,
+        operator: *authority_info.key,
+        server_seed_hash,
+    })
+    .emit()?;
+    
+    msg!("Commitment posted");
+    
+    Ok(())
+}
+
+// Process CloseCrashRound and CloseRound instructions
+fn process_close_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    crash: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_info = next_account_info(account_info_iter)?;
+    let casino_state_info = next_account_info(account_info_iter)?;
+    let operator_account_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let round_operator_info = next_account_info(account_info_iter)?;
+    
+    // Check that the authority signed the transaction
+    if !authority_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    load_casino_state(program_id, casino_state_info)?;
+    
+    // Check that the signer is an operator allowed to settle
+    authorize_operator(
+        program_id,
+        casino_state_info.key,
+        authority_info,
+        operator_account_info,
+        ROLE_SETTLE,
+    )?;
+    
+    // What closing depends on: whether the seed was revealed, and when the
+    // bets became refundable if it wasn't
+    let (casino, operator, open_bets, is_revealed, refundable_at) = match crash {
+        true => {
+            let round = load_crash_round(program_id, round_info)?;
+            (round.casino, round.operator, round.open_bets, round.is_settled, round.refundable_at)
+        },
+        false => {
+            let round = load_round(program_id, round_info)?;
+            (round.casino, round.operator, round.open_bets, round.is_spun, round.refundable_at)
+        },
+    };
+    
+    // The round must belong to this casino
+    if casino != *casino_state_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    if open_bets != 0 {
+        return Err(CasinoError::RoundHasOpenBets.into());
+    }
+    
+    // Until the settlement timeout an unrevealed round may still take bets or
+    // be settled
+    if !is_revealed && Clock::get()?.unix_timestamp < refundable_at {
+        return Err(CasinoError::SettleTimeoutNotReached.into());
+    }
+    
+    // The rent goes back to the operator who paid for the account
+    if *round_operator_info.key != operator {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    close_account(round_info, round_operator_info)?;
+    
+    CasinoEvent::RoundClosed(events::RoundClosed {
+        casino,
+        round: *round_info.key,
+        operator,
+    })
+    .emit()?;
+    
+    msg!("Round closed");
+    
+    Ok(())
+}
+
+// Process ClaimLegacyRefund instruction
+fn process_claim_legacy_refund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_id: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let player_info = next_account_info(account_info_iter)?;
+    let game_info = next_account_info(account_info_iter)?;
+    
+    // Check that the player signed the transaction
+    if !player_info.is_signer {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    if game_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    
+    let (expected_game_pubkey, _) = find_legacy_game_address(program_id, &game_id);
+    if expected_game_pubkey != *game_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Deserialize the version 1 game state
+    let game_state = GameStateV1::try_from_slice(&game_info.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    
+    // Only the player who placed the bet can reclaim it
+    if *player_info.key != game_state.player {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    // Settled games have already been paid out
+    if game_state.is_settled {
+        return Err(CasinoError::GameAlreadySettled.into());
+    }
+    
+    // The account holds the stake on top of its rent
+    let amount = game_info.lamports();
+    close_account(game_info, player_info)?;
+    
+    msg!("Legacy refund claimed: {} lamports", amount);
+    
+    Ok(())
+}
+
+// Close a game account: its rent goes to the player, any other lamports to the
+// vault, and the wiped account is handed back to the system program.
+// Returns the refunded and swept amounts.
+fn close_game_account(
+    game_info: &AccountInfo,
+    player_info: &AccountInfo,
+    vault_info: &AccountInfo,
+) -> Result<(u64, u64), ProgramError> {
+    let rent = Rent::get()?;
+    let game_lamports = game_info.lamports();
+    let refund = game_lamports.min(rent.minimum_balance(game_info.data_len()));
+    let excess = game_lamports - refund;
+    
+    **game_info.try_borrow_mut_lamports()? = 0;
+    **player_info.try_borrow_mut_lamports()? += refund;
+    **vault_info.try_borrow_mut_lamports()? += excess;
+    
+    game_info.data.borrow_mut().fill(0);
+    game_info.realloc(0, false)?;
+    game_info.assign(&system_program::id());
+    
+    Ok((refund, excess))
+}
+
+// Close a program-owned account, sending all of its lamports to `recipient_info`
+fn close_account(
+    account_info: &AccountInfo,
+    recipient_info: &AccountInfo,
+) -> ProgramResult {
+    let lamports = account_info.lamports();
+    **account_info.try_borrow_mut_lamports()? = 0;
+    **recipient_info.try_borrow_mut_lamports()? += lamports;
+    
+    account_info.data.borrow_mut().fill(0);
+    account_info.realloc(0, false)?;
+    account_info.assign(&system_program::id());
+    
+    Ok(())
+}
+
+/// Derives the commitment account address for a server seed hash of a casino
+pub fn find_commitment_address(program_id: &Pubkey, casino_state: &Pubkey, server_seed_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"commitment", casino_state.as_ref(), server_seed_hash], program_id)
+}
+
+// Load the commitment account of a server seed hash, checking its address and
+// that an operator posted it
+fn load_commitment(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    server_seed_hash: &[u8; 32],
+    commitment_info: &AccountInfo,
+) -> Result<Commitment, ProgramError> {
+    let (expected_commitment_pubkey, _) = find_commitment_address(program_id, casino_state, server_seed_hash);
+    if expected_commitment_pubkey != *commitment_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // A commitment never posted, or already bet against, has no account
+    if commitment_info.owner != program_id {
+        return Err(CasinoError::CommitmentNotPosted.into());
+    }
+    
+    Commitment::from_account_data(&commitment_info.data.borrow())
+}
+
+/// Derives the operator account address for an operator key of a casino
+pub fn find_operator_address(program_id: &Pubkey, casino_state: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"operator", casino_state.as_ref(), operator.as_ref()], program_id)
+}
+
+// Create a rent-exempt program account of `space` bytes at the PDA signed for
+// by `seeds`, paid for by `payer_info`. Anyone can send lamports to an address
+// before it is created, which makes `create_account` fail, so an account that
+// already holds some is topped up, allocated and assigned instead.
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(space);
+    
+    if account_info.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                account_info.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[seeds],
+        );
+    }
+    
+    let shortfall = required_lamports.saturating_sub(account_info.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, shortfall),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )?;
+    
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        &[account_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )
+}
+
+// Create an operator account PDA funded by the authority
+fn create_operator_account<'a>(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    authority_info: &AccountInfo<'a>,
+    operator_account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    operator_state: &OperatorState,
+) -> ProgramResult {
+    let (expected_operator_pubkey, bump_seed) =
+        find_operator_address(program_id, casino_state, &operator_state.operator);
+    if expected_operator_pubkey != *operator_account_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let data = operator_state.to_account_data()?;
+    
+    create_pda_account(
+        program_id,
+        authority_info,
+        operator_account_info,
+        system_program_info,
+        data.len(),
+        &[
+            b"operator",
+            casino_state.as_ref(),
+            operator_state.operator.as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+    
+    operator_account_info.data.borrow_mut().copy_from_slice(&data);
+    
+    Ok(())
+}
+
+// Load a casino state account, checking its owner, type and address
+fn load_casino_state(
+    program_id: &Pubkey,
+    casino_state_info: &AccountInfo,
+) -> Result<CasinoState, ProgramError> {
+    let casino_state = CasinoState::load(program_id, casino_state_info)?;
+    
+    let expected_casino_state_pubkey = Pubkey::create_program_address(
+        &[b"casino", casino_state.creator.as_ref(), &[casino_state.bump]],
+        program_id,
+    )?;
+    if expected_casino_state_pubkey != *casino_state_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(casino_state)
+}
+
+/// Derives the game account address for a player's game in a casino
+pub fn find_game_address(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    player: &Pubkey,
+    game_id: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"game", casino_state.as_ref(), player.as_ref(), game_id],
+        program_id,
+    )
+}
+
+/// Derives the game account address a version 1 program used for a game ID
+pub fn find_legacy_game_address(program_id: &Pubkey, game_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[game_id], program_id)
+}
+
+// Load a game account of a casino, checking its owner, type and address and
+// that the game was placed in that casino
+fn load_game_state(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    game_info: &AccountInfo,
+) -> Result<GameState, ProgramError> {
+    let game_state = GameState::load(program_id, game_info)?;
+    
+    if game_state.casino != *casino_state {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let expected_game_pubkey = Pubkey::create_program_address(
+        &[
+            b"game",
+            game_state.casino.as_ref(),
+            game_state.player.as_ref(),
+            &game_state.game_id,
+            &[game_state.bump],
+        ],
+        program_id,
+    )?;
+    if expected_game_pubkey != *game_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(game_state)
+}
+
+/// Derives the crash round account address for a round of a casino
+pub fn find_crash_round_address(program_id: &Pubkey, casino_state: &Pubkey, round_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"crash_round", casino_state.as_ref(), round_id], program_id)
+}
+
+// Load a crash round account, checking its owner, type and address
+fn load_crash_round(
+    program_id: &Pubkey,
+    round_info: &AccountInfo,
+) -> Result<CrashRound, ProgramError> {
+    let round = CrashRound::load(program_id, round_info)?;
+    
+    let expected_round_pubkey = Pubkey::create_program_address(
+        &[b"crash_round", round.casino.as_ref(), &round.round_id, &[round.bump]],
+        program_id,
+    )?;
+    if expected_round_pubkey != *round_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(round)
+}
+
+/// Derives the account address for a player's bet in a crash round
+pub fn find_crash_bet_address(program_id: &Pubkey, round: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"crash_bet", round.as_ref(), player.as_ref()], program_id)
+}
+
+// Load a crash bet account of a round, checking its owner, type and address
+// and that the bet was placed in that round
+fn load_crash_bet(
+    program_id: &Pubkey,
+    round: &Pubkey,
+    bet_info: &AccountInfo,
+) -> Result<CrashBet, ProgramError> {
+    let bet = CrashBet::load(program_id, bet_info)?;
+    
+    if bet.round != *round {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let expected_bet_pubkey = Pubkey::create_program_address(
+        &[b"crash_bet", round.as_ref(), bet.player.as_ref(), &[bet.bump]],
+        program_id,
+    )?;
+    if expected_bet_pubkey != *bet_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(bet)
+}
+
+/// Derives the roulette round account address for a round of a casino
+pub fn find_round_address(program_id: &Pubkey, casino_state: &Pubkey, round_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"round", casino_state.as_ref(), round_id], program_id)
+}
+
+// Load a roulette round account, checking its owner, type and address
+fn load_round(
+    program_id: &Pubkey,
+    round_info: &AccountInfo,
+) -> Result<Round, ProgramError> {
+    let round = Round::load(program_id, round_info)?;
+    
+    let expected_round_pubkey = Pubkey::create_program_address(
+        &[b"round", round.casino.as_ref(), &round.round_id, &[round.bump]],
+        program_id,
+    )?;
+    if expected_round_pubkey != *round_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(round)
+}
+
+/// Derives the account address for a player's bets in a roulette round
+pub fn find_round_bet_address(program_id: &Pubkey, round: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"round_bet", round.as_ref(), player.as_ref()], program_id)
+}
+
+// Load a roulette round bet account, checking its owner, type and address
+// and that the bets were placed in that round
+fn load_round_bet(
+    program_id: &Pubkey,
+    round: &Pubkey,
+    bet_info: &AccountInfo,
+) -> Result<RoundBet, ProgramError> {
+    let bet = RoundBet::load(program_id, bet_info)?;
+    
+    if bet.round != *round {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let expected_bet_pubkey = Pubkey::create_program_address(
+        &[b"round_bet", round.as_ref(), bet.player.as_ref(), &[bet.bump]],
+        program_id,
+    )?;
+    if expected_bet_pubkey != *bet_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(bet)
+}
+
+// Load the operator account of an operator key, checking its address, owner
+// and type
+fn load_operator_account(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    operator: &Pubkey,
+    operator_account_info: &AccountInfo,
+) -> Result<OperatorState, ProgramError> {
+    let (expected_operator_pubkey, _) = find_operator_address(program_id, casino_state, operator);
+    if expected_operator_pubkey != *operator_account_info.key || operator_account_info.owner != program_id {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    OperatorState::from_account_data(&operator_account_info.data.borrow())
+}
+
+// Load the signer's operator account and check that it grants `role`
+fn authorize_operator(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    signer_info: &AccountInfo,
+    operator_account_info: &AccountInfo,
+    role: u8,
+) -> Result<OperatorState, ProgramError> {
+    let operator_state = load_operator_account(
+        program_id,
+        casino_state,
+        signer_info.key,
+        operator_account_info,
+    )?;
+    
+    if operator_state.roles & role == 0 {
+        return Err(CasinoError::Unauthorized.into());
+    }
+    
+    Ok(operator_state)
+}
+
+/// Derives the house vault address for a casino
+pub fn find_vault_address(program_id: &Pubkey, casino_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", casino_state.as_ref()], program_id)
+}
+
+// Create the house vault as a data-less PDA owned by the program so payouts
+// can be debited from it directly
+fn create_vault_account<'a>(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (expected_vault_pubkey, vault_bump_seed) = find_vault_address(program_id, casino_state);
+    if expected_vault_pubkey != *vault_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    create_pda_account(
+        program_id,
+        payer_info,
+        vault_info,
+        system_program_info,
+        0,
+        &[b"vault", casino_state.as_ref(), &[vault_bump_seed]],
+    )?;
+    
+    Ok(())
+}
+
+// Check that the vault account is the casino's vault PDA and owned by the program
+fn validate_vault(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    vault_info: &AccountInfo,
+) -> ProgramResult {
+    let (expected_vault_pubkey, _) = find_vault_address(program_id, casino_state);
+    
+    if expected_vault_pubkey != *vault_info.key || vault_info.owner != program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    Ok(())
+}
+
+// The hash of the most recent slot in the SlotHashes sysvar. The account is
+// too large to deserialize on chain, so only its first entry is read.
+fn latest_slot_hash(slot_hashes_info: &AccountInfo) -> Result<[u8; 32], ProgramError> {
+    if *slot_hashes_info.key != slot_hashes::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // A u64 entry count, then (slot, hash) pairs with the most recent first
+    let data = slot_hashes_info.try_borrow_data()?;
+    match data.get(..8) {
+        Some(count) if count != [0; 8] => {},
+        _ => return Err(ProgramError::InvalidAccountData),
+    }
+    let mut slot_hash = [0u8; 32];
+    slot_hash.copy_from_slice(data.get(16..48).ok_or(ProgramError::InvalidAccountData)?);
+    Ok(slot_hash)
+}
+
+// Lamports in the vault above its rent-exempt minimum
+fn vault_available_balance(vault_info: &AccountInfo) -> Result<u64, ProgramError> {
+    let rent = Rent::get()?;
+    Ok(vault_info
+        .lamports()
+        .saturating_sub(rent.minimum_balance(vault_info.data_len())))
+}
+
+// Check that the token account is the vault's associated token account for the mint
+fn validate_vault_token_account(
+    vault: &Pubkey,
+    mint: &Pubkey,
+    vault_token_info: &AccountInfo,
+) -> ProgramResult {
+    let expected_vault_token_pubkey =
+        spl_associated_token_account::get_associated_token_address(vault, mint);
+    
+    if expected_vault_token_pubkey != *vault_token_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    Ok(())
+}
+
+// Move tokens with the SPL token program, signing with the given seeds if any
+fn transfer_tokens<'a>(
+    token_program_info: &AccountInfo<'a>,
+    source_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if *token_program_info.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            source_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+// Pay tokens out of the vault's token account, signing as the vault PDA.
+// The caller must have validated the vault token account against the mint.
+fn pay_tokens_from_vault<'a>(
+    program_id: &Pubkey,
+    casino_state: &Pubkey,
+    vault_info: &AccountInfo<'a>,
+    vault_token_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let vault_token = spl_token::state::Account::unpack(&vault_token_info.data.borrow())?;
+    if vault_token.amount < amount {
+        return Err(CasinoError::InsufficientFunds.into());
+    }
+    
+    let (_, vault_bump_seed) = find_vault_address(program_id, casino_state);
+    
+    transfer_tokens(
+        token_program_info,
+        vault_token_info,
+        destination_info,
+        vault_info,
+        amount,
+        &[&[b"vault", casino_state.as_ref(), &[vault_bump_seed]]],
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
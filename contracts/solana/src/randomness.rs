@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: ISC
+//! Commit–reveal randomness used to settle games provably fairly.
+//!
+//! The operator publishes `commit(server_seed)` before a bet is placed and the
+//! player picks a client seed when betting. At settlement the operator reveals
+//! the server seed and the outcome is derived from both seeds and the game
+//! account, the same way the Node roulette combines its public and server seeds.
+use solana_program::{
+    hash::{hash, hashv},
+    pubkey::Pubkey,
+};
+
+/// Hashes a server seed into the commitment stored with a bet
+pub fn commit(server_seed: &[u8; 32]) -> [u8; 32] {
+    hash(server_seed).to_bytes()
+}
+
+/// Derives the result hash of a game from the revealed server seed, the
+/// player's client seed and the game account address
+pub fn result_hash(server_seed: &[u8; 32], client_seed: &[u8; 32], game: &Pubkey) -> [u8; 32] {
+    hashv(&[server_seed, client_seed, game.as_ref()]).to_bytes()
+}
+
+/// Combines a player's client seed with a seed nobody picks, such as the slot
+/// hash a coinflip challenge was accepted in, into the one the game is
+/// settled with
+pub fn combine_seeds(first: &[u8; 32], second: &[u8; 32]) -> [u8; 32] {
+    hashv(&[first, second]).to_bytes()
+}
+
+/// Reduces a result hash to a roll in `0..modulus`, reading the first four bytes
+/// big-endian like the Node backend reads the first eight hex characters
+pub fn roll(result_hash: &[u8; 32], modulus: u32) -> u32 {
+    let mut head = [0u8; 4];
+    head.copy_from_slice(&result_hash[..4]);
+    u32::from_be_bytes(head) % modulus
+}
+
+/// Outcome of a plain win/loss game: an even roll wins
+pub fn is_win(result_hash: &[u8; 32]) -> bool {
+    roll(result_hash, 2) == 0
+}
+
+/// Payout of a plain win/loss game: a win pays even odds less the house
+/// edge, `2 * bet_amount * (10000 - house_edge) / 10000`, a loss nothing.
+/// `None` if the payout overflows.
+pub fn payout(bet_amount: u64, house_edge: u16, result_hash: &[u8; 32]) -> Option<u64> {
+    if !is_win(result_hash) {
+        return Some(0);
+    }
+    win_payout(bet_amount, house_edge)
+}
+
+/// Payout of a winning plain win/loss bet, or `None` if it overflows
+pub fn win_payout(bet_amount: u64, house_edge: u16) -> Option<u64> {
+    let basis_points = 10_000u64;
+    bet_amount
+        .checked_mul(2)?
+        .checked_mul(basis_points.checked_sub(house_edge as u64)?)?
+        .checked_div(basis_points)
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: ISC
+//! The 15-slot red/black/green wheel played by `socket/roulette/roulette.js`.
+//!
+//! Slot 0 is green, odd slots are red and even slots are black. The payout
+//! multipliers mirror `WonGreenMultiplier`, `WonDangerMultiplier` and
+//! `WonGreyMultiplier` in `config/constants.js`.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::randomness;
+
+/// Number of slots on the wheel
+pub const SLOTS: u32 = 15;
+
+/// Payout multiplier for a winning red bet
+pub const RED_MULTIPLIER: u64 = 2;
+
+/// Payout multiplier for a winning black bet
+pub const BLACK_MULTIPLIER: u64 = 2;
+
+/// Payout multiplier for a winning green bet
+pub const GREEN_MULTIPLIER: u64 = 14;
+
+/// Colour a roulette bet is placed on
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouletteColor {
+    Red,
+    Black,
+    Green,
+}
+
+impl RouletteColor {
+    /// Total payout multiplier (stake included) when this colour wins
+    pub fn multiplier(self) -> u64 {
+        match self {
+            RouletteColor::Red => RED_MULTIPLIER,
+            RouletteColor::Black => BLACK_MULTIPLIER,
+            RouletteColor::Green => GREEN_MULTIPLIER,
+        }
+    }
+}
+
+/// Winning slot for a result hash
+pub fn winning_slot(result_hash: &[u8; 32]) -> u8 {
+    randomness::roll(result_hash, SLOTS) as u8
+}
+
+/// Colour of a slot on the wheel
+pub fn slot_color(slot: u8) -> RouletteColor {
+    match slot {
+        0 => RouletteColor::Green,
+        slot if slot % 2 == 1 => RouletteColor::Red,
+        _ => RouletteColor::Black,
+    }
+}
+
+/// Amount paid for a bet on `color` given a result hash (0 if it lost), or
+/// `None` if the payout overflows
+pub fn payout(bet_amount: u64, color: RouletteColor, result_hash: &[u8; 32]) -> Option<u64> {
+    if slot_color(winning_slot(result_hash)) == color {
+        bet_amount.checked_mul(color.multiplier())
+    } else {
+        Some(0)
+    }
+}
@@ -1,10 +1,11 @@
 #[cfg(test)]
 mod security_tests {
     use {
-        crate::{id, CasinoState, GameState, process_instruction},
+        crate::{id, test_utils::*, CasinoError, CasinoState, GameState, ProgramAccount, process_instruction},
         borsh::BorshDeserialize,
         solana_program::{
-            instruction::{AccountMeta, Instruction},
+            clock::Clock,
+            instruction::{AccountMeta, Instruction, InstructionError},
             program_error::ProgramError,
             pubkey::Pubkey,
             rent::Rent,
@@ -90,6 +91,7 @@ mod security_tests {
             Some(300),
             Some(200_000),
             Some(2_000_000_000),
+            None,
         );
         
         let mut update_transaction = Transaction::new_with_payer(
@@ -107,6 +109,8 @@ mod security_tests {
             &attacker.pubkey(),
             &casino_state_pubkey,
             &attacker.pubkey(),
+            crate::ROLE_ALL,
+            u64::MAX,
         );
         
         let mut add_transaction = Transaction::new_with_payer(
@@ -206,6 +210,7 @@ mod security_tests {
             None,
             Some(invalid_min_bet),
             Some(invalid_max_bet),
+            None,
         );
         
         let mut invalid_params_transaction = Transaction::new_with_payer(
@@ -217,6 +222,76 @@ mod security_tests {
         // This should fail because min_bet > max_bet
         let result = banks_client.process_transaction(invalid_params_transaction).await;
         assert!(result.is_err());
+        
+        // A crash round that runs to the top multiplier must be over, with
+        // time to settle it, before its players may claim refunds
+        assert!(crate::MIN_SETTLE_TIMEOUT > crate::crash::seconds_to_reach(crate::crash::MAX_MULTIPLIER));
+        
+        let short_timeout_instruction = crate::client::update_params(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            None,
+            None,
+            None,
+            Some(crate::MIN_SETTLE_TIMEOUT - 1),
+        );
+        
+        let mut short_timeout_transaction = Transaction::new_with_payer(
+            &[short_timeout_instruction],
+            Some(&payer.pubkey()),
+        );
+        short_timeout_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(short_timeout_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+        
+        // Nor may the timeout be long enough to overflow a refund deadline
+        let long_timeout_instruction = crate::client::update_params(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            None,
+            None,
+            None,
+            Some(crate::MAX_SETTLE_TIMEOUT + 1),
+        );
+        
+        let mut long_timeout_transaction = Transaction::new_with_payer(
+            &[long_timeout_instruction],
+            Some(&payer.pubkey()),
+        );
+        long_timeout_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(long_timeout_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+        
+        // The shortest timeout allowed is accepted
+        let min_timeout_instruction = crate::client::update_params(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            None,
+            None,
+            None,
+            Some(crate::MIN_SETTLE_TIMEOUT),
+        );
+        
+        let mut min_timeout_transaction = Transaction::new_with_payer(
+            &[min_timeout_instruction],
+            Some(&payer.pubkey()),
+        );
+        min_timeout_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(min_timeout_transaction).await.unwrap();
+        
+        let casino_state = crate::client::fetch_casino_state(&mut banks_client, &casino_state_pubkey)
+            .await
+            .unwrap();
+        assert_eq!(casino_state.settle_timeout, crate::MIN_SETTLE_TIMEOUT);
     }
 
     #[tokio::test]
@@ -277,25 +352,33 @@ mod security_tests {
         // Try to place a bet below minimum
         let game_id_1 = [1u8; 32];
         let (game_pubkey_1, _) = Pubkey::find_program_address(
-            &[&game_id_1],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id_1],
             &id::id(),
         );
         
         let below_min_bet = 50_000; // 0.00005 SOL
         
+        let server_seed_1 = seed_for(&game_pubkey_1, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed_1),
+        );
         let below_min_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey_1,
             game_id_1,
             below_min_bet,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed_1),
         );
         
         let mut below_min_transaction = Transaction::new_with_payer(
-            &[below_min_instruction],
+            &[commitment_instruction, below_min_instruction],
             Some(&payer.pubkey()),
         );
-        below_min_transaction.sign(&[&payer, &player], recent_blockhash);
+        below_min_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         // This should fail because bet is below minimum
         let result = banks_client.process_transaction(below_min_transaction).await;
@@ -304,25 +387,33 @@ mod security_tests {
         // Try to place a bet above maximum
         let game_id_2 = [2u8; 32];
         let (game_pubkey_2, _) = Pubkey::find_program_address(
-            &[&game_id_2],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id_2],
             &id::id(),
         );
         
         let above_max_bet = 1_500_000_000; // 1.5 SOL
         
+        let server_seed_2 = seed_for(&game_pubkey_2, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed_2),
+        );
         let above_max_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey_2,
             game_id_2,
             above_max_bet,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed_2),
         );
         
         let mut above_max_transaction = Transaction::new_with_payer(
-            &[above_max_instruction],
+            &[commitment_instruction, above_max_instruction],
             Some(&payer.pubkey()),
         );
-        above_max_transaction.sign(&[&payer, &player], recent_blockhash);
+        above_max_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         // This should fail because bet is above maximum
         let result = banks_client.process_transaction(above_max_transaction).await;
@@ -331,25 +422,33 @@ mod security_tests {
         // Place a valid bet
         let game_id_3 = [3u8; 32];
         let (game_pubkey_3, _) = Pubkey::find_program_address(
-            &[&game_id_3],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id_3],
             &id::id(),
         );
         
         let valid_bet = 500_000; // 0.0005 SOL
         
+        let server_seed_3 = seed_for(&game_pubkey_3, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed_3),
+        );
         let valid_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey_3,
             game_id_3,
             valid_bet,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed_3),
         );
         
         let mut valid_bet_transaction = Transaction::new_with_payer(
-            &[valid_bet_instruction],
+            &[commitment_instruction, valid_bet_instruction],
             Some(&payer.pubkey()),
         );
-        valid_bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        valid_bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         // This should succeed
         banks_client.process_transaction(valid_bet_transaction).await.unwrap();
@@ -371,7 +470,7 @@ mod security_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -419,29 +518,51 @@ mod security_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
-        // Calculate maximum possible win with house edge
+        // Fund the house vault so it can cover payouts above the stake
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut fund_vault_transaction = Transaction::new_with_payer(
+            &[fund_vault_instruction],
+            Some(&payer.pubkey()),
+        );
+        fund_vault_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(fund_vault_transaction).await.unwrap();
+        
+        // A win pays even odds less the house edge
         let basis_points = 10000;
-        let max_possible_win = bet_amount * basis_points / (basis_points - house_edge as u64);
+        let max_possible_win = 2 * bet_amount * (basis_points - house_edge as u64) / basis_points;
         
         // Try to settle with win amount too high
         let excessive_win_amount = max_possible_win + 100_000;
-        let result_hash = [2u8; 32];
         
         let excessive_settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -450,7 +571,7 @@ mod security_tests {
             &player.pubkey(),
             true,
             excessive_win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut excessive_settle_transaction = Transaction::new_with_payer(
@@ -463,8 +584,34 @@ mod security_tests {
         let result = banks_client.process_transaction(excessive_settle_transaction).await;
         assert!(result.is_err());
         
-        // Settle with valid win amount
-        let valid_win_amount = max_possible_win - 1;
+        // Underpaying the player is rejected too
+        let short_settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            max_possible_win - 1,
+            server_seed,
+        );
+        
+        let mut short_settle_transaction = Transaction::new_with_payer(
+            &[short_settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        short_settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(short_settle_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(CasinoError::ExpectedAmountMismatch as u32),
+            )
+        );
+        
+        // Settle with exactly the payout the house edge allows
+        let valid_win_amount = max_possible_win;
         
         let valid_settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -473,7 +620,7 @@ mod security_tests {
             &player.pubkey(),
             true,
             valid_win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut valid_settle_transaction = Transaction::new_with_payer(
@@ -502,7 +649,7 @@ mod security_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -550,25 +697,47 @@ mod security_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
+        // Fund the house vault so it can cover payouts above the stake
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut fund_vault_transaction = Transaction::new_with_payer(
+            &[fund_vault_instruction],
+            Some(&payer.pubkey()),
+        );
+        fund_vault_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(fund_vault_transaction).await.unwrap();
+        
         // Settle the game
-        let win_amount = 900_000; // 0.0009 SOL
-        let result_hash = [2u8; 32];
+        let win_amount = 975_000;
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -577,7 +746,7 @@ mod security_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
@@ -589,8 +758,8 @@ mod security_tests {
         banks_client.process_transaction(settle_transaction).await.unwrap();
         
         // Try to settle the same game again
-        let second_win_amount = 800_000; // 0.0008 SOL
-        let second_result_hash = [3u8; 32];
+        let second_win_amount = 505_000; // 0.000505 SOL
+        let second_server_seed = server_seed;
         
         let second_settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -599,7 +768,7 @@ mod security_tests {
             &player.pubkey(),
             true,
             second_win_amount,
-            second_result_hash,
+            second_server_seed,
         );
         
         let mut second_settle_transaction = Transaction::new_with_payer(
@@ -630,7 +799,7 @@ mod security_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -686,25 +855,47 @@ mod security_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
+        // Fund the house vault so it can cover payouts above the stake
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut fund_vault_transaction = Transaction::new_with_payer(
+            &[fund_vault_instruction],
+            Some(&payer.pubkey()),
+        );
+        fund_vault_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(fund_vault_transaction).await.unwrap();
+        
         // Try to settle game as non-operator
-        let win_amount = 900_000; // 0.0009 SOL
-        let result_hash = [2u8; 32];
+        let win_amount = 975_000;
         
         let non_operator_settle_instruction = crate::client::settle_game(
             &operator.pubkey(),
@@ -713,7 +904,7 @@ mod security_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut non_operator_settle_transaction = Transaction::new_with_payer(
@@ -731,6 +922,8 @@ mod security_tests {
             &authority.pubkey(),
             &casino_state_pubkey,
             &operator.pubkey(),
+            crate::ROLE_SETTLE,
+            u64::MAX,
         );
         
         let mut add_operator_transaction = Transaction::new_with_payer(
@@ -749,9 +942,11 @@ mod security_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
+        // Use a fresh blockhash so this is not a duplicate of the rejected attempt
+        let recent_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
         let mut operator_settle_transaction = Transaction::new_with_payer(
             &[operator_settle_instruction],
             Some(&payer.pubkey()),
@@ -778,4 +973,1307 @@ mod security_tests {
         let result = banks_client.process_transaction(remove_authority_transaction).await;
         assert!(result.is_err());
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_unauthorized_vault_withdrawal() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let attacker = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            attacker.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund the casino as the legitimate authority
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let fund_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // Attacker tries to drain the vault into their own account
+        let withdraw_instruction = crate::client::withdraw_from_vault(
+            &attacker.pubkey(),
+            &casino_state_pubkey,
+            &attacker.pubkey(),
+            100_000_000,
+        );
+        
+        let mut withdraw_transaction = Transaction::new_with_payer(
+            &[withdraw_instruction],
+            Some(&payer.pubkey()),
+        );
+        withdraw_transaction.sign(&[&payer, &attacker], recent_blockhash);
+        
+        // This should fail because attacker is not the authority
+        let result = banks_client.process_transaction(withdraw_transaction).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_server_seed_verification() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        
+        let game_id = [9u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund the casino
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let fund_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // Place a bet against a committed server seed that makes the player lose
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, |hash| !crate::randomness::is_win(hash));
+        
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
+        let place_bet_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            game_id,
+            500_000,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
+        );
+        
+        let mut bet_transaction = Transaction::new_with_payer(
+            &[commitment_instruction, place_bet_instruction],
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+        
+        banks_client.process_transaction(bet_transaction).await.unwrap();
+        
+        // Revealing a seed that was not committed should fail
+        let winning_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        
+        let wrong_seed_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            975_000,
+            winning_seed,
+        );
+        
+        let mut wrong_seed_transaction = Transaction::new_with_payer(
+            &[wrong_seed_instruction],
+            Some(&payer.pubkey()),
+        );
+        wrong_seed_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(wrong_seed_transaction).await;
+        assert!(result.is_err());
+        
+        // Claiming a win the committed seed does not produce should fail
+        let wrong_outcome_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seed,
+        );
+        
+        let mut wrong_outcome_transaction = Transaction::new_with_payer(
+            &[wrong_outcome_instruction],
+            Some(&payer.pubkey()),
+        );
+        wrong_outcome_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(wrong_outcome_transaction).await;
+        assert!(result.is_err());
+        
+        // Settling with the committed seed and the derived outcome succeeds
+        let settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            false,
+            0,
+            server_seed,
+        );
+        
+        let mut settle_transaction = Transaction::new_with_payer(
+            &[settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(settle_transaction).await.unwrap();
+        
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
+            .await
+            .unwrap();
+        assert!(game_state.is_settled);
+        assert!(!game_state.is_win);
+        assert_eq!(game_state.server_seed, server_seed);
+    }
+
+    #[tokio::test]
+    async fn test_operator_roles_and_payout_cap() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let operator = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        
+        let game_ids = [[10u8; 32], [11u8; 32]];
+        let game_pubkeys: Vec<Pubkey> = game_ids
+            .iter()
+            .map(|game_id| {
+                crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), game_id).0
+            })
+            .collect();
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund the casino, and add a settlement-only operator
+        // that may pay out at most 1_000_000 lamports a day
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let fund_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        let add_operator_instruction = crate::client::add_operator(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &operator.pubkey(),
+            crate::ROLE_SETTLE,
+            1_000_000,
+        );
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction, add_operator_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // The operator has no params role
+        let update_instruction = crate::client::update_params(
+            &operator.pubkey(),
+            &casino_state_pubkey,
+            Some(1000),
+            None,
+            None,
+            None,
+        );
+        
+        let mut update_transaction = Transaction::new_with_payer(
+            &[update_instruction],
+            Some(&payer.pubkey()),
+        );
+        update_transaction.sign(&[&payer, &operator], recent_blockhash);
+        
+        let result = banks_client.process_transaction(update_transaction).await;
+        assert!(result.is_err());
+        
+        // Place two winning bets
+        let server_seeds: Vec<[u8; 32]> = game_pubkeys
+            .iter()
+            .map(|game_pubkey| seed_for(game_pubkey, &CLIENT_SEED, crate::randomness::is_win))
+            .collect();
+        
+        let place_bet_instructions: Vec<Instruction> = game_ids
+            .iter()
+            .zip(&game_pubkeys)
+            .zip(&server_seeds)
+            .flat_map(|((game_id, game_pubkey), server_seed)| {
+                let server_seed_hash = crate::randomness::commit(server_seed);
+                [
+                    crate::client::post_commitment(&authority.pubkey(), &casino_state_pubkey, server_seed_hash),
+                    crate::client::place_bet(
+                        &player.pubkey(),
+                        &casino_state_pubkey,
+                        game_pubkey,
+                        *game_id,
+                        500_000,
+                        CLIENT_SEED,
+                        server_seed_hash,
+                    ),
+                ]
+            })
+            .collect();
+        
+        let mut bet_transaction = Transaction::new_with_payer(
+            &place_bet_instructions,
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+        
+        banks_client.process_transaction(bet_transaction).await.unwrap();
+        
+        // The first win fits under the operator's cap
+        let first_settle_instruction = crate::client::settle_game(
+            &operator.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkeys[0],
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seeds[0],
+        );
+        
+        let mut first_settle_transaction = Transaction::new_with_payer(
+            &[first_settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        first_settle_transaction.sign(&[&payer, &operator], recent_blockhash);
+        
+        banks_client.process_transaction(first_settle_transaction).await.unwrap();
+        
+        // The second would take the operator over its daily cap
+        let second_settle_instruction = crate::client::settle_game(
+            &operator.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkeys[1],
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seeds[1],
+        );
+        
+        let mut second_settle_transaction = Transaction::new_with_payer(
+            &[second_settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        second_settle_transaction.sign(&[&payer, &operator], recent_blockhash);
+        
+        let result = banks_client.process_transaction(second_settle_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(CasinoError::PayoutCapExceeded as u32),
+            )
+        );
+        
+        // The authority has no cap and can still settle it
+        let authority_settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkeys[1],
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seeds[1],
+        );
+        
+        let mut authority_settle_transaction = Transaction::new_with_payer(
+            &[authority_settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        authority_settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(authority_settle_transaction).await.unwrap();
+        
+        let operator_state =
+            crate::client::fetch_operator_state(&mut banks_client, &casino_state_pubkey, &operator.pubkey())
+                .await
+                .unwrap();
+        assert_eq!(operator_state.paid_today, 975_000);
+    }
+    
+    #[tokio::test]
+    async fn test_pause_and_unpause() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let guardian = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        
+        let game_id = [12u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund the casino, and add a guardian that may only pause
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let fund_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        let add_operator_instruction = crate::client::add_operator(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &guardian.pubkey(),
+            crate::ROLE_PAUSE,
+            0,
+        );
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction, add_operator_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // The player has no operator account and cannot pause
+        let player_pause_instruction = crate::client::pause(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            true,
+            true,
+        );
+        
+        let mut player_pause_transaction = Transaction::new_with_payer(
+            &[player_pause_instruction],
+            Some(&payer.pubkey()),
+        );
+        player_pause_transaction.sign(&[&payer, &player], recent_blockhash);
+        
+        let result = banks_client.process_transaction(player_pause_transaction).await;
+        assert!(result.is_err());
+        
+        // The guardian pauses betting
+        let pause_instruction = crate::client::pause(
+            &guardian.pubkey(),
+            &casino_state_pubkey,
+            true,
+            false,
+        );
+        
+        let mut pause_transaction = Transaction::new_with_payer(
+            &[pause_instruction],
+            Some(&payer.pubkey()),
+        );
+        pause_transaction.sign(&[&payer, &guardian], recent_blockhash);
+        
+        banks_client.process_transaction(pause_transaction).await.unwrap();
+        
+        let casino_state = crate::client::fetch_casino_state(&mut banks_client, &casino_state_pubkey)
+            .await
+            .unwrap();
+        assert!(casino_state.betting_paused);
+        assert!(!casino_state.settlement_paused);
+        
+        // Bets are rejected while betting is paused
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let place_bet_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            game_id,
+            500_000,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
+        );
+        
+        let mut bet_transaction = Transaction::new_with_payer(
+            std::slice::from_ref(&place_bet_instruction),
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        
+        let result = banks_client.process_transaction(bet_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(CasinoError::Paused as u32),
+            )
+        );
+        
+        // The authority resumes betting and pauses settlement
+        let unpause_instruction = crate::client::unpause(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            true,
+            false,
+        );
+        let pause_settlement_instruction = crate::client::pause(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            false,
+            true,
+        );
+        
+        let mut unpause_transaction = Transaction::new_with_payer(
+            &[unpause_instruction, pause_settlement_instruction],
+            Some(&payer.pubkey()),
+        );
+        unpause_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(unpause_transaction).await.unwrap();
+        
+        let recent_blockhash = banks_client
+            .get_new_latest_blockhash(&recent_blockhash)
+            .await
+            .unwrap();
+        
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
+        
+        let mut bet_transaction = Transaction::new_with_payer(
+            &[commitment_instruction, place_bet_instruction],
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+        
+        banks_client.process_transaction(bet_transaction).await.unwrap();
+        
+        // Settlement is rejected while it is paused
+        let settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seed,
+        );
+        
+        let mut settle_transaction = Transaction::new_with_payer(
+            std::slice::from_ref(&settle_instruction),
+            Some(&payer.pubkey()),
+        );
+        settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(settle_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(CasinoError::Paused as u32),
+            )
+        );
+        
+        // Once the guardian resumes settlement the game can be settled
+        let unpause_instruction = crate::client::unpause(
+            &guardian.pubkey(),
+            &casino_state_pubkey,
+            false,
+            true,
+        );
+        
+        let mut unpause_transaction = Transaction::new_with_payer(
+            &[unpause_instruction, settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        unpause_transaction.sign(&[&payer, &guardian, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(unpause_transaction).await.unwrap();
+        
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
+            .await
+            .unwrap();
+        assert!(game_state.is_settled);
+    }
+    
+    #[tokio::test]
+    async fn test_spoofed_accounts() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let attacker = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        let (attacker_casino_pubkey, attacker_casino_bump) = Pubkey::find_program_address(
+            &[b"casino", attacker.pubkey().as_ref()],
+            &id::id(),
+        );
+        let (authority_operator_pubkey, _) =
+            crate::client::find_operator_address(&casino_state_pubkey, &authority.pubkey());
+        
+        let game_id = [13u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+        let forged_game_id = [14u8; 32];
+        let (_, forged_game_bump) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &forged_game_id);
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            attacker.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Casino state handing the attacker control, stored in an account the
+        // program does not own and in a program account at the wrong address
+        let forged_casino_state = CasinoState {
+            authority: attacker.pubkey(),
+            house_edge: 0,
+            min_bet: 0,
+            max_bet: u64::MAX,
+            mint: None,
+            settle_timeout: 1,
+            creator: attacker.pubkey(),
+            pending_authority: Pubkey::default(),
+            betting_paused: false,
+            settlement_paused: false,
+            liabilities: 0,
+            bump: attacker_casino_bump,
+        };
+        
+        let foreign_casino_pubkey = Pubkey::new_unique();
+        program_test.add_account(
+            foreign_casino_pubkey,
+            Account {
+                lamports: 1_000_000_000,
+                data: forged_casino_state.to_account_data().unwrap(),
+                owner: attacker.pubkey(),
+                ..Account::default()
+            },
+        );
+        
+        let misplaced_casino_pubkey = Pubkey::new_unique();
+        assert_ne!(misplaced_casino_pubkey, attacker_casino_pubkey);
+        program_test.add_account(
+            misplaced_casino_pubkey,
+            Account {
+                lamports: 1_000_000_000,
+                data: forged_casino_state.to_account_data().unwrap(),
+                owner: id::id(),
+                ..Account::default()
+            },
+        );
+        
+        // A winning game for the player that was never bet on
+        let forged_game_pubkey = Pubkey::new_unique();
+        let forged_server_seed = [7u8; 32];
+        let forged_game_state = GameState {
+            player: player.pubkey(),
+            bet_amount: 50_000_000,
+            is_settled: false,
+            is_win: false,
+            win_amount: 0,
+            result_hash: [0; 32],
+            server_seed_hash: crate::randomness::commit(&forged_server_seed),
+            client_seed: CLIENT_SEED,
+            server_seed: [0; 32],
+            game_type: crate::GameType::Standard,
+            house_edge: 250,
+            created_at: 0,
+            refundable_at: crate::DEFAULT_SETTLE_TIMEOUT,
+            casino: casino_state_pubkey,
+            game_id: forged_game_id,
+            bump: forged_game_bump,
+        };
+        program_test.add_account(
+            forged_game_pubkey,
+            Account {
+                lamports: 1_000_000_000,
+                data: forged_game_state.to_account_data().unwrap(),
+                owner: id::id(),
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund the real casino
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let fund_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // Casino state in an account the program does not own
+        let foreign_casino_instruction = crate::client::update_params(
+            &attacker.pubkey(),
+            &foreign_casino_pubkey,
+            Some(1000),
+            None,
+            None,
+            None,
+        );
+        
+        let mut foreign_casino_transaction = Transaction::new_with_payer(
+            &[foreign_casino_instruction],
+            Some(&payer.pubkey()),
+        );
+        foreign_casino_transaction.sign(&[&payer, &attacker], recent_blockhash);
+        
+        let result = banks_client.process_transaction(foreign_casino_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+        );
+        
+        // Program-owned casino state at an address it was not derived for
+        let misplaced_casino_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &misplaced_casino_pubkey,
+            &game_pubkey,
+            game_id,
+            1,
+            CLIENT_SEED,
+            [0; 32],
+        );
+        
+        let mut misplaced_casino_transaction = Transaction::new_with_payer(
+            &[misplaced_casino_instruction],
+            Some(&payer.pubkey()),
+        );
+        misplaced_casino_transaction.sign(&[&payer, &player], recent_blockhash);
+        
+        let result = banks_client.process_transaction(misplaced_casino_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+        
+        // Another program account type posing as the casino state
+        let wrong_type_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &authority_operator_pubkey,
+            &game_pubkey,
+            game_id,
+            500_000,
+            CLIENT_SEED,
+            [0; 32],
+        );
+        
+        let mut wrong_type_transaction = Transaction::new_with_payer(
+            &[wrong_type_instruction],
+            Some(&payer.pubkey()),
+        );
+        wrong_type_transaction.sign(&[&payer, &player], recent_blockhash);
+        
+        let result = banks_client.process_transaction(wrong_type_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+        );
+        
+        // A game state planted outside its PDA cannot be settled
+        let forged_settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &forged_game_pubkey,
+            &player.pubkey(),
+            true,
+            50_000_000,
+            forged_server_seed,
+        );
+        
+        let mut forged_settle_transaction = Transaction::new_with_payer(
+            &[forged_settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        forged_settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(forged_settle_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+        
+        // Nothing was paid out of the vault
+        let (vault_pubkey, _) = crate::client::find_vault_address(&casino_state_pubkey);
+        let vault_account = banks_client
+            .get_account(vault_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(vault_account.lamports >= 100_000_000);
+    }
+    
+    #[tokio::test]
+    async fn test_game_bound_to_casino() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let other_authority = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        let (other_casino_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", other_authority.pubkey().as_ref()],
+            &id::id(),
+        );
+        
+        // The same game ID gives each casino its own game account
+        let game_id = [15u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+        let (other_game_pubkey, _) =
+            crate::client::find_game_address(&other_casino_pubkey, &player.pubkey(), &game_id);
+        assert_ne!(game_pubkey, other_game_pubkey);
+        
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            other_authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // Initialize and fund both casinos
+        let mut instructions = Vec::new();
+        for authority in [&authority, &other_authority] {
+            let (casino_pubkey, _) = Pubkey::find_program_address(
+                &[b"casino", authority.pubkey().as_ref()],
+                &id::id(),
+            );
+            instructions.push(crate::client::initialize(
+                &authority.pubkey(),
+                &casino_pubkey,
+                250,
+                100_000,
+                1_000_000_000,
+            ));
+            instructions.push(crate::client::fund_vault(
+                &authority.pubkey(),
+                &casino_pubkey,
+                100_000_000,
+            ));
+        }
+        
+        let mut init_transaction = Transaction::new_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority, &other_authority], recent_blockhash);
+        
+        banks_client.process_transaction(init_transaction).await.unwrap();
+        
+        // Place a winning bet in the first casino
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
+        let place_bet_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            game_id,
+            500_000,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
+        );
+        
+        let mut bet_transaction = Transaction::new_with_payer(
+            &[commitment_instruction, place_bet_instruction],
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+        
+        banks_client.process_transaction(bet_transaction).await.unwrap();
+        
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
+            .await
+            .unwrap();
+        assert_eq!(game_state.casino, casino_state_pubkey);
+        
+        // The other casino's operator cannot settle it out of its own vault
+        let settle_instruction = crate::client::settle_game(
+            &other_authority.pubkey(),
+            &other_casino_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seed,
+        );
+        
+        let mut settle_transaction = Transaction::new_with_payer(
+            &[settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        settle_transaction.sign(&[&payer, &other_authority], recent_blockhash);
+        
+        let result = banks_client.process_transaction(settle_transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+        
+        // The casino the bet was placed in settles it
+        let settle_instruction = crate::client::settle_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            true,
+            975_000,
+            server_seed,
+        );
+        
+        let mut settle_transaction = Transaction::new_with_payer(
+            &[settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(settle_transaction).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prefunded_addresses() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let operator = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = crate::client::find_casino_state_address(&authority.pubkey());
+        let (vault_pubkey, _) = crate::client::find_vault_address(&casino_state_pubkey);
+        let (operator_account, _) = crate::client::find_operator_address(&casino_state_pubkey, &operator.pubkey());
+        
+        let game_id = [16u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let (commitment_pubkey, _) = crate::client::find_commitment_address(
+            &casino_state_pubkey,
+            &crate::randomness::commit(&server_seed),
+        );
+        
+        // Fund the accounts
+        for account in [authority.pubkey(), player.pubkey()] {
+            program_test.add_account(
+                account,
+                Account {
+                    lamports: 1_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+        
+        // An attacker sends lamports to every address the casino will create,
+        // a single lamport to most and more than the rent to the vault
+        let donation = 10_000_000;
+        for (address, lamports) in [
+            (casino_state_pubkey, 1),
+            (vault_pubkey, donation),
+            (operator_account, 1),
+            (commitment_pubkey, 1),
+            (game_pubkey, 1),
+        ] {
+            program_test.add_account(
+                address,
+                Account {
+                    lamports,
+                    ..Account::default()
+                },
+            );
+        }
+        
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        
+        // The casino is still initialized, its operator added and a bet placed
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let add_operator_instruction = crate::client::add_operator(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &operator.pubkey(),
+            crate::ROLE_SETTLE,
+            u64::MAX,
+        );
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
+        let place_bet_instruction = crate::client::place_bet(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            game_id,
+            500_000,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
+        );
+        
+        let mut transaction = Transaction::new_with_payer(
+            &[init_instruction, add_operator_instruction, commitment_instruction, place_bet_instruction],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+        
+        banks_client.process_transaction(transaction).await.unwrap();
+        
+        let rent = banks_client.get_rent().await.unwrap();
+        for address in [casino_state_pubkey, operator_account, game_pubkey] {
+            let account = banks_client.get_account(address).await.unwrap().unwrap();
+            assert_eq!(account.owner, id::id());
+            assert_eq!(account.lamports, rent.minimum_balance(account.data.len()));
+        }
+        
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
+            .await
+            .unwrap();
+        assert_eq!(game_state.bet_amount, 500_000);
+        
+        // The donation stays in the vault, with the stake and the consumed
+        // commitment's rent
+        let vault_account = banks_client.get_account(vault_pubkey).await.unwrap().unwrap();
+        assert_eq!(vault_account.owner, id::id());
+        assert!(vault_account.lamports > donation + 500_000);
+    }
+
+    #[tokio::test]
+    async fn test_withheld_settlement_refund() {
+        let mut program_test = program_test();
+        
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        
+        // Find PDAs
+        let (casino_state_pubkey, _) = crate::client::find_casino_state_address(&authority.pubkey());
+        let (vault_pubkey, _) = crate::client::find_vault_address(&casino_state_pubkey);
+        
+        let game_ids = [[17u8; 32], [18u8; 32]];
+        let [standard_game, dice_game] = game_ids.map(|game_id| {
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id).0
+        });
+        
+        // Fund the accounts
+        for account in [&authority, &player] {
+            program_test.add_account(
+                account.pubkey(),
+                Account {
+                    lamports: 1_000_000_000,
+                    ..Account::default()
+                },
+            );
+        }
+        
+        // Start the program test with a context so the clock can be moved
+        let mut context = program_test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+        
+        let settle_timeout = crate::MIN_SETTLE_TIMEOUT;
+        let init_instruction = crate::client::initialize(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            250,
+            100_000,
+            1_000_000_000,
+        );
+        let update_instruction = crate::client::update_params(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            None,
+            None,
+            None,
+            Some(settle_timeout),
+        );
+        
+        // Both bets win, so the operator has every reason not to reveal the
+        // seeds: a standard bet paying 1.95x and a dice bet under 10 paying 9.75x
+        let bet_amount = 500_000;
+        let mut instructions = vec![init_instruction, update_instruction];
+        for (game_id, game) in game_ids.iter().zip([standard_game, dice_game]) {
+            let server_seed = seed_for(&game, &CLIENT_SEED, |hash| {
+                if game == standard_game {
+                    crate::randomness::is_win(hash)
+                } else {
+                    crate::dice::is_win(10, crate::dice::roll(hash))
+                }
+            });
+            let server_seed_hash = crate::randomness::commit(&server_seed);
+            instructions.push(crate::client::post_commitment(&authority.pubkey(), &casino_state_pubkey, server_seed_hash));
+            instructions.push(if game == standard_game {
+                crate::client::place_bet(
+                    &player.pubkey(),
+                    &casino_state_pubkey,
+                    &game,
+                    *game_id,
+                    bet_amount,
+                    CLIENT_SEED,
+                    server_seed_hash,
+                )
+            } else {
+                crate::client::place_dice_bet(
+                    &player.pubkey(),
+                    &casino_state_pubkey,
+                    &game,
+                    *game_id,
+                    bet_amount,
+                    10,
+                    CLIENT_SEED,
+                    server_seed_hash,
+                )
+            });
+        }
+        
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &authority, &player], context.last_blockhash);
+        context.banks_client.process_transaction(transaction).await.unwrap();
+        
+        // The vault's bankroll is what it holds beyond its rent and the stakes
+        let rent = context.banks_client.get_rent().await.unwrap();
+        let vault_rent = rent.minimum_balance(0);
+        let vault_account = context.banks_client.get_account(vault_pubkey).await.unwrap().unwrap();
+        let bankroll = vault_account.lamports - vault_rent - 2 * bet_amount;
+        assert!(bankroll > 475_000);
+        
+        // Left unsettled past the timeout, the standard bet is paid its win
+        let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += settle_timeout;
+        context.set_sysvar(&clock);
+        
+        let game_account = context.banks_client.get_account(standard_game).await.unwrap().unwrap();
+        let game_rent = game_account.lamports;
+        let player_before = context.banks_client.get_account(player.pubkey()).await.unwrap().unwrap().lamports;
+        
+        let claim_instruction = crate::client::claim_refund(&player.pubkey(), &casino_state_pubkey, &standard_game);
+        let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(&[claim_instruction], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &player], recent_blockhash);
+        context.banks_client.process_transaction(transaction).await.unwrap();
+        
+        let player_account = context.banks_client.get_account(player.pubkey()).await.unwrap().unwrap();
+        assert_eq!(player_account.lamports, player_before + game_rent + 975_000);
+        
+        // The dice bet's win is more than the vault holds, so it takes the
+        // stake and the whole of the bankroll left
+        let claim_instruction = crate::client::claim_refund(&player.pubkey(), &casino_state_pubkey, &dice_game);
+        let mut transaction = Transaction::new_with_payer(&[claim_instruction], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &player], recent_blockhash);
+        context.banks_client.process_transaction(transaction).await.unwrap();
+        
+        let vault_account = context.banks_client.get_account(vault_pubkey).await.unwrap().unwrap();
+        assert_eq!(vault_account.lamports, vault_rent);
+        
+        let casino_state = crate::client::fetch_casino_state(&mut context.banks_client, &casino_state_pubkey)
+            .await
+            .unwrap();
+        assert_eq!(casino_state.liabilities, 0);
+    }
+}
@@ -0,0 +1,33 @@
+// Shared helpers for the program tests
+use solana_program::{hash::Hash, pubkey::Pubkey, slot_hashes::SlotHashes};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTestContext};
+
+use crate::{client, randomness};
+
+// Lets the tests read accounts through the client's fetch helpers
+impl client::AccountFetcher for BanksClient {
+    type Error = BanksClientError;
+
+    async fn get_account_data(&mut self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, Self::Error> {
+        Ok(self.get_account(*address).await?.map(|account| (account.owner, account.data)))
+    }
+}
+
+/// Client seed used by test bets
+pub const CLIENT_SEED: [u8; 32] = [42u8; 32];
+
+/// Finds a server seed whose result hash for `key` (the game or round
+/// account) and `client_seed` satisfies `wanted`, e.g. `randomness::is_win`
+/// for a standard bet that wins
+pub fn seed_for(key: &Pubkey, client_seed: &[u8; 32], wanted: impl Fn(&[u8; 32]) -> bool) -> [u8; 32] {
+    (0u8..=u8::MAX)
+        .map(|byte| [byte; 32])
+        .find(|seed| wanted(&randomness::result_hash(seed, client_seed, key)))
+        .expect("no server seed gives the wanted result")
+}
+
+/// Makes `slot_hash` the latest entry of the SlotHashes sysvar, which
+/// accepting a coinflip challenge mixes into the flip
+pub fn set_slot_hash(context: &mut ProgramTestContext, slot_hash: [u8; 32]) {
+    context.set_sysvar(&SlotHashes::new(&[(0, Hash::new_from_array(slot_hash))]));
+}
@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod token_tests {
+    use {
+        crate::{id, process_instruction},
+        solana_program::{program_pack::Pack, pubkey::Pubkey, system_instruction},
+        solana_program_test::*,
+        solana_sdk::{
+            account::Account,
+            signature::{Keypair, Signer},
+            transaction::Transaction,
+        },
+        spl_associated_token_account::get_associated_token_address,
+    };
+
+    // Helper function to create a program test environment
+    fn program_test() -> ProgramTest {
+        ProgramTest::new(
+            "casino_game",
+            id::id(),
+            processor!(process_instruction),
+        )
+    }
+
+    // Helper function to read the balance of a token account
+    async fn token_balance(banks_client: &mut BanksClient, token_account: Pubkey) -> u64 {
+        let account = banks_client
+            .get_account(token_account)
+            .await
+            .unwrap()
+            .unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    #[tokio::test]
+    async fn test_token_casino_bet_and_settle() {
+        let mut program_test = program_test();
+
+        // Create test accounts
+        let authority = Keypair::new();
+        let player = Keypair::new();
+        let mint = Keypair::new();
+
+        // Find PDAs
+        let (casino_state_pubkey, _) = Pubkey::find_program_address(
+            &[b"casino", authority.pubkey().as_ref()],
+            &id::id(),
+        );
+
+        let game_id = [7u8; 32];
+        let (game_pubkey, _) =
+            crate::client::find_game_address(&casino_state_pubkey, &player.pubkey(), &game_id);
+
+        let authority_token = get_associated_token_address(&authority.pubkey(), &mint.pubkey());
+        let player_token = get_associated_token_address(&player.pubkey(), &mint.pubkey());
+        let vault_token = crate::client::get_vault_token_address(&casino_state_pubkey, &mint.pubkey());
+
+        // Fund the accounts
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+
+        program_test.add_account(
+            player.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+
+        // Start the program test
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        let rent = banks_client.get_rent().await.unwrap();
+
+        // Create the mint and token accounts for the authority and the player
+        let setup_instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &authority.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &payer.pubkey(),
+                &authority.pubkey(),
+                &mint.pubkey(),
+                &spl_token::id(),
+            ),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &payer.pubkey(),
+                &player.pubkey(),
+                &mint.pubkey(),
+                &spl_token::id(),
+            ),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &authority_token,
+                &authority.pubkey(),
+                &[],
+                100_000_000,
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &player_token,
+                &authority.pubkey(),
+                &[],
+                10_000_000,
+            )
+            .unwrap(),
+        ];
+
+        let mut setup_transaction = Transaction::new_with_payer(
+            &setup_instructions,
+            Some(&payer.pubkey()),
+        );
+        setup_transaction.sign(&[&payer, &mint, &authority], recent_blockhash);
+
+        banks_client.process_transaction(setup_transaction).await.unwrap();
+
+        // Initialize a USDC-style casino and seed its token bankroll
+        let house_edge = 250; // 2.5%
+        let min_bet = 100_000;
+        let max_bet = 5_000_000;
+
+        let init_instruction = crate::client::initialize_with_mint(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &mint.pubkey(),
+            house_edge,
+            min_bet,
+            max_bet,
+        );
+        let fund_instruction = crate::client::fund_vault_tokens(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &mint.pubkey(),
+            50_000_000,
+        );
+
+        let mut init_transaction = Transaction::new_with_payer(
+            &[init_instruction, fund_instruction],
+            Some(&payer.pubkey()),
+        );
+        init_transaction.sign(&[&payer, &authority], recent_blockhash);
+
+        banks_client.process_transaction(init_transaction).await.unwrap();
+
+        assert_eq!(token_balance(&mut banks_client, vault_token).await, 50_000_000);
+
+        // Place a token bet
+        let bet_amount = 1_000_000;
+
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&crate::test_utils::seed_for(
+                &game_pubkey,
+                &crate::test_utils::CLIENT_SEED,
+                crate::randomness::is_win,
+            )),
+        );
+        let place_bet_instruction = crate::client::place_token_bet(
+            &player.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &mint.pubkey(),
+            game_id,
+            bet_amount,
+            crate::test_utils::CLIENT_SEED,
+            crate::randomness::commit(&crate::test_utils::seed_for(
+                &game_pubkey,
+                &crate::test_utils::CLIENT_SEED,
+                crate::randomness::is_win,
+            )),
+        );
+
+        let mut bet_transaction = Transaction::new_with_payer(
+            &[commitment_instruction, place_bet_instruction],
+            Some(&payer.pubkey()),
+        );
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
+
+        banks_client.process_transaction(bet_transaction).await.unwrap();
+
+        assert_eq!(token_balance(&mut banks_client, player_token).await, 9_000_000);
+        assert_eq!(token_balance(&mut banks_client, vault_token).await, 51_000_000);
+
+        // Settle the game as a win paid from the token vault
+        let win_amount = 1_950_000;
+
+        let settle_instruction = crate::client::settle_token_game(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &game_pubkey,
+            &player.pubkey(),
+            &mint.pubkey(),
+            true,
+            win_amount,
+            crate::test_utils::seed_for(
+                &game_pubkey,
+                &crate::test_utils::CLIENT_SEED,
+                crate::randomness::is_win,
+            ),
+        );
+
+        let mut settle_transaction = Transaction::new_with_payer(
+            &[settle_instruction],
+            Some(&payer.pubkey()),
+        );
+        settle_transaction.sign(&[&payer, &authority], recent_blockhash);
+
+        banks_client.process_transaction(settle_transaction).await.unwrap();
+
+        assert_eq!(token_balance(&mut banks_client, player_token).await, 9_000_000 + win_amount);
+        assert_eq!(token_balance(&mut banks_client, vault_token).await, 51_000_000 - win_amount);
+
+        // The authority can take tokens back out of the bankroll
+        let withdraw_instruction = crate::client::withdraw_vault_tokens(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            &authority.pubkey(),
+            &mint.pubkey(),
+            10_000_000,
+        );
+
+        let mut withdraw_transaction = Transaction::new_with_payer(
+            &[withdraw_instruction],
+            Some(&payer.pubkey()),
+        );
+        withdraw_transaction.sign(&[&payer, &authority], recent_blockhash);
+
+        banks_client.process_transaction(withdraw_transaction).await.unwrap();
+
+        assert_eq!(token_balance(&mut banks_client, authority_token).await, 60_000_000);
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tokio_tests {
     use {
-        crate::{id, CasinoState, GameState, process_instruction},
+        crate::{id, test_utils::*, CasinoState, process_instruction},
         borsh::{BorshDeserialize, BorshSerialize},
         solana_program::{
             instruction::{AccountMeta, Instruction},
@@ -204,7 +204,7 @@ mod tokio_tests {
         
         let game_id = [1u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -252,30 +252,34 @@ mod tokio_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
         // Verify the game account was created with the correct data
-        let game_account = banks_client
-            .get_account(game_pubkey)
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
             .await
-            .unwrap()
             .unwrap();
-        
-        let game_state = GameState::try_from_slice(&game_account.data).unwrap();
         assert_eq!(game_state.player, player.pubkey());
         assert_eq!(game_state.bet_amount, bet_amount);
         assert_eq!(game_state.is_settled, false);
@@ -297,7 +301,7 @@ mod tokio_tests {
         
         let game_id = [2u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -345,26 +349,40 @@ mod tokio_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
         // Settle the game (player wins)
         let is_win = true;
-        let win_amount = bet_amount; // 1:1 payout
-        let result_hash = [3u8; 32];
+        let win_amount = 975_000; // Even odds less the 2.5% house edge
+        
+        // The payout is more than the stake, so the vault needs a bankroll
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -373,11 +391,11 @@ mod tokio_tests {
             &player.pubkey(),
             is_win,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
-            &[settle_instruction],
+            &[fund_vault_instruction, settle_instruction],
             Some(&payer.pubkey()),
         );
         settle_transaction.sign(&[&payer, &authority], recent_blockhash);
@@ -385,17 +403,17 @@ mod tokio_tests {
         banks_client.process_transaction(settle_transaction).await.unwrap();
         
         // Verify the game was settled correctly
-        let game_account = banks_client
-            .get_account(game_pubkey)
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
             .await
-            .unwrap()
             .unwrap();
-        
-        let game_state = GameState::try_from_slice(&game_account.data).unwrap();
         assert_eq!(game_state.is_settled, true);
         assert_eq!(game_state.is_win, is_win);
         assert_eq!(game_state.win_amount, win_amount);
-        assert_eq!(game_state.result_hash, result_hash);
+        assert_eq!(game_state.server_seed, server_seed);
+        assert_eq!(
+            game_state.result_hash,
+            crate::randomness::result_hash(&server_seed, &CLIENT_SEED, &game_pubkey)
+        );
     }
 
     #[tokio::test]
@@ -460,6 +478,7 @@ mod tokio_tests {
             Some(300),
             Some(200_000),
             Some(2_000_000_000),
+            None,
         );
         
         let mut update_transaction = Transaction::new_with_payer(
@@ -557,7 +576,7 @@ mod tokio_tests {
         
         let game_id = [4u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -605,19 +624,27 @@ mod tokio_tests {
         // Try to place a bet with zero amount
         let zero_bet = 0;
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let zero_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             zero_bet,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut zero_bet_transaction = Transaction::new_with_payer(
-            &[zero_bet_instruction],
+            &[commitment_instruction, zero_bet_instruction],
             Some(&payer.pubkey()),
         );
-        zero_bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        zero_bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         // This should fail because bet amount is zero
         let result = banks_client.process_transaction(zero_bet_transaction).await;
@@ -640,7 +667,7 @@ mod tokio_tests {
         
         let game_id = [5u8; 32];
         let (game_pubkey, _) = Pubkey::find_program_address(
-            &[&game_id],
+            &[b"game", casino_state_pubkey.as_ref(), player.pubkey().as_ref(), &game_id],
             &id::id(),
         );
         
@@ -688,26 +715,47 @@ mod tokio_tests {
         // Place a bet
         let bet_amount = 500_000; // 0.0005 SOL
         
+        let server_seed = seed_for(&game_pubkey, &CLIENT_SEED, crate::randomness::is_win);
+        let commitment_instruction = crate::client::post_commitment(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            crate::randomness::commit(&server_seed),
+        );
         let place_bet_instruction = crate::client::place_bet(
             &player.pubkey(),
             &casino_state_pubkey,
             &game_pubkey,
             game_id,
             bet_amount,
+            CLIENT_SEED,
+            crate::randomness::commit(&server_seed),
         );
         
         let mut bet_transaction = Transaction::new_with_payer(
-            &[place_bet_instruction],
+            &[commitment_instruction, place_bet_instruction],
             Some(&payer.pubkey()),
         );
-        bet_transaction.sign(&[&payer, &player], recent_blockhash);
+        bet_transaction.sign(&[&payer, &authority, &player], recent_blockhash);
         
         banks_client.process_transaction(bet_transaction).await.unwrap();
         
-        // With 0% house edge, the max win should be equal to the bet amount
-        // Try to settle with win amount equal to bet amount
-        let win_amount = bet_amount;
-        let result_hash = [6u8; 32];
+        // Fund the house vault so it can cover payouts above the stake
+        let fund_vault_instruction = crate::client::fund_vault(
+            &authority.pubkey(),
+            &casino_state_pubkey,
+            100_000_000,
+        );
+        
+        let mut fund_vault_transaction = Transaction::new_with_payer(
+            &[fund_vault_instruction],
+            Some(&payer.pubkey()),
+        );
+        fund_vault_transaction.sign(&[&payer, &authority], recent_blockhash);
+        
+        banks_client.process_transaction(fund_vault_transaction).await.unwrap();
+        
+        // With 0% house edge a win pays exactly even odds, double the stake
+        let win_amount = 2 * bet_amount;
         
         let settle_instruction = crate::client::settle_game(
             &authority.pubkey(),
@@ -716,7 +764,7 @@ mod tokio_tests {
             &player.pubkey(),
             true,
             win_amount,
-            result_hash,
+            server_seed,
         );
         
         let mut settle_transaction = Transaction::new_with_payer(
@@ -729,13 +777,9 @@ mod tokio_tests {
         banks_client.process_transaction(settle_transaction).await.unwrap();
         
         // Verify the game was settled correctly
-        let game_account = banks_client
-            .get_account(game_pubkey)
+        let game_state = crate::client::fetch_game_state(&mut banks_client, &game_pubkey)
             .await
-            .unwrap()
             .unwrap();
-        
-        let game_state = GameState::try_from_slice(&game_account.data).unwrap();
         assert_eq!(game_state.is_settled, true);
         assert_eq!(game_state.is_win, true);
         assert_eq!(game_state.win_amount, win_amount);